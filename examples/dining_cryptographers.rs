@@ -149,6 +149,8 @@ impl Round<DinerId> for Round1 {
         CommunicationInfo {
             message_destinations,
             expecting_messages_from,
+            // Everyone must send their messages
+            optional_messages_from: BTreeSet::new(),
             // Participate in echo broadcasts
             echo_round_participation: EchoRoundParticipation::Default,
         }
@@ -242,6 +244,7 @@ impl Round<DinerId> for Round2 {
         CommunicationInfo {
             message_destinations: everyone_else.clone(),
             expecting_messages_from: everyone_else,
+            optional_messages_from: BTreeSet::new(),
             echo_round_participation: EchoRoundParticipation::Default,
         }
     }
@@ -341,10 +344,10 @@ impl EntryPoint<DinerId> for DiningEntryPoint {
         _shared_randomness: &[u8],
         id: &DinerId,
     ) -> Result<BoxedRound<DinerId, Self::Protocol>, LocalError> {
-        let paid = id.id() == 0 && rng.next_u32() % 2 == 0;
+        let paid = id.id() == 0 && rng.next_u32() & 1 == 0;
         let round = Round1 {
             diner_id: *id,
-            own_toss: rng.next_u32() % 2 == 0,
+            own_toss: rng.next_u32() & 1 == 0,
             paid,
         };
         trace!(
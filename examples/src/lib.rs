@@ -5,3 +5,5 @@ pub mod simple_chain;
 
 #[cfg(test)]
 mod simple_malicious;
+#[cfg(test)]
+mod simple_tampering;
@@ -5,10 +5,10 @@ use manul::protocol::{
     Artifact, BoxedFormat, BoxedRound, CommunicationInfo, DirectMessage, EchoBroadcast, EntryPoint, FinalizeOutcome,
     LocalError, MessageValidationError, NormalBroadcast, PartyId, Payload, Protocol, ProtocolError, ProtocolMessage,
     ProtocolMessagePart, ProtocolValidationError, ReceiveError, RequiredMessageParts, RequiredMessages, Round, RoundId,
-    TransitionInfo,
+    TransitionInfo, VerifiableProtocol,
 };
 use rand_core::CryptoRngCore;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use tracing::debug;
 
 #[derive(Debug)]
@@ -61,8 +61,8 @@ impl<Id> ProtocolError<Id> for SimpleProtocolError {
 
                 // Deserialize the echos
                 let _r1_echos = r1_echos_serialized
-                    .iter()
-                    .map(|(_id, echo)| echo.deserialize::<Round1Echo>(format))
+                    .values()
+                    .map(|echo| echo.deserialize::<Round1Echo>(format))
                     .collect::<Result<Vec<_>, _>>()?;
 
                 // Message contents would be checked here
@@ -72,6 +72,18 @@ impl<Id> ProtocolError<Id> for SimpleProtocolError {
     }
 }
 
+impl<Id> VerifiableProtocol<Id> for SimpleProtocol {
+    const NAME: &'static str = "simple";
+    const VERSION: u32 = 1;
+
+    fn deserialize_associated_data<'de, D>(deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        <()>::deserialize(deserializer)
+    }
+}
+
 impl<Id> Protocol<Id> for SimpleProtocol {
     type Result = u8;
     type ProtocolError = SimpleProtocolError;
@@ -132,8 +144,8 @@ pub(crate) struct Round1Message {
 }
 
 #[derive(Serialize, Deserialize)]
-struct Round1Echo {
-    my_position: u8,
+pub(crate) struct Round1Echo {
+    pub(crate) my_position: u8,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -385,10 +397,17 @@ mod tests {
     use alloc::collections::BTreeSet;
 
     use manul::{
-        dev::{run_sync, BinaryFormat, TestSessionParams, TestSigner},
+        dev::{
+            proptest::{entry_points, message_schedules, signers},
+            run_sync, run_sync_configured, run_sync_scheduled, run_sync_with_config, BinaryFormat, MessageInterceptor,
+            MessageSchedule, PassThrough, TestSessionParams, TestSigner, TestVerifier,
+        },
+        protocol::{LocalError, RoundId},
+        session::{BroadcastConsistency, Message, PartyEncoding, SessionConfig},
         signature::Keypair,
     };
-    use rand_core::OsRng;
+    use proptest::{prelude::*, strategy::Just};
+    use rand_core::{CryptoRngCore, OsRng};
     use test_log::test;
 
     use super::SimpleProtocolEntryPoint;
@@ -414,4 +433,177 @@ mod tests {
             assert_eq!(result, 6); // (0 + 1 + 2) * 2
         }
     }
+
+    #[test]
+    fn round_with_party_indices() {
+        let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+        let all_ids = signers
+            .iter()
+            .map(|signer| signer.verifying_key())
+            .collect::<BTreeSet<_>>();
+        let entry_points = signers
+            .into_iter()
+            .map(|signer| (signer, SimpleProtocolEntryPoint::new(all_ids.clone())))
+            .collect::<Vec<_>>();
+
+        let config = SessionConfig::default().with_party_encoding(PartyEncoding::indexed(all_ids).unwrap());
+        let results = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
+            .unwrap()
+            .results()
+            .unwrap();
+
+        for (_id, result) in results {
+            assert_eq!(result, 6); // (0 + 1 + 2) * 2
+        }
+    }
+
+    #[test]
+    fn round_with_merkle_echo() {
+        let signers = (0..4).map(TestSigner::new).collect::<Vec<_>>();
+        let all_ids = signers
+            .iter()
+            .map(|signer| signer.verifying_key())
+            .collect::<BTreeSet<_>>();
+        let entry_points = signers
+            .into_iter()
+            .map(|signer| (signer, SimpleProtocolEntryPoint::new(all_ids.clone())))
+            .collect::<Vec<_>>();
+
+        let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::MerkleEcho);
+        let results = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
+            .unwrap()
+            .results()
+            .unwrap();
+
+        for (_id, result) in results {
+            assert_eq!(result, 12); // (0 + 1 + 2 + 3) * 2
+        }
+    }
+
+    #[test]
+    fn round_with_bracha() {
+        let signers = (0..4).map(TestSigner::new).collect::<Vec<_>>();
+        let all_ids = signers
+            .iter()
+            .map(|signer| signer.verifying_key())
+            .collect::<BTreeSet<_>>();
+        let entry_points = signers
+            .into_iter()
+            .map(|signer| (signer, SimpleProtocolEntryPoint::new(all_ids.clone())))
+            .collect::<Vec<_>>();
+
+        let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::Bracha);
+        let results = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
+            .unwrap()
+            .results()
+            .unwrap();
+
+        for (_id, result) in results {
+            assert_eq!(result, 12); // (0 + 1 + 2 + 3) * 2
+        }
+    }
+
+    // Records the rounds of all the sent messages.
+    struct RoundRecorder(BTreeSet<RoundId>);
+
+    impl MessageInterceptor<TestSessionParams<BinaryFormat>> for RoundRecorder {
+        fn intercept(
+            &mut self,
+            _rng: &mut dyn CryptoRngCore,
+            _from: &TestVerifier,
+            _to: &TestVerifier,
+            message: Message<TestVerifier>,
+        ) -> Result<Vec<Message<TestVerifier>>, LocalError> {
+            self.0.insert(message.round_id().clone());
+            Ok(vec![message])
+        }
+    }
+
+    #[test]
+    fn round_with_trusted_broadcast() {
+        let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+        let all_ids = signers
+            .iter()
+            .map(|signer| signer.verifying_key())
+            .collect::<BTreeSet<_>>();
+        let entry_points = signers
+            .into_iter()
+            .map(|signer| (signer, SimpleProtocolEntryPoint::new(all_ids.clone())))
+            .collect::<Vec<_>>();
+
+        let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::Trusted);
+        let mut recorder = RoundRecorder(BTreeSet::new());
+        let results = run_sync_configured::<_, TestSessionParams<BinaryFormat>>(
+            &mut OsRng,
+            entry_points,
+            config,
+            MessageSchedule::random(&mut OsRng),
+            &mut recorder,
+        )
+        .unwrap()
+        .results()
+        .unwrap();
+
+        for (_id, result) in results {
+            assert_eq!(result, 6); // (0 + 1 + 2) * 2
+        }
+
+        // No echo rounds were inserted
+        assert_eq!(recorder.0, BTreeSet::from([RoundId::new(1), RoundId::new(2)]));
+    }
+
+    #[test]
+    fn round_with_piggybacked_echo() {
+        let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+        let all_ids = signers
+            .iter()
+            .map(|signer| signer.verifying_key())
+            .collect::<BTreeSet<_>>();
+        let entry_points = signers
+            .into_iter()
+            .map(|signer| (signer, SimpleProtocolEntryPoint::new(all_ids.clone())))
+            .collect::<Vec<_>>();
+
+        let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::PiggybackedEcho);
+        let mut recorder = RoundRecorder(BTreeSet::new());
+        let results = run_sync_configured::<_, TestSessionParams<BinaryFormat>>(
+            &mut OsRng,
+            entry_points,
+            config,
+            MessageSchedule::random(&mut OsRng),
+            &mut recorder,
+        )
+        .unwrap()
+        .results()
+        .unwrap();
+
+        for (_id, result) in results {
+            assert_eq!(result, 6); // (0 + 1 + 2) * 2
+        }
+
+        // The echo round messages for round 1 were sent along with the round 2 messages
+        assert_eq!(recorder.0, BTreeSet::from([RoundId::new(1), RoundId::new(2)]));
+    }
+
+    proptest! {
+        #[test]
+        fn honest_parties_agree(
+            entry_points in entry_points(signers(2..=6), |ids, _id| Just(SimpleProtocolEntryPoint::new(ids.clone()))),
+            schedule in message_schedules(),
+        ) {
+            let parties = entry_points.len() as u8;
+            let execution_result = run_sync_scheduled::<_, TestSessionParams<BinaryFormat>>(
+                &mut OsRng,
+                entry_points,
+                schedule,
+                &mut PassThrough,
+            )
+            .unwrap();
+
+            let result = execution_result.check_agreement(&BTreeSet::new()).unwrap();
+            // Each party's position is counted in both rounds
+            prop_assert_eq!(*result, parties * (parties - 1));
+            prop_assert!(execution_result.check_no_false_blame(&BTreeSet::new()).is_ok());
+        }
+    }
 }
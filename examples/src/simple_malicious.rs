@@ -3,12 +3,17 @@ use core::fmt::Debug;
 
 use manul::{
     combinators::misbehave::{Misbehaving, MisbehavingEntryPoint},
-    dev::{run_sync, BinaryFormat, TestSessionParams, TestSigner},
+    dev::{
+        proptest::{message_schedules, signers},
+        run_sync, run_sync_scheduled, run_sync_with_config, BinaryFormat, PassThrough, TestSessionParams, TestSigner,
+    },
     protocol::{
         Artifact, BoxedFormat, BoxedRound, DirectMessage, EntryPoint, LocalError, PartyId, ProtocolMessagePart,
     },
+    session::{BroadcastConsistency, PartyEncoding, SessionConfig},
     signature::Keypair,
 };
+use proptest::{prelude::*, strategy::Just};
 use rand_core::{CryptoRngCore, OsRng};
 use test_log::test;
 
@@ -185,3 +190,244 @@ fn attributable_failure_round2() {
     assert!(report1.provable_errors[&v0].verify(&()).is_ok());
     assert!(report2.provable_errors[&v0].verify(&()).is_ok());
 }
+
+#[test]
+fn attributable_failure_round2_with_party_indices() {
+    // The evidence includes the echo round messages referring to the parties by their indices
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+
+    let entry_points = signers
+        .iter()
+        .enumerate()
+        .map(|(idx, signer)| {
+            let behavior = if idx == 0 {
+                Some(Behavior::AttributableFailureRound2)
+            } else {
+                None
+            };
+
+            let entry_point = MaliciousEntryPoint::new(SimpleProtocolEntryPoint::new(all_ids.clone()), behavior);
+            (*signer, entry_point)
+        })
+        .collect::<Vec<_>>();
+
+    let config = SessionConfig::default().with_party_encoding(PartyEncoding::indexed(all_ids).unwrap());
+    let mut reports = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
+        .unwrap()
+        .reports;
+
+    let v0 = signers[0].verifying_key();
+    let v1 = signers[1].verifying_key();
+    let v2 = signers[2].verifying_key();
+
+    let report1 = reports.remove(&v1).unwrap();
+    let report2 = reports.remove(&v2).unwrap();
+
+    assert!(report1.provable_errors[&v0].verify(&()).is_ok());
+    assert!(report2.provable_errors[&v0].verify(&()).is_ok());
+}
+
+#[test]
+fn attributable_failure_round2_with_merkle_echo() {
+    // The evidence includes the Merkle echo round message committing to the echo broadcasts
+    let signers = (0..4).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+
+    let entry_points = signers
+        .iter()
+        .enumerate()
+        .map(|(idx, signer)| {
+            let behavior = if idx == 0 {
+                Some(Behavior::AttributableFailureRound2)
+            } else {
+                None
+            };
+
+            let entry_point = MaliciousEntryPoint::new(SimpleProtocolEntryPoint::new(all_ids.clone()), behavior);
+            (*signer, entry_point)
+        })
+        .collect::<Vec<_>>();
+
+    let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::MerkleEcho);
+    let reports = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
+        .unwrap()
+        .reports;
+
+    let v0 = signers[0].verifying_key();
+    for (id, report) in reports {
+        if id != v0 {
+            assert!(report.provable_errors[&v0].verify(&()).is_ok());
+        }
+    }
+}
+
+#[test]
+fn attributable_failure_round2_with_piggybacked_echo() {
+    // The evidence includes the echo round message attached to the round 2 message
+    let signers = (0..4).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+
+    let entry_points = signers
+        .iter()
+        .enumerate()
+        .map(|(idx, signer)| {
+            let behavior = if idx == 0 {
+                Some(Behavior::AttributableFailureRound2)
+            } else {
+                None
+            };
+
+            let entry_point = MaliciousEntryPoint::new(SimpleProtocolEntryPoint::new(all_ids.clone()), behavior);
+            (*signer, entry_point)
+        })
+        .collect::<Vec<_>>();
+
+    let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::PiggybackedEcho);
+    let reports = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
+        .unwrap()
+        .reports;
+
+    let v0 = signers[0].verifying_key();
+    for (id, report) in reports {
+        if id != v0 {
+            assert!(report.provable_errors[&v0].verify(&()).is_ok());
+        }
+    }
+}
+
+#[test]
+fn attributable_failure_round2_with_bracha() {
+    // The Bracha echo round may be finalized without the message from the malicious node,
+    // in which case its error in round 2 cannot be proven.
+    let signers = (0..4).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+
+    let entry_points = signers
+        .iter()
+        .enumerate()
+        .map(|(idx, signer)| {
+            let behavior = if idx == 0 {
+                Some(Behavior::AttributableFailureRound2)
+            } else {
+                None
+            };
+
+            let entry_point = MaliciousEntryPoint::new(SimpleProtocolEntryPoint::new(all_ids.clone()), behavior);
+            (*signer, entry_point)
+        })
+        .collect::<Vec<_>>();
+
+    let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::Bracha);
+    let reports = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
+        .unwrap()
+        .reports;
+
+    let v0 = signers[0].verifying_key();
+    for (id, report) in reports {
+        if id != v0 {
+            if let Some(evidence) = report.provable_errors.get(&v0) {
+                assert!(evidence.verify(&()).is_ok());
+            } else {
+                assert!(report.unprovable_errors.contains_key(&v0));
+            }
+        }
+    }
+}
+
+#[test]
+fn attributable_failure_with_trusted_broadcast() {
+    // The errors that only involve the messages of the malicious node are still provable,
+    // but the ones that need the echo broadcasts it received are not.
+    for (behavior, provable) in [
+        (Behavior::AttributableFailure, true),
+        (Behavior::AttributableFailureRound2, false),
+    ] {
+        let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+        let all_ids = signers
+            .iter()
+            .map(|signer| signer.verifying_key())
+            .collect::<BTreeSet<_>>();
+
+        let entry_points = signers
+            .iter()
+            .enumerate()
+            .map(|(idx, signer)| {
+                let behavior = if idx == 0 { Some(behavior) } else { None };
+                let entry_point = MaliciousEntryPoint::new(SimpleProtocolEntryPoint::new(all_ids.clone()), behavior);
+                (*signer, entry_point)
+            })
+            .collect::<Vec<_>>();
+
+        let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::Trusted);
+        let reports = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
+            .unwrap()
+            .reports;
+
+        let v0 = signers[0].verifying_key();
+        for (id, report) in reports {
+            if id != v0 {
+                if provable {
+                    assert!(report.provable_errors[&v0].verify(&()).is_ok());
+                } else {
+                    assert!(report.provable_errors.is_empty());
+                    assert!(report.unprovable_errors.contains_key(&v0));
+                }
+            }
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn malicious_party_is_blamed(
+        (signers, malicious_idx) in signers(3..=6).prop_flat_map(|signers| {
+            let parties = signers.len();
+            (Just(signers), 0..parties)
+        }),
+        behavior in prop_oneof![
+            Just(Behavior::SerializedGarbage),
+            Just(Behavior::AttributableFailure),
+            Just(Behavior::AttributableFailureRound2),
+        ],
+        schedule in message_schedules(),
+    ) {
+        let all_ids = signers
+            .iter()
+            .map(|signer| signer.verifying_key())
+            .collect::<BTreeSet<_>>();
+        let malicious = BTreeSet::from([signers[malicious_idx].verifying_key()]);
+
+        let entry_points = signers
+            .iter()
+            .enumerate()
+            .map(|(idx, signer)| {
+                let behavior = if idx == malicious_idx { Some(behavior) } else { None };
+                let entry_point = MaliciousEntryPoint::new(SimpleProtocolEntryPoint::new(all_ids.clone()), behavior);
+                (*signer, entry_point)
+            })
+            .collect::<Vec<_>>();
+
+        let execution_result = run_sync_scheduled::<_, TestSessionParams<BinaryFormat>>(
+            &mut OsRng,
+            entry_points,
+            schedule,
+            &mut PassThrough,
+        )
+        .unwrap();
+
+        prop_assert_eq!(execution_result.check_attribution(&malicious), Ok(()));
+    }
+}
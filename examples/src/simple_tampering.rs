@@ -0,0 +1,644 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use manul::{
+    dev::{
+        run_sync_configured, BinaryFormat, ExecutionResult, MessageInterceptor, MessageSchedule, TestSessionParams,
+        TestSigner, TestVerifier,
+    },
+    protocol::{LocalError, RemoteError, RemoteErrorKind, RoundId},
+    session::{
+        BroadcastConsistency, CanFinalize, ErrorBudgetBanPolicy, Message, MessageLimits, OffenseKind,
+        PreprocessOutcome, RoundOutcome, Session, SessionConfig, SessionId, SessionOutcome, SessionReport, WireFormat,
+    },
+    signature::Keypair,
+};
+use rand_core::{CryptoRngCore, OsRng};
+use test_log::test;
+
+use crate::simple::{Round1Echo, SimpleProtocol, SimpleProtocolEntryPoint};
+
+type SP = TestSessionParams<BinaryFormat>;
+
+const OVERSIZED_PAYLOAD_LEN: usize = 1024;
+
+#[derive(Debug, Clone, Copy)]
+enum Tampering {
+    /// Bundle the echo broadcast from round 1 with the round 2 message.
+    MixedMetadata,
+    /// Sign all the message parts with a key of a party outside of the session.
+    ForgedSignature,
+    /// Replace signatures with garbage.
+    InvalidSignature,
+    /// Deliver each message preceded by a copy with the signatures replaced with garbage.
+    GarbledCopy,
+    /// Deliver each round 1 message twice.
+    Replay,
+    /// Deliver each round 1 message twice, re-signing the parts of the copy.
+    ResignedReplay,
+    /// Deliver each round 1 message followed by a copy with the direct message intended for another destination.
+    ConflictingDirectMessage,
+    /// Deliver each round 1 message followed by a copy with a different (correctly signed) echo broadcast.
+    ConflictingEchoBroadcast,
+    /// Do not deliver round 2 messages.
+    Drop,
+    /// Send a different (correctly signed) echo broadcast to the first destination in round 1.
+    Equivocation,
+    /// Do not deliver any messages of the rounds inserted by the session after round 1.
+    DropEchoes,
+    /// Replace the echo broadcast in round 1 with a large (correctly signed) one.
+    Oversized,
+}
+
+/// Tampers with the messages sent by `sender`, leaving everyone else's intact.
+struct Tamperer {
+    signer: TestSigner,
+    sender: TestVerifier,
+    tampering: Tampering,
+    // The destination of the tampered message, for the tamperings that only affect one destination.
+    victim: Option<TestVerifier>,
+    round1_messages: BTreeMap<TestVerifier, Message<TestVerifier>>,
+}
+
+impl Tamperer {
+    fn new(signer: TestSigner, tampering: Tampering) -> Self {
+        Self {
+            signer,
+            sender: signer.verifying_key(),
+            tampering,
+            victim: None,
+            round1_messages: BTreeMap::new(),
+        }
+    }
+}
+
+impl MessageInterceptor<SP> for Tamperer {
+    fn intercept(
+        &mut self,
+        mut rng: &mut dyn CryptoRngCore,
+        from: &TestVerifier,
+        to: &TestVerifier,
+        message: Message<TestVerifier>,
+    ) -> Result<Vec<Message<TestVerifier>>, LocalError> {
+        if from != &self.sender {
+            return Ok(vec![message]);
+        }
+
+        let is_round1 = message.round_id() == &RoundId::new(1);
+        let is_round2 = message.round_id() == &RoundId::new(2);
+        // Any other round 1 message, with a direct message intended for another destination.
+        let other_round1_message = self
+            .round1_messages
+            .iter()
+            .find(|(destination, _)| *destination != to)
+            .map(|(_, message)| message.clone());
+        if is_round1 {
+            self.round1_messages.insert(*to, message.clone());
+        }
+
+        let messages = match self.tampering {
+            Tampering::MixedMetadata if is_round2 => {
+                let round1_message = self
+                    .round1_messages
+                    .get(to)
+                    .ok_or_else(|| LocalError::new("Round 1 message has not been sent"))?;
+                vec![message.with_echo_broadcast_of(round1_message)]
+            }
+            Tampering::ForgedSignature => vec![message.resigned::<SP>(&mut rng, &TestSigner::new(255))?],
+            Tampering::InvalidSignature => vec![message.with_invalid_signatures()],
+            Tampering::GarbledCopy => vec![message.clone().with_invalid_signatures(), message],
+            Tampering::Replay if is_round1 => vec![message.clone(), message],
+            Tampering::ResignedReplay if is_round1 => {
+                vec![message.clone(), message.resigned::<SP>(&mut rng, &self.signer)?]
+            }
+            Tampering::ConflictingDirectMessage if is_round1 => match other_round1_message {
+                Some(other) => vec![message.clone(), message.with_direct_message_of(&other)],
+                None => vec![message],
+            },
+            Tampering::ConflictingEchoBroadcast if is_round1 => {
+                let echo = Round1Echo { my_position: u8::MAX };
+                let conflicting = message
+                    .clone()
+                    .with_echo_broadcast_payload::<SP, _>(&mut rng, &self.signer, echo)?;
+                vec![message, conflicting]
+            }
+            Tampering::Drop if is_round2 => Vec::new(),
+            Tampering::DropEchoes if !is_round1 && !is_round2 => Vec::new(),
+            Tampering::Equivocation if is_round1 && self.round1_messages.len() == 1 => {
+                self.victim = Some(*to);
+                let echo = Round1Echo { my_position: u8::MAX };
+                vec![message.with_echo_broadcast_payload::<SP, _>(&mut rng, &self.signer, echo)?]
+            }
+            Tampering::Oversized if is_round1 => {
+                let echo = vec![0u8; OVERSIZED_PAYLOAD_LEN];
+                vec![message.with_echo_broadcast_payload::<SP, _>(&mut rng, &self.signer, echo)?]
+            }
+            _ => vec![message],
+        };
+        Ok(messages)
+    }
+}
+
+fn run_with_tampering(tampering: Tampering) -> (TestVerifier, ExecutionResult<SimpleProtocol, SP>) {
+    let (tamperer, execution_result) = run_with_tampering_and_config(tampering, SessionConfig::default());
+    (tamperer.sender, execution_result)
+}
+
+fn run_with_tampering_and_config(
+    tampering: Tampering,
+    config: SessionConfig<TestVerifier>,
+) -> (Tamperer, ExecutionResult<SimpleProtocol, SP>) {
+    // With 4 nodes the Bracha broadcast can tolerate one faulty node.
+    run_with_tampering_on_nodes(tampering, config, 4)
+}
+
+fn make_entry_points(node_count: u8) -> Vec<(TestSigner, SimpleProtocolEntryPoint<TestVerifier>)> {
+    let signers = (0..node_count).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+
+    signers
+        .iter()
+        .map(|signer| (*signer, SimpleProtocolEntryPoint::new(all_ids.clone())))
+        .collect()
+}
+
+fn run_with_tampering_on_nodes(
+    tampering: Tampering,
+    config: SessionConfig<TestVerifier>,
+    node_count: u8,
+) -> (Tamperer, ExecutionResult<SimpleProtocol, SP>) {
+    let entry_points = make_entry_points(node_count);
+    let mut tamperer = Tamperer::new(TestSigner::new(0), tampering);
+    let schedule = MessageSchedule::random(&mut OsRng);
+    let execution_result =
+        run_sync_configured::<_, SP>(&mut OsRng, entry_points, config, schedule, &mut tamperer).unwrap();
+    (tamperer, execution_result)
+}
+
+fn check_unprovable_error(tampering: Tampering, expected_kind: RemoteErrorKind, expected_description: &str) {
+    check_unprovable_error_with_config(tampering, SessionConfig::default(), expected_kind, expected_description)
+}
+
+fn check_unprovable_error_with_config(
+    tampering: Tampering,
+    config: SessionConfig<TestVerifier>,
+    expected_kind: RemoteErrorKind,
+    expected_description: &str,
+) {
+    let (tamperer, execution_result) = run_with_tampering_and_config(tampering, config);
+    let sender = tamperer.sender;
+    for (id, report) in execution_result.reports {
+        if id == sender {
+            continue;
+        }
+        let error = &report.unprovable_errors[&sender];
+        assert!(
+            error.message().contains(expected_description),
+            "Unexpected error description: {error}"
+        );
+        assert_eq!(error.kind(), expected_kind);
+        assert!(report.provable_errors.is_empty());
+
+        // The kind is preserved when the error is serialized
+        let serialized = BinaryFormat::serialize(error).unwrap();
+        let deserialized = BinaryFormat::deserialize::<RemoteError>(&serialized).unwrap();
+        assert_eq!(deserialized.kind(), expected_kind);
+    }
+}
+
+#[test]
+fn mixed_metadata() {
+    check_unprovable_error(
+        Tampering::MixedMetadata,
+        RemoteErrorKind::MismatchedMetadata,
+        "Mismatched metadata",
+    );
+}
+
+#[test]
+fn forged_signature() {
+    check_unprovable_error(
+        Tampering::ForgedSignature,
+        RemoteErrorKind::SignatureMismatch,
+        "Message verification failed",
+    );
+}
+
+#[test]
+fn invalid_signature() {
+    check_unprovable_error(
+        Tampering::InvalidSignature,
+        RemoteErrorKind::InvalidSignature,
+        "The signature could not be deserialized",
+    );
+}
+
+#[test]
+fn oversized_message() {
+    let limits = MessageLimits::default().with_max_part_size(OVERSIZED_PAYLOAD_LEN / 2);
+    check_unprovable_error_with_config(
+        Tampering::Oversized,
+        SessionConfig::default().with_message_limits(limits),
+        RemoteErrorKind::LimitExceeded,
+        "The message part size",
+    );
+}
+
+#[test]
+fn exceeded_sender_quota() {
+    let limits = MessageLimits::default().with_max_bytes_per_sender(OVERSIZED_PAYLOAD_LEN / 2);
+    check_unprovable_error_with_config(
+        Tampering::Oversized,
+        SessionConfig::default().with_message_limits(limits),
+        RemoteErrorKind::LimitExceeded,
+        "The total size of the messages from the sender",
+    );
+}
+
+/// Executes the session round by round: every node sends all its messages for the round
+/// before any of them are delivered, and every node finalizes the round once all of them are.
+///
+/// If `redeliver` is `true`, every message is delivered twice, and the copy is expected to be ignored.
+fn run_in_lockstep(config: SessionConfig<TestVerifier>, redeliver: bool) -> Vec<SessionReport<SimpleProtocol, SP>> {
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+    let mut sessions = make_entry_points(4)
+        .into_iter()
+        .map(|(signer, entry_point)| {
+            Session::<_, SP>::new_with_config(&mut OsRng, session_id.clone(), signer, entry_point, config.clone())
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let mut reports = Vec::new();
+    while !sessions.is_empty() {
+        let mut accums = sessions
+            .iter()
+            .map(|session| session.make_accumulator())
+            .collect::<Vec<_>>();
+
+        let mut messages = Vec::new();
+        for (session, accum) in sessions.iter().zip(accums.iter_mut()) {
+            for destination in session.message_destinations() {
+                let (message, artifact) = session.make_message(&mut OsRng, destination).unwrap();
+                session.add_artifact(accum, artifact).unwrap();
+                messages.push((session.verifier(), *destination, message));
+            }
+        }
+
+        for (from, to, message) in messages {
+            let Some(idx) = sessions.iter().position(|session| session.verifier() == to) else {
+                continue;
+            };
+            let session = &sessions[idx];
+            let copy = redeliver.then(|| message.clone());
+            if let Some(verified) = session
+                .preprocess_message(&mut accums[idx], &from, message)
+                .unwrap()
+                .ok()
+            {
+                let processed = session.process_message(verified);
+                session.add_processed_message(&mut accums[idx], processed).unwrap();
+            }
+            if let Some(copy) = copy {
+                let outcome = session.preprocess_message(&mut accums[idx], &from, copy).unwrap();
+                assert!(matches!(outcome, PreprocessOutcome::Duplicate));
+            }
+        }
+
+        let mut next_sessions = Vec::new();
+        for (session, accum) in sessions.into_iter().zip(accums) {
+            if !matches!(session.can_finalize(&accum), CanFinalize::Yes) {
+                reports.push(session.terminate_due_to_errors(accum).unwrap());
+                continue;
+            }
+            match session.finalize_round(&mut OsRng, accum).unwrap() {
+                RoundOutcome::Finished(report) => reports.push(report),
+                RoundOutcome::AnotherRound {
+                    session,
+                    cached_messages,
+                } => {
+                    assert!(cached_messages.is_empty());
+                    next_sessions.push(session);
+                }
+            }
+        }
+        sessions = next_sessions;
+    }
+
+    reports
+}
+
+fn run_with_quota(quota: usize, redeliver: bool) -> Vec<SessionReport<SimpleProtocol, SP>> {
+    let limits = MessageLimits::default().with_max_bytes_per_sender(quota);
+    run_in_lockstep(SessionConfig::default().with_message_limits(limits), redeliver)
+}
+
+/// Finds the smallest quota that allows the session to finish.
+fn smallest_sufficient_quota() -> usize {
+    let (mut too_small, mut enough) = (0, 4096);
+    while enough - too_small > 1 {
+        let quota = (too_small + enough) / 2;
+        if run_with_quota(quota, false)
+            .iter()
+            .all(|report| matches!(report.outcome, SessionOutcome::Result(_)))
+        {
+            enough = quota;
+        } else {
+            too_small = quota;
+        }
+    }
+    enough
+}
+
+#[test]
+fn sender_quota_spans_rounds() {
+    let enough = smallest_sufficient_quota();
+
+    // The sizes of the messages vary slightly between executions, so we leave some margin.
+    // The quota is still much larger than the messages sent in any single round (the largest are the echo round ones),
+    // so if it applied to each round separately, the session would finish.
+    // Since it applies to the whole session, it is exceeded in the last round.
+    for report in run_with_quota(enough - 16, false) {
+        assert!(!matches!(report.outcome, SessionOutcome::Result(_)));
+        let offenses = report.offenses.values().flatten().collect::<Vec<_>>();
+        assert!(!offenses.is_empty());
+        for offense in offenses {
+            assert_eq!(offense.kind, OffenseKind::Unprovable(RemoteErrorKind::LimitExceeded));
+            assert_eq!(offense.round_id, RoundId::new(2));
+        }
+    }
+}
+
+#[test]
+fn redeliveries_do_not_count_towards_sender_quota() {
+    let enough = smallest_sufficient_quota();
+
+    // Leave some margin, since the sizes of the messages vary slightly between executions.
+    // It is still smaller than the size of any message, so counting the copies would exceed the quota.
+    for report in run_with_quota(enough + 16, true) {
+        assert!(matches!(report.outcome, SessionOutcome::Result(_)));
+        assert!(report.offenses.is_empty());
+    }
+}
+
+#[test]
+fn garbled_copy() {
+    // By default, the sender is banned on the first error
+    check_unprovable_error(
+        Tampering::GarbledCopy,
+        RemoteErrorKind::InvalidSignature,
+        "The signature could not be deserialized",
+    );
+}
+
+#[test]
+fn garbled_copy_within_error_budget() {
+    // Each node receives one garbled copy in each of the three rounds (round 1, its echo round, and round 2),
+    // so they fit in the budget.
+    let config = SessionConfig::default().with_ban_policy(ErrorBudgetBanPolicy::new(3));
+    let (tamperer, execution_result) = run_with_tampering_and_config(Tampering::GarbledCopy, config);
+    let sender = tamperer.sender;
+    for (id, report) in execution_result.reports {
+        assert!(report.unprovable_errors.is_empty());
+        assert!(report.provable_errors.is_empty());
+        assert!(matches!(report.outcome, SessionOutcome::Result(_)));
+
+        // The errors are still recorded as offenses
+        if id != sender {
+            let offenses = &report.offenses[&sender];
+            assert_eq!(offenses.len(), 3);
+            for offense in offenses {
+                assert_eq!(offense.kind, OffenseKind::Unprovable(RemoteErrorKind::InvalidSignature));
+                assert!(offense.evidence.is_none());
+            }
+        }
+    }
+}
+
+fn check_replay(tampering: Tampering) {
+    let (_sender, execution_result) = run_with_tampering(tampering);
+    for report in execution_result.reports.into_values() {
+        // Depending on the delivery order, the copy arrives while the original is being processed,
+        // or after the receiver has moved on to the next round. Either way, it is ignored.
+        assert!(report.unprovable_errors.is_empty());
+        assert!(report.provable_errors.is_empty());
+        assert!(matches!(report.outcome, SessionOutcome::Result(_)));
+    }
+}
+
+#[test]
+fn replayed_message() {
+    check_replay(Tampering::Replay);
+}
+
+#[test]
+fn resigned_replayed_message() {
+    check_replay(Tampering::ResignedReplay);
+}
+
+#[test]
+fn conflicting_direct_messages() {
+    let (sender, execution_result) = run_with_tampering(Tampering::ConflictingDirectMessage);
+    let mut conflicts = 0;
+    for (id, report) in execution_result.reports {
+        if id != sender {
+            assert!(report.unprovable_errors.keys().all(|id| id == &sender));
+            assert!(report.provable_errors.is_empty());
+            if let Some(error) = report.unprovable_errors.get(&sender) {
+                assert_eq!(error.kind(), RemoteErrorKind::ConflictingMessages);
+                let error = error.to_string();
+                assert!(
+                    error.contains("conflicting messages"),
+                    "Unexpected error description: {error}"
+                );
+                conflicts += 1;
+            }
+        }
+    }
+    // The first destination does not receive a conflicting message.
+    assert_eq!(conflicts, 2);
+}
+
+#[test]
+fn conflicting_echo_broadcasts() {
+    let (sender, execution_result) = run_with_tampering(Tampering::ConflictingEchoBroadcast);
+    for (id, report) in execution_result.reports {
+        if id != sender {
+            let evidence = &report.provable_errors[&sender];
+            assert_eq!(evidence.guilty_party(), &sender);
+            assert!(evidence.verify(&()).is_ok());
+
+            let offense = &report.offenses[&sender][0];
+            assert_eq!(offense.kind, OffenseKind::Provable);
+            assert_eq!(offense.round_id, RoundId::new(1));
+            assert!(offense.evidence.as_ref().unwrap().verify(&()).is_ok());
+        }
+    }
+}
+
+#[test]
+fn dropped_message() {
+    let (sender, execution_result) = run_with_tampering(Tampering::Drop);
+    for (id, report) in execution_result.reports {
+        if id != sender {
+            assert!(!matches!(report.outcome, SessionOutcome::Result(_)));
+            assert_eq!(report.missing_messages[&RoundId::new(2)], BTreeSet::from([sender]));
+            assert!(report.unprovable_errors.is_empty());
+        }
+    }
+}
+
+fn check_equivocation(config: SessionConfig<TestVerifier>) {
+    check_equivocation_on_nodes(config, 4)
+}
+
+fn check_equivocation_on_nodes(config: SessionConfig<TestVerifier>, node_count: u8) {
+    let (tamperer, execution_result) = run_with_tampering_on_nodes(Tampering::Equivocation, config, node_count);
+    let sender = tamperer.sender;
+    execution_result
+        .check_no_false_blame(&BTreeSet::from([sender]))
+        .unwrap();
+    for (id, report) in execution_result.reports {
+        if id != sender {
+            assert!(!matches!(report.outcome, SessionOutcome::Result(_)));
+            // The evidence comes from the node that echoed the broadcast,
+            // but it proves the fault of the original sender, and is registered under it.
+            assert!(report.provable_errors.keys().all(|blamed| blamed == &sender));
+            assert!(report
+                .provable_errors
+                .get(&sender)
+                .is_some_and(|evidence| evidence.guilty_party() == &sender && evidence.verify(&()).is_ok()));
+        }
+    }
+}
+
+#[test]
+fn equivocation() {
+    check_equivocation(SessionConfig::default());
+}
+
+#[test]
+fn equivocation_with_merkle_echo() {
+    check_equivocation(SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::MerkleEcho));
+}
+
+#[test]
+fn equivocation_with_merkle_echo_many_nodes() {
+    // Takes several resolution rounds to get from the roots to the disputed leaves.
+    check_equivocation_on_nodes(
+        SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::MerkleEcho),
+        10,
+    );
+}
+
+#[test]
+fn equivocation_with_piggybacked_echo() {
+    check_equivocation(SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::PiggybackedEcho));
+}
+
+#[test]
+fn equivocation_with_bracha() {
+    let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::Bracha);
+    let (tamperer, execution_result) = run_with_tampering_and_config(Tampering::Equivocation, config);
+    let sender = tamperer.sender;
+    let victim = tamperer.victim.unwrap();
+
+    // The nodes that got different echo broadcasts may be ready to deliver different sets of them,
+    // and may relay the evidence of the equivocation to each other,
+    // but none of that should be held against any honest node.
+    execution_result
+        .check_no_false_blame(&BTreeSet::from([sender]))
+        .unwrap();
+
+    for (id, report) in execution_result.reports {
+        if id == sender {
+            continue;
+        }
+
+        assert!(report.unprovable_errors.is_empty());
+        assert!(report.offenses.keys().all(|blamed| blamed == &sender));
+        assert!(report
+            .provable_errors
+            .values()
+            .all(|evidence| evidence.guilty_party() == &sender && evidence.verify(&()).is_ok()));
+
+        // Depending on the order of delivery, the nodes either get the evidence and ban the sender,
+        // or wait for the messages from the victim (or from the nodes that banned the sender).
+        // Either way, the protocol itself needs the messages from everyone, so it cannot finish.
+        assert!(!matches!(report.outcome, SessionOutcome::Result(_)));
+
+        if id == victim {
+            // The victim cannot get enough echoes matching what it received, but it has the evidence
+            assert!(!report.provable_errors.is_empty());
+        }
+    }
+}
+
+#[test]
+fn dropped_echoes_with_bracha() {
+    let config = SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::Bracha);
+    let (tamperer, execution_result) = run_with_tampering_and_config(Tampering::DropEchoes, config);
+    for (id, report) in execution_result.reports {
+        if id != tamperer.sender {
+            assert!(matches!(report.outcome, SessionOutcome::Result(_)));
+            assert!(report.provable_errors.is_empty());
+            assert!(report.unprovable_errors.is_empty());
+        }
+    }
+}
+
+#[test]
+fn dropped_echoes() {
+    // Without the reliable broadcast, a single missing echo stalls the session
+    let (sender, execution_result) = run_with_tampering(Tampering::DropEchoes);
+    for (id, report) in execution_result.reports {
+        if id != sender {
+            assert!(!matches!(report.outcome, SessionOutcome::Result(_)));
+        }
+    }
+}
+
+#[test]
+fn message_from_outsider() {
+    // Someone from outside of the session executes the protocol with a larger set of parties,
+    // so their messages are validly signed and have the right session ID.
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+    let parties = (0..3)
+        .map(|id| TestSigner::new(id).verifying_key())
+        .collect::<BTreeSet<_>>();
+    let outsider = TestSigner::new(3);
+    let outsider_parties = parties
+        .iter()
+        .cloned()
+        .chain([outsider.verifying_key()])
+        .collect::<BTreeSet<_>>();
+
+    let session = Session::<_, SP>::new(
+        &mut OsRng,
+        session_id.clone(),
+        TestSigner::new(0),
+        SimpleProtocolEntryPoint::new(parties),
+    )
+    .unwrap();
+    let outsider_session = Session::<_, SP>::new(
+        &mut OsRng,
+        session_id,
+        outsider,
+        SimpleProtocolEntryPoint::new(outsider_parties),
+    )
+    .unwrap();
+
+    let (message, _artifact) = outsider_session.make_message(&mut OsRng, &session.verifier()).unwrap();
+
+    // The message is rejected as a remote error instead of failing the session with a local one
+    let mut accum = session.make_accumulator();
+    let outcome = session
+        .preprocess_message(&mut accum, &outsider.verifying_key(), message)
+        .unwrap();
+    match outcome {
+        PreprocessOutcome::Error(error) => assert_eq!(error.kind(), RemoteErrorKind::UnexpectedSender),
+        _ => panic!("The message from an outsider was not rejected"),
+    }
+}
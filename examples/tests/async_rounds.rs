@@ -0,0 +1,323 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::marker::PhantomData;
+
+use manul::{
+    dev::{run_sync, tokio::run_async_with_async_entry_points, BinaryFormat, TestSessionParams, TestSigner},
+    protocol::{
+        Artifact, AsyncEntryPoint, AsyncRound, BoxFuture, BoxedFormat, BoxedRound, CommunicationInfo, DirectMessage,
+        EchoBroadcast, EntryPoint, FinalizeOutcome, LocalError, MessageValidationError, NoProtocolErrors,
+        NormalBroadcast, PartyId, Payload, Protocol, ProtocolMessage, ProtocolMessagePart, ReceiveError, RoundId,
+        SyncEntryPoint, TransitionInfo,
+    },
+    session::SessionOutcome,
+    signature::Keypair,
+};
+use manul_example::simple::SimpleProtocolEntryPoint;
+use rand_core::{CryptoRngCore, OsRng};
+use serde::{Deserialize, Serialize};
+
+/// Sums up the values of all the nodes, with the values being "loaded" asynchronously.
+#[derive(Debug)]
+struct AsyncSumProtocol<Id>(PhantomData<Id>);
+
+impl<Id: PartyId> Protocol<Id> for AsyncSumProtocol<Id> {
+    type Result = u64;
+    type ProtocolError = NoProtocolErrors;
+
+    fn verify_direct_message_is_invalid(
+        _format: &BoxedFormat,
+        _round_id: &RoundId,
+        _message: &DirectMessage,
+    ) -> Result<(), MessageValidationError> {
+        unimplemented!()
+    }
+
+    fn verify_echo_broadcast_is_invalid(
+        _format: &BoxedFormat,
+        _round_id: &RoundId,
+        _message: &EchoBroadcast,
+    ) -> Result<(), MessageValidationError> {
+        unimplemented!()
+    }
+
+    fn verify_normal_broadcast_is_invalid(
+        _format: &BoxedFormat,
+        _round_id: &RoundId,
+        _message: &NormalBroadcast,
+    ) -> Result<(), MessageValidationError> {
+        unimplemented!()
+    }
+}
+
+/// Simulates a request to an external service.
+async fn external_request<T>(value: T) -> T {
+    tokio::task::yield_now().await;
+    value
+}
+
+#[derive(Debug, Clone)]
+struct AsyncSumEntryPoint<Id> {
+    all_ids: BTreeSet<Id>,
+}
+
+impl<Id: PartyId> AsyncEntryPoint<Id> for AsyncSumEntryPoint<Id> {
+    type Protocol = AsyncSumProtocol<Id>;
+
+    fn entry_round_id() -> RoundId {
+        1.into()
+    }
+
+    fn make_round<'a>(
+        self,
+        _rng: &'a mut (dyn CryptoRngCore + Send),
+        _shared_randomness: &'a [u8],
+        id: &'a Id,
+    ) -> BoxFuture<'a, Result<BoxedRound<Id, Self::Protocol>, LocalError>>
+    where
+        Self: 'a,
+    {
+        Box::pin(async move {
+            let position = self
+                .all_ids
+                .iter()
+                .position(|other_id| other_id == id)
+                .ok_or_else(|| LocalError::new("This node is not among the parties"))?;
+            let value = external_request(position as u64 + 1).await;
+            let mut other_ids = self.all_ids;
+            other_ids.remove(id);
+            Ok(BoxedRound::new_async(Round1 { other_ids, value }))
+        })
+    }
+}
+
+/// A synchronous entry point returning an asynchronous round.
+#[derive(Debug, Clone)]
+struct SyncEntryPointWithAsyncRound<Id>(AsyncSumEntryPoint<Id>);
+
+impl<Id: PartyId> EntryPoint<Id> for SyncEntryPointWithAsyncRound<Id> {
+    type Protocol = AsyncSumProtocol<Id>;
+
+    fn entry_round_id() -> RoundId {
+        1.into()
+    }
+
+    fn make_round(
+        self,
+        _rng: &mut dyn CryptoRngCore,
+        _shared_randomness: &[u8],
+        id: &Id,
+    ) -> Result<BoxedRound<Id, Self::Protocol>, LocalError> {
+        let mut other_ids = self.0.all_ids;
+        other_ids.remove(id);
+        Ok(BoxedRound::new_async(Round1 { other_ids, value: 1 }))
+    }
+}
+
+#[derive(Debug)]
+struct Round1<Id> {
+    other_ids: BTreeSet<Id>,
+    value: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Round1Echo {
+    value: u64,
+}
+
+impl<Id: PartyId> AsyncRound<Id> for Round1<Id> {
+    type Protocol = AsyncSumProtocol<Id>;
+
+    fn transition_info(&self) -> TransitionInfo {
+        TransitionInfo::new_linear(1)
+    }
+
+    fn communication_info(&self) -> CommunicationInfo<Id> {
+        CommunicationInfo::regular(&self.other_ids)
+    }
+
+    fn make_echo_broadcast(
+        &self,
+        _rng: &mut dyn CryptoRngCore,
+        format: &BoxedFormat,
+    ) -> Result<EchoBroadcast, LocalError> {
+        EchoBroadcast::new(format, Round1Echo { value: self.value })
+    }
+
+    fn receive_message<'a>(
+        &'a self,
+        format: &'a BoxedFormat,
+        _from: &'a Id,
+        message: ProtocolMessage,
+    ) -> BoxFuture<'a, Result<Payload, ReceiveError<Id, Self::Protocol>>> {
+        Box::pin(async move {
+            message.normal_broadcast.assert_is_none()?;
+            message.direct_message.assert_is_none()?;
+            let echo = message.echo_broadcast.deserialize::<Round1Echo>(format)?;
+            let value = external_request(echo.value).await;
+            Ok(Payload::new(value))
+        })
+    }
+
+    fn finalize<'a>(
+        self: Box<Self>,
+        _rng: &'a mut (dyn CryptoRngCore + Send),
+        payloads: BTreeMap<Id, Payload>,
+        _artifacts: BTreeMap<Id, Artifact>,
+    ) -> BoxFuture<'a, Result<FinalizeOutcome<Id, Self::Protocol>, LocalError>> {
+        Box::pin(async move {
+            let mut sum = self.value;
+            for payload in payloads.into_values() {
+                sum += payload.downcast::<u64>()?;
+            }
+            let sum = external_request(sum).await;
+            Ok(FinalizeOutcome::AnotherRound(BoxedRound::new_async(Round2 {
+                other_ids: self.other_ids,
+                sum,
+            })))
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Round2<Id> {
+    other_ids: BTreeSet<Id>,
+    sum: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Round2Broadcast {
+    sum: u64,
+}
+
+impl<Id: PartyId> AsyncRound<Id> for Round2<Id> {
+    type Protocol = AsyncSumProtocol<Id>;
+
+    fn transition_info(&self) -> TransitionInfo {
+        TransitionInfo::new_linear_terminating(2)
+    }
+
+    fn communication_info(&self) -> CommunicationInfo<Id> {
+        CommunicationInfo::regular(&self.other_ids)
+    }
+
+    fn make_normal_broadcast(
+        &self,
+        _rng: &mut dyn CryptoRngCore,
+        format: &BoxedFormat,
+    ) -> Result<NormalBroadcast, LocalError> {
+        NormalBroadcast::new(format, Round2Broadcast { sum: self.sum })
+    }
+
+    fn receive_message<'a>(
+        &'a self,
+        format: &'a BoxedFormat,
+        _from: &'a Id,
+        message: ProtocolMessage,
+    ) -> BoxFuture<'a, Result<Payload, ReceiveError<Id, Self::Protocol>>> {
+        Box::pin(async move {
+            message.echo_broadcast.assert_is_none()?;
+            message.direct_message.assert_is_none()?;
+            let broadcast = message.normal_broadcast.deserialize::<Round2Broadcast>(format)?;
+            if external_request(broadcast.sum).await != self.sum {
+                return Err(ReceiveError::unprovable("Mismatched sums"));
+            }
+            Ok(Payload::empty())
+        })
+    }
+
+    fn finalize<'a>(
+        self: Box<Self>,
+        _rng: &'a mut (dyn CryptoRngCore + Send),
+        _payloads: BTreeMap<Id, Payload>,
+        _artifacts: BTreeMap<Id, Artifact>,
+    ) -> BoxFuture<'a, Result<FinalizeOutcome<Id, Self::Protocol>, LocalError>> {
+        Box::pin(async move { Ok(FinalizeOutcome::Result(external_request(self.sum).await)) })
+    }
+}
+
+fn make_entry_points() -> Vec<(TestSigner, AsyncSumEntryPoint<manul::dev::TestVerifier>)> {
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    signers
+        .into_iter()
+        .map(|signer| {
+            (
+                signer,
+                AsyncSumEntryPoint {
+                    all_ids: all_ids.clone(),
+                },
+            )
+        })
+        .collect()
+}
+
+async fn async_rounds(offload_processing: bool) {
+    let reports = run_async_with_async_entry_points::<_, TestSessionParams<BinaryFormat>>(
+        &mut OsRng,
+        make_entry_points(),
+        offload_processing,
+    )
+    .await
+    .unwrap()
+    .reports;
+
+    for report in reports.into_values() {
+        assert!(report.provable_errors.is_empty());
+        assert!(report.unprovable_errors.is_empty());
+        match report.outcome {
+            SessionOutcome::Result(sum) => assert_eq!(sum, 1 + 2 + 3),
+            outcome => panic!("Unexpected outcome: {outcome:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn async_rounds_no_offload() {
+    async_rounds(false).await
+}
+
+#[tokio::test]
+async fn async_rounds_with_offload() {
+    async_rounds(true).await
+}
+
+#[tokio::test]
+async fn sync_entry_point_adapter() {
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    let entry_points = signers
+        .into_iter()
+        .map(|signer| {
+            (
+                signer,
+                SyncEntryPoint::new(SimpleProtocolEntryPoint::new(all_ids.clone())),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let results =
+        run_async_with_async_entry_points::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, true)
+            .await
+            .unwrap()
+            .results()
+            .unwrap();
+    assert_eq!(results.len(), 3);
+}
+
+#[test]
+fn sync_session_rejects_async_rounds() {
+    let entry_points = make_entry_points()
+        .into_iter()
+        .map(|(signer, entry_point)| (signer, SyncEntryPointWithAsyncRound(entry_point)))
+        .collect::<Vec<_>>();
+    let result = run_sync::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points);
+    assert!(result.is_err());
+}
@@ -1,13 +1,29 @@
 extern crate alloc;
 
-use alloc::collections::BTreeSet;
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use core::time::Duration;
+use std::sync::Mutex;
 
 use manul::{
     dev::{tokio::run_async, BinaryFormat, TestSessionParams, TestSigner},
+    session::{
+        tokio::{
+            par_run_session_with_retransmission, run_session_with_retransmission, Incoming, MessageIn, MessageOut,
+            Outgoing,
+        },
+        Session, SessionId, SessionReport,
+    },
     signature::Keypair,
 };
-use manul_example::simple::SimpleProtocolEntryPoint;
+use manul_example::simple::{SimpleProtocol, SimpleProtocolEntryPoint};
 use rand_core::OsRng;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+type SP = TestSessionParams<BinaryFormat>;
 
 async fn async_run(offload_processing: bool) {
     // Create 4 parties
@@ -41,3 +57,132 @@ async fn async_run_no_offload() {
 async fn async_run_with_offload() {
     async_run(true).await
 }
+
+/// Runs the sessions with retransmission, losing the first transmission of every message from one node to another.
+async fn async_run_with_retransmission(offload_processing: bool) {
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+    let mut ids = all_ids.iter().copied();
+    let (sender, receiver) = (ids.next().unwrap(), ids.next().unwrap());
+
+    let (out_tx, mut out_rx) = mpsc::channel::<Outgoing<SP>>(1000);
+    let (report_tx, mut report_rx) = mpsc::channel::<SessionReport<SimpleProtocol, SP>>(3);
+    let all_finished = CancellationToken::new();
+    let mut txs = BTreeMap::new();
+    let mut tasks = Vec::new();
+
+    for signer in signers {
+        let id = signer.verifying_key();
+        let (tx, mut rx) = mpsc::channel::<Incoming<SP>>(1000);
+        txs.insert(id, tx);
+
+        let entry_point = SimpleProtocolEntryPoint::new(all_ids.clone());
+        let session = Session::<_, SP>::new(&mut OsRng, session_id.clone(), signer, entry_point).unwrap();
+        let session_id = session_id.clone();
+        let out_tx = out_tx.clone();
+        let report_tx = report_tx.clone();
+        let all_finished = all_finished.clone();
+        tasks.push(tokio::spawn(async move {
+            let (report, outbox) = if offload_processing {
+                par_run_session_with_retransmission(&mut OsRng, &out_tx, &mut rx, CancellationToken::new(), session)
+                    .await
+            } else {
+                run_session_with_retransmission(&mut OsRng, &out_tx, &mut rx, CancellationToken::new(), session).await
+            }
+            .unwrap();
+            report_tx.send(report).await.unwrap();
+
+            // The other nodes may still need the messages of the last round.
+            let answer_requests = async {
+                while let Some(incoming) = rx.recv().await {
+                    if let Incoming::ResendRequest(request) = incoming {
+                        if let Some(message) = outbox.respond(&request).unwrap() {
+                            let message_out = MessageOut {
+                                session_id: session_id.clone(),
+                                from: id,
+                                to: *request.requester(),
+                                message,
+                            };
+                            out_tx.send(Outgoing::Message(message_out)).await.unwrap();
+                        }
+                    }
+                }
+            };
+            tokio::select! {
+                _ = answer_requests => {}
+                _ = all_finished.cancelled() => {}
+            }
+        }));
+    }
+
+    let lost = Arc::new(Mutex::new(BTreeSet::new()));
+    let router_txs = txs.clone();
+    let router_lost = lost.clone();
+    let router = tokio::spawn(async move {
+        while let Some(outgoing) = out_rx.recv().await {
+            let (to, incoming) = match outgoing {
+                Outgoing::Message(message_out) => {
+                    if message_out.from == sender
+                        && message_out.to == receiver
+                        && router_lost
+                            .lock()
+                            .unwrap()
+                            .insert(message_out.message.round_id().clone())
+                    {
+                        continue;
+                    }
+                    let message_in = MessageIn {
+                        from: message_out.from,
+                        message: message_out.message,
+                    };
+                    (message_out.to, Incoming::Message(message_in))
+                }
+                Outgoing::ResendRequest { to, request } => (to, Incoming::ResendRequest(request)),
+            };
+            router_txs[&to].send(incoming).await.unwrap();
+        }
+    });
+
+    // The transport does not notice the lost messages, so the nodes are periodically prompted to request them.
+    let timer = tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            for tx in txs.values() {
+                tx.send(Incoming::ResendTimeout).await.unwrap();
+            }
+        }
+    });
+
+    for _ in 0..3 {
+        let report = tokio::time::timeout(Duration::from_secs(10), report_rx.recv())
+            .await
+            .expect("The sessions are stuck")
+            .unwrap();
+        assert!(report.provable_errors.is_empty());
+        assert!(report.unprovable_errors.is_empty());
+        assert!(report.result().is_some());
+    }
+
+    all_finished.cancel();
+    for task in tasks {
+        task.await.unwrap();
+    }
+    timer.abort();
+    router.abort();
+
+    assert!(lost.lock().unwrap().len() > 1);
+}
+
+#[tokio::test]
+async fn async_run_with_retransmission_no_offload() {
+    async_run_with_retransmission(false).await
+}
+
+#[tokio::test]
+async fn async_run_with_retransmission_with_offload() {
+    async_run_with_retransmission(true).await
+}
@@ -0,0 +1,117 @@
+extern crate alloc;
+
+use alloc::{collections::BTreeSet, sync::Arc};
+use std::sync::Mutex;
+
+use manul::{
+    dev::{run_sync, tokio::run_async, BinaryFormat, TestHasher, TestSignature, TestSigner, TestVerifier},
+    protocol::BoxFuture,
+    session::{AsyncSessionParameters, SessionParameters},
+    signature::{self, Keypair, RandomizedDigestSigner},
+};
+use manul_example::simple::SimpleProtocolEntryPoint;
+use rand_core::{CryptoRngCore, OsRng};
+
+/// Simulates a signer with the key held in an HSM, which can only be accessed asynchronously.
+#[derive(Debug, Clone)]
+struct RemoteSigner {
+    signer: TestSigner,
+    // The sizes of the batches of digests requested to be signed.
+    batches: Arc<Mutex<Vec<usize>>>,
+}
+
+impl<D: digest::Digest> RandomizedDigestSigner<D, TestSignature> for RemoteSigner {
+    fn try_sign_digest_with_rng(
+        &self,
+        _rng: &mut impl CryptoRngCore,
+        _digest: D,
+    ) -> Result<TestSignature, signature::Error> {
+        // The key can only be accessed asynchronously
+        Err(signature::Error::new())
+    }
+}
+
+impl Keypair for RemoteSigner {
+    type VerifyingKey = TestVerifier;
+
+    fn verifying_key(&self) -> Self::VerifyingKey {
+        self.signer.verifying_key()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RemoteSignerParams;
+
+impl SessionParameters for RemoteSignerParams {
+    type Signer = RemoteSigner;
+    type Verifier = TestVerifier;
+    type Signature = TestSignature;
+    type Digest = TestHasher;
+    type WireFormat = BinaryFormat;
+}
+
+impl AsyncSessionParameters for RemoteSignerParams {
+    fn sign_digests<'a>(
+        signer: &'a Self::Signer,
+        mut rng: &'a mut (dyn CryptoRngCore + Send),
+        digests: Vec<Self::Digest>,
+    ) -> BoxFuture<'a, Result<Vec<Self::Signature>, signature::Error>> {
+        Box::pin(async move {
+            signer.batches.lock().unwrap().push(digests.len());
+            // Simulate a request to the HSM
+            tokio::task::yield_now().await;
+            digests
+                .into_iter()
+                .map(|digest| signer.signer.try_sign_digest_with_rng(&mut rng, digest))
+                .collect()
+        })
+    }
+}
+
+fn make_entry_points(batches: &Arc<Mutex<Vec<usize>>>) -> Vec<(RemoteSigner, SimpleProtocolEntryPoint<TestVerifier>)> {
+    let signers = (0..3)
+        .map(|id| RemoteSigner {
+            signer: TestSigner::new(id),
+            batches: batches.clone(),
+        })
+        .collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    signers
+        .into_iter()
+        .map(|signer| (signer, SimpleProtocolEntryPoint::new(all_ids.clone())))
+        .collect()
+}
+
+async fn async_signing(offload_processing: bool) {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let results = run_async::<_, RemoteSignerParams>(&mut OsRng, make_entry_points(&batches), offload_processing)
+        .await
+        .unwrap()
+        .results()
+        .unwrap();
+    assert_eq!(results.len(), 3);
+
+    // The broadcasts of each round are signed in a single batch.
+    let batches = batches.lock().unwrap();
+    assert!(batches.iter().any(|batch_size| *batch_size > 1));
+}
+
+#[tokio::test]
+async fn async_signing_no_offload() {
+    async_signing(false).await
+}
+
+#[tokio::test]
+async fn async_signing_with_offload() {
+    async_signing(true).await
+}
+
+#[test]
+fn sync_session_requires_sync_signer() {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let result = run_sync::<_, RemoteSignerParams>(&mut OsRng, make_entry_points(&batches));
+    assert!(result.is_err());
+}
@@ -0,0 +1,61 @@
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+
+use futures::{executor::ThreadPool, task::SpawnExt};
+use manul::{
+    dev::{futures::run_async, BinaryFormat, TestSessionParams, TestSigner},
+    protocol::BoxFuture,
+    session::futures::Spawner,
+    signature::Keypair,
+};
+use manul_example::simple::SimpleProtocolEntryPoint;
+use rand_core::OsRng;
+
+/// Launches the tasks in a thread pool from `futures`, without depending on any specific async runtime.
+#[derive(Debug, Clone)]
+struct ThreadPoolSpawner(ThreadPool);
+
+impl Spawner for ThreadPoolSpawner {
+    fn spawn(&self, future: BoxFuture<'static, ()>) {
+        self.0.spawn(future).unwrap();
+    }
+}
+
+fn futures_run(offload_processing: bool) {
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+
+    let entry_points = signers
+        .into_iter()
+        .map(|signer| {
+            let entry_point = SimpleProtocolEntryPoint::new(all_ids.clone());
+            (signer, entry_point)
+        })
+        .collect::<Vec<_>>();
+
+    let spawner = ThreadPoolSpawner(ThreadPool::new().unwrap());
+    let results = futures::executor::block_on(run_async::<_, TestSessionParams<BinaryFormat>>(
+        &mut OsRng,
+        &spawner,
+        entry_points,
+        offload_processing,
+    ))
+    .unwrap()
+    .results()
+    .unwrap();
+    assert_eq!(results.len(), 3);
+}
+
+#[test]
+fn futures_run_no_offload() {
+    futures_run(false)
+}
+
+#[test]
+fn futures_run_with_offload() {
+    futures_run(true)
+}
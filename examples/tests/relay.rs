@@ -0,0 +1,140 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use manul::{
+    dev::{BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
+    session::{
+        DriverAction, DriverEvent, Relay, RelayClient, Session, SessionDriver, SessionId, SessionOutcome, SessionReport,
+    },
+    signature::Keypair,
+};
+use manul_example::simple::{SimpleProtocol, SimpleProtocolEntryPoint};
+use rand_core::OsRng;
+
+type SP = TestSessionParams<BinaryFormat>;
+
+struct Node {
+    signer: TestSigner,
+    driver: SessionDriver<SimpleProtocol, SP>,
+    client: RelayClient<SP>,
+    events: VecDeque<DriverEvent<SimpleProtocol, SP>>,
+}
+
+fn make_nodes() -> BTreeMap<TestVerifier, Node> {
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+
+    signers
+        .into_iter()
+        .map(|signer| {
+            let id = signer.verifying_key();
+            let entry_point = SimpleProtocolEntryPoint::new(all_ids.clone());
+            let session = Session::<_, SP>::new(&mut OsRng, session_id.clone(), signer, entry_point).unwrap();
+            let node = Node {
+                signer,
+                driver: SessionDriver::new(session),
+                client: RelayClient::new(id, session_id.clone()),
+                events: [DriverEvent::Tick].into(),
+            };
+            (id, node)
+        })
+        .collect()
+}
+
+/// Runs the nodes communicating only through a relay,
+/// which withholds the messages for which `withhold(from, to)` returns `true`.
+///
+/// If the nodes stall, they are cancelled.
+fn run_with_relay(
+    withhold: impl Fn(&TestVerifier, &TestVerifier) -> bool,
+) -> BTreeMap<TestVerifier, SessionReport<SimpleProtocol, SP>> {
+    let mut nodes = make_nodes();
+    let mut relay = Relay::new();
+    let mut reports = BTreeMap::new();
+
+    while reports.len() < nodes.len() {
+        let mut progress = false;
+
+        for (id, node) in nodes.iter_mut() {
+            while let Some(event) = node.events.pop_front() {
+                progress = true;
+                if node.driver.is_finished() {
+                    continue;
+                }
+                for action in node.driver.handle(&mut OsRng, event).unwrap() {
+                    match action {
+                        DriverAction::CreateMessage(creator) => node
+                            .events
+                            .push_back(DriverEvent::MessageCreated(creator.create(&mut OsRng).unwrap())),
+                        DriverAction::ProcessMessage(processor) => node
+                            .events
+                            .push_back(DriverEvent::MessageProcessed(processor.process())),
+                        DriverAction::Send { to, message } => {
+                            let envelope = node.client.seal(&mut OsRng, &node.signer, to, message).unwrap();
+                            if !withhold(id, &to) {
+                                relay.deposit(envelope);
+                            }
+                        }
+                        DriverAction::SendResendRequest { .. } => unreachable!("Retransmission is not enabled"),
+                        DriverAction::Finished(report) => {
+                            reports.insert(*id, report);
+                        }
+                    }
+                }
+            }
+        }
+
+        for (id, node) in nodes.iter_mut() {
+            for envelope in relay.fetch(id) {
+                let (from, message) = node.client.open(envelope).unwrap();
+                node.events.push_back(DriverEvent::MessageReceived { from, message });
+                progress = true;
+            }
+        }
+
+        if !progress {
+            for node in nodes.values_mut() {
+                if !node.driver.is_finished() {
+                    node.events.push_back(DriverEvent::Cancel);
+                }
+            }
+        }
+    }
+
+    reports
+}
+
+#[test]
+fn relayed_execution() {
+    let reports = run_with_relay(|_from, _to| false);
+    assert_eq!(reports.len(), 3);
+    for report in reports.into_values() {
+        assert!(report.result().is_some());
+    }
+}
+
+#[test]
+fn withheld_messages() {
+    let culprit = TestSigner::new(0).verifying_key();
+    let victim = TestSigner::new(1).verifying_key();
+
+    let reports = run_with_relay(|from, to| from == &culprit && to == &victim);
+
+    let report = &reports[&victim];
+    assert!(matches!(report.outcome, SessionOutcome::Terminated));
+    assert!(report.provable_errors.is_empty());
+    assert!(report.unprovable_errors.is_empty());
+    let missing = report.missing_messages.values().next().unwrap();
+    assert_eq!(missing, &[culprit].into());
+
+    // The withheld messages do not count against the sender on the other nodes either.
+    for report in reports.values() {
+        assert!(report.provable_errors.is_empty());
+        assert!(report.unprovable_errors.is_empty());
+    }
+}
@@ -0,0 +1,160 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use manul::{
+    dev::{BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
+    session::{DriverAction, DriverEvent, Message, Session, SessionDriver, SessionId, SessionReport},
+    signature::Keypair,
+};
+use manul_example::simple::{SimpleProtocol, SimpleProtocolEntryPoint};
+use rand_core::OsRng;
+
+type SP = TestSessionParams<BinaryFormat>;
+
+fn make_drivers(retransmission: bool) -> BTreeMap<TestVerifier, SessionDriver<SimpleProtocol, SP>> {
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+
+    signers
+        .into_iter()
+        .map(|signer| {
+            let id = signer.verifying_key();
+            let entry_point = SimpleProtocolEntryPoint::new(all_ids.clone());
+            let session = Session::<_, SP>::new(&mut OsRng, session_id.clone(), signer, entry_point).unwrap();
+            let driver = if retransmission {
+                SessionDriver::new_with_retransmission(session)
+            } else {
+                SessionDriver::new(session)
+            };
+            (id, driver)
+        })
+        .collect()
+}
+
+/// Runs the drivers in a single event loop, with the message creation and processing
+/// executed out of order, as if they were done in parallel tasks.
+///
+/// The messages for which `lose(from, to, message)` returns `true` are not delivered.
+/// If the drivers stall, they are notified with [`DriverEvent::ResendTimeout`].
+fn run_event_loop(
+    drivers: BTreeMap<TestVerifier, SessionDriver<SimpleProtocol, SP>>,
+    mut lose: impl FnMut(&TestVerifier, &TestVerifier, &Message<TestVerifier>) -> bool,
+) -> BTreeMap<TestVerifier, SessionReport<SimpleProtocol, SP>> {
+    let mut drivers = drivers;
+    let mut events = drivers
+        .keys()
+        .map(|id| (*id, DriverEvent::Tick))
+        .collect::<VecDeque<_>>();
+    let mut reports = BTreeMap::new();
+
+    while reports.len() < drivers.len() {
+        let Some((id, event)) = events.pop_front() else {
+            for (id, driver) in drivers.iter() {
+                if !driver.is_finished() {
+                    events.push_back((*id, DriverEvent::ResendTimeout));
+                }
+            }
+            continue;
+        };
+
+        let driver = drivers.get_mut(&id).unwrap();
+        // Finished drivers can still answer resend requests.
+        if driver.is_finished() && !matches!(event, DriverEvent::ResendRequested(_)) {
+            continue;
+        }
+        for action in driver.handle(&mut OsRng, event).unwrap() {
+            match action {
+                // The work is reported back after the events that are already queued.
+                DriverAction::CreateMessage(creator) => {
+                    events.push_back((id, DriverEvent::MessageCreated(creator.create(&mut OsRng).unwrap())))
+                }
+                DriverAction::ProcessMessage(processor) => {
+                    events.push_back((id, DriverEvent::MessageProcessed(processor.process())))
+                }
+                DriverAction::Send { to, message } => {
+                    if !lose(&id, &to, &message) {
+                        events.push_front((to, DriverEvent::MessageReceived { from: id, message }))
+                    }
+                }
+                DriverAction::SendResendRequest { to, request } => {
+                    events.push_back((to, DriverEvent::ResendRequested(request)))
+                }
+                DriverAction::Finished(report) => {
+                    reports.insert(id, report);
+                }
+            }
+        }
+    }
+
+    reports
+}
+
+#[test]
+fn driver_event_loop() {
+    let reports = run_event_loop(make_drivers(false), |_from, _to, _message| false);
+    assert_eq!(reports.len(), 3);
+    for report in reports.into_values() {
+        assert!(report.result().is_some());
+    }
+}
+
+#[test]
+fn driver_retransmission() {
+    let drivers = make_drivers(true);
+    let mut ids = drivers.keys().copied();
+    let (sender, receiver) = (ids.next().unwrap(), ids.next().unwrap());
+
+    // The first transmission of every message from `sender` to `receiver` is lost.
+    let mut lost = BTreeSet::new();
+    let reports = run_event_loop(drivers, |from, to, message| {
+        from == &sender && to == &receiver && lost.insert(message.round_id().clone())
+    });
+
+    assert!(lost.len() > 1);
+    assert_eq!(reports.len(), 3);
+    for report in reports.into_values() {
+        assert!(report.provable_errors.is_empty());
+        assert!(report.unprovable_errors.is_empty());
+        assert!(report.result().is_some());
+    }
+}
+
+#[test]
+fn driver_cancellation() {
+    let mut drivers = make_drivers(false);
+    let driver = drivers.values_mut().next().unwrap();
+
+    let actions = driver.handle(&mut OsRng, DriverEvent::Tick).unwrap();
+    let mut creators = actions
+        .into_iter()
+        .map(|action| match action {
+            DriverAction::CreateMessage(creator) => creator,
+            _ => panic!("Unexpected action"),
+        })
+        .collect::<Vec<_>>();
+
+    // The session is not terminated until all the requested messages are reported back.
+    assert!(driver.handle(&mut OsRng, DriverEvent::Cancel).unwrap().is_empty());
+    let last_creator = creators.pop().unwrap();
+    for creator in creators {
+        let created = creator.create(&mut OsRng).unwrap();
+        let actions = driver.handle(&mut OsRng, DriverEvent::MessageCreated(created)).unwrap();
+        assert!(matches!(actions.as_slice(), [DriverAction::Send { .. }]));
+    }
+
+    let created = last_creator.create(&mut OsRng).unwrap();
+    let actions = driver.handle(&mut OsRng, DriverEvent::MessageCreated(created)).unwrap();
+    let report = match actions.into_iter().last().unwrap() {
+        DriverAction::Finished(report) => report,
+        _ => panic!("Unexpected action"),
+    };
+    assert!(report.result().is_none());
+
+    assert!(driver.is_finished());
+    assert!(driver.handle(&mut OsRng, DriverEvent::Tick).is_err());
+}
@@ -0,0 +1,60 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use futures::{executor::block_on, future, stream, SinkExt, StreamExt};
+use manul::{
+    dev::{BinaryFormat, TestSessionParams, TestSigner},
+    session::{
+        futures::{split_session, MessageIn},
+        Session, SessionId,
+    },
+    signature::Keypair,
+};
+use manul_example::simple::SimpleProtocolEntryPoint;
+use rand_core::OsRng;
+
+type SP = TestSessionParams<BinaryFormat>;
+
+#[test]
+fn session_as_sink_and_stream() {
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+
+    let mut sinks = BTreeMap::new();
+    let mut streams = Vec::new();
+    let mut tasks = Vec::new();
+    for signer in signers {
+        let id = signer.verifying_key();
+        let entry_point = SimpleProtocolEntryPoint::new(all_ids.clone());
+        let session = Session::<_, SP>::new(&mut OsRng, session_id.clone(), signer, entry_point).unwrap();
+        let (sink, stream, task) = split_session(OsRng, future::pending(), session);
+        sinks.insert(id, sink);
+        streams.push(stream);
+        tasks.push(task);
+    }
+
+    // Route the outgoing messages of every session into the sink of the destination,
+    // until all the sessions are finished.
+    let router = stream::select_all(streams).for_each(|message_out| {
+        let mut sink = sinks[&message_out.to].clone();
+        async move {
+            // The destination session may already be finished.
+            let _ = sink
+                .send(MessageIn {
+                    from: message_out.from,
+                    message: message_out.message,
+                })
+                .await;
+        }
+    });
+
+    let (reports, ()) = block_on(future::join(future::join_all(tasks), router));
+    for report in reports {
+        assert!(report.unwrap().result().is_some());
+    }
+}
@@ -0,0 +1,100 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::time::Duration;
+
+use manul::{
+    dev::{BinaryFormat, TestSessionParams, TestSigner},
+    session::{tcp::connect, tokio::run_session, Session, SessionId},
+    signature::Keypair,
+};
+use manul_example::simple::SimpleProtocolEntryPoint;
+use rand_core::OsRng;
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+type SP = TestSessionParams<BinaryFormat>;
+
+async fn bind() -> TcpListener {
+    TcpListener::bind("127.0.0.1:0").await.unwrap()
+}
+
+#[tokio::test]
+async fn tcp_loopback() {
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+
+    let mut listeners = BTreeMap::new();
+    for signer in &signers {
+        listeners.insert(signer.verifying_key(), bind().await);
+    }
+    let addresses = listeners
+        .iter()
+        .map(|(id, listener)| (*id, listener.local_addr().unwrap()))
+        .collect::<BTreeMap<_, _>>();
+
+    let handles = signers
+        .into_iter()
+        .map(|signer| {
+            let id = signer.verifying_key();
+            let listener = listeners.remove(&id).unwrap();
+            let peers = addresses
+                .iter()
+                .filter(|(peer_id, _address)| **peer_id != id)
+                .map(|(peer_id, address)| (*peer_id, *address))
+                .collect();
+            let entry_point = SimpleProtocolEntryPoint::new(all_ids.clone());
+            let session_id = session_id.clone();
+
+            tokio::spawn(async move {
+                let (tx, mut rx) = connect::<SP>(&mut OsRng, &signer, &session_id, listener, peers)
+                    .await
+                    .unwrap();
+                let session = Session::<_, SP>::new(&mut OsRng, session_id, signer, entry_point).unwrap();
+                run_session(&mut OsRng, &tx, &mut rx, CancellationToken::new(), session).await
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        let report = handle.await.unwrap().unwrap();
+        assert!(report.result().is_some());
+    }
+}
+
+#[tokio::test]
+async fn tcp_rejects_unexpected_peer() {
+    let node = TestSigner::new(0);
+    let peer = TestSigner::new(1);
+    let impostor = TestSigner::new(2);
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+
+    let node_listener = bind().await;
+    let impostor_listener = bind().await;
+
+    // The node expects `peer` at the address where the impostor listens.
+    let node_peers = BTreeMap::from([(peer.verifying_key(), impostor_listener.local_addr().unwrap())]);
+    let impostor_peers = BTreeMap::from([(node.verifying_key(), node_listener.local_addr().unwrap())]);
+
+    let (mut node_rng, mut impostor_rng) = (OsRng, OsRng);
+    let node_task = connect::<SP>(&mut node_rng, &node, &session_id, node_listener, node_peers);
+    let impostor_task = connect::<SP>(
+        &mut impostor_rng,
+        &impostor,
+        &session_id,
+        impostor_listener,
+        impostor_peers,
+    );
+
+    // Depending on the order of the identities, the node either fails to authenticate the dialed peer,
+    // or keeps waiting for the right peer to connect.
+    let node_result = tokio::time::timeout(Duration::from_secs(1), async {
+        tokio::join!(node_task, impostor_task).0
+    })
+    .await;
+    assert!(!matches!(node_result, Ok(Ok(_))));
+}
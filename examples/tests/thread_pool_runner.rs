@@ -0,0 +1,211 @@
+extern crate alloc;
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use core::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use std::{
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Mutex,
+    },
+    thread,
+};
+
+use manul::{
+    dev::{BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
+    session::{
+        thread_pool::{
+            par_run_session, par_run_session_with_retransmission, Incoming, MessageIn, MessageOut, Outgoing,
+        },
+        LocalError, Session, SessionId,
+    },
+    signature::Keypair,
+};
+use manul_example::simple::SimpleProtocolEntryPoint;
+use rand_core::OsRng;
+
+type SP = TestSessionParams<BinaryFormat>;
+
+fn thread_pool_run(num_threads: usize) {
+    let num_threads = NonZeroUsize::new(num_threads).unwrap();
+
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+
+    let channels = all_ids
+        .iter()
+        .map(|id| (*id, mpsc::channel::<MessageIn<SP>>()))
+        .collect::<BTreeMap<_, _>>();
+    let txs = channels
+        .iter()
+        .map(|(id, (tx, _rx))| (*id, tx.clone()))
+        .collect::<BTreeMap<TestVerifier, _>>();
+
+    let handles = signers
+        .into_iter()
+        .zip(channels.into_values())
+        .map(|(signer, (_tx, rx))| {
+            let entry_point = SimpleProtocolEntryPoint::new(all_ids.clone());
+            let session = Session::<_, SP>::new(&mut OsRng, session_id.clone(), signer, entry_point).unwrap();
+            let txs = txs.clone();
+
+            thread::spawn(move || {
+                let send = |message_out: MessageOut<SP>| {
+                    txs[&message_out.to]
+                        .send(MessageIn {
+                            from: message_out.from,
+                            message: message_out.message,
+                        })
+                        .map_err(|_| LocalError::new("The destination has exited"))
+                };
+                let receive = |timeout| {
+                    Ok(match timeout {
+                        Some(timeout) => rx.recv_timeout(timeout).ok(),
+                        None => rx.recv().ok(),
+                    })
+                };
+                par_run_session(&mut OsRng, num_threads, send, receive, session)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        let report = handle.join().unwrap().unwrap();
+        assert!(report.result().is_some());
+    }
+}
+
+#[test]
+fn thread_pool_run_single_worker() {
+    thread_pool_run(1)
+}
+
+#[test]
+fn thread_pool_run_multiple_workers() {
+    thread_pool_run(4)
+}
+
+fn thread_pool_run_with_retransmission(num_threads: usize) {
+    let num_threads = NonZeroUsize::new(num_threads).unwrap();
+    let resend_timeout = Duration::from_millis(50);
+
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+    let mut ids = all_ids.iter().copied();
+    let (sender, receiver) = (ids.next().unwrap(), ids.next().unwrap());
+
+    let channels = all_ids
+        .iter()
+        .map(|id| (*id, mpsc::channel::<Incoming<SP>>()))
+        .collect::<BTreeMap<_, _>>();
+    let txs = channels
+        .iter()
+        .map(|(id, (tx, _rx))| (*id, tx.clone()))
+        .collect::<BTreeMap<TestVerifier, _>>();
+
+    // The first transmission of every message from `sender` to `receiver` is lost.
+    let lost = Arc::new(Mutex::new(BTreeSet::new()));
+    let finished = Arc::new(AtomicUsize::new(0));
+
+    let handles = signers
+        .into_iter()
+        .zip(channels.into_values())
+        .map(|(signer, (_tx, rx))| {
+            let id = signer.verifying_key();
+            let entry_point = SimpleProtocolEntryPoint::new(all_ids.clone());
+            let session = Session::<_, SP>::new(&mut OsRng, session_id.clone(), signer, entry_point).unwrap();
+            let session_id = session_id.clone();
+            let txs = txs.clone();
+            let lost = lost.clone();
+            let finished = finished.clone();
+
+            thread::spawn(move || {
+                let mut send = |outgoing: Outgoing<SP>| {
+                    let (to, incoming) = match outgoing {
+                        Outgoing::Message(message_out) => {
+                            if message_out.from == sender
+                                && message_out.to == receiver
+                                && lost.lock().unwrap().insert(message_out.message.round_id().clone())
+                            {
+                                return Ok(());
+                            }
+                            let message_in = MessageIn {
+                                from: message_out.from,
+                                message: message_out.message,
+                            };
+                            (message_out.to, Incoming::Message(message_in))
+                        }
+                        Outgoing::ResendRequest { to, request } => (to, Incoming::ResendRequest(request)),
+                    };
+                    txs[&to]
+                        .send(incoming)
+                        .map_err(|_| LocalError::new("The destination has exited"))
+                };
+                // The transport does not notice the lost messages, so if nothing arrives for a while,
+                // the runner is prompted to request them.
+                let receive = |timeout| {
+                    Ok(match timeout {
+                        Some(timeout) => rx.recv_timeout(timeout).ok(),
+                        None => match rx.recv_timeout(resend_timeout) {
+                            Ok(incoming) => Some(incoming),
+                            Err(RecvTimeoutError::Timeout) => Some(Incoming::ResendTimeout),
+                            Err(RecvTimeoutError::Disconnected) => None,
+                        },
+                    })
+                };
+                let (report, outbox) =
+                    par_run_session_with_retransmission(&mut OsRng, num_threads, &mut send, receive, session).unwrap();
+                finished.fetch_add(1, Ordering::SeqCst);
+
+                // The other nodes may still need the messages of the last round.
+                while finished.load(Ordering::SeqCst) < 3 {
+                    if let Ok(Incoming::ResendRequest(request)) = rx.recv_timeout(resend_timeout) {
+                        if let Some(message) = outbox.respond(&request).unwrap() {
+                            let message_out = MessageOut {
+                                session_id: session_id.clone(),
+                                from: id,
+                                to: *request.requester(),
+                                message,
+                            };
+                            send(Outgoing::Message(message_out)).unwrap();
+                        }
+                    }
+                }
+
+                report
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        let report = handle.join().unwrap();
+        assert!(report.provable_errors.is_empty());
+        assert!(report.unprovable_errors.is_empty());
+        assert!(report.result().is_some());
+    }
+    assert!(lost.lock().unwrap().len() > 1);
+}
+
+#[test]
+fn thread_pool_run_with_retransmission_single_worker() {
+    thread_pool_run_with_retransmission(1)
+}
+
+#[test]
+fn thread_pool_run_with_retransmission_multiple_workers() {
+    thread_pool_run_with_retransmission(4)
+}
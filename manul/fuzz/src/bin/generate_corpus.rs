@@ -0,0 +1,19 @@
+//! Writes the seed inputs for each fuzzing target into `corpus/<target>`,
+//! where `cargo fuzz run` picks them up by default.
+
+use std::{fs, path::PathBuf};
+
+use manul_fuzz::Target;
+
+fn main() -> std::io::Result<()> {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("corpus");
+    for target in Target::ALL {
+        let dir = root.join(target.name());
+        fs::create_dir_all(&dir)?;
+        for (idx, seed) in target.seeds().into_iter().enumerate() {
+            fs::write(dir.join(format!("seed-{idx}")), seed)?;
+        }
+        println!("Wrote seeds for `{}` to {}", target.name(), dir.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,396 @@
+//! Fuzzing harnesses for the ingestion of untrusted data by [`Session`].
+//!
+//! Each harness takes arbitrary bytes, interprets them as something received from the network,
+//! and feeds them to a session executing the example protocol from `manul-example`.
+//! Whatever the input, the session is expected to attribute the failure to the sender
+//! (that is, produce a `RemoteError` or an `Evidence`), and never panic or return a `LocalError`.
+//!
+//! To run a harness (requires a nightly toolchain and `cargo-fuzz`), seed the corpus and start the fuzzer:
+//!
+//! ```text
+//! cargo run --bin generate-corpus
+//! cargo +nightly fuzz run message
+//! ```
+//!
+//! `cargo test` in this crate runs every harness on the seeds and their deterministic mutations,
+//! which works with a stable toolchain and without network access.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use manul::{
+    dev::{BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
+    session::{
+        CanFinalize, Evidence, EvidenceError, Message, RoundAccumulator, RoundOutcome, Session, SessionId,
+        SessionParameters, WireFormat,
+    },
+    signature::Keypair,
+};
+use manul_example::simple::{SimpleProtocol, SimpleProtocolEntryPoint};
+use rand_chacha::ChaCha8Rng;
+use rand_core::SeedableRng;
+
+/// The session parameters used by the harnesses.
+pub type SP = TestSessionParams<BinaryFormat>;
+
+type Id = TestVerifier;
+
+/// The number of parties in the session.
+const PARTIES: u8 = 3;
+
+/// The party receiving the fuzzed data.
+const VICTIM: u8 = 1;
+
+/// The stage of the protocol at which the victim receives the fuzzed message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// The first round of the protocol, with all the three kinds of message parts present.
+    Round1,
+    /// The echo round following the first round.
+    EchoRound,
+    /// The second (and the last) round of the protocol.
+    Round2,
+}
+
+impl Stage {
+    fn rounds_before(&self) -> usize {
+        match self {
+            Self::Round1 => 0,
+            Self::EchoRound => 1,
+            Self::Round2 => 2,
+        }
+    }
+}
+
+struct Node {
+    session: Session<SimpleProtocol, SP>,
+    accum: RoundAccumulator<SimpleProtocol, SP>,
+}
+
+/// The harness state: the victim's session at the requested stage,
+/// along with the honest messages other parties sent at that stage.
+struct Setup {
+    rng: ChaCha8Rng,
+    victim: Node,
+    /// Messages sent to the victim, along with the senders.
+    incoming: Vec<(Id, Message<Id>)>,
+    /// Messages sent to other parties, keyed by the sender and the destination.
+    others: BTreeMap<(Id, Id), Message<Id>>,
+}
+
+fn ids() -> BTreeSet<Id> {
+    (0..PARTIES).map(TestVerifier::new).collect()
+}
+
+fn deliver(node: &mut Node, from: &Id, message: Message<Id>) {
+    let preprocessed = node
+        .session
+        .preprocess_message(&mut node.accum, from, message)
+        .expect("preprocessing does not fail");
+    if let Some(verified) = preprocessed.ok() {
+        let processed = node.session.process_message(verified);
+        node.session
+            .add_processed_message(&mut node.accum, processed)
+            .expect("adding a processed message does not fail");
+    }
+}
+
+fn make_messages(rng: &mut ChaCha8Rng, node: &mut Node) -> Vec<(Id, Message<Id>)> {
+    let destinations = node.session.message_destinations().clone();
+    destinations
+        .into_iter()
+        .map(|destination| {
+            let (message, artifact) = node
+                .session
+                .make_message(rng, &destination)
+                .expect("message creation does not fail");
+            node.session
+                .add_artifact(&mut node.accum, artifact)
+                .expect("adding an artifact does not fail");
+            (destination, message)
+        })
+        .collect()
+}
+
+impl Setup {
+    fn new(stage: Stage) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        let session_id = SessionId::from_seed::<SP>(b"manul-fuzz");
+
+        let mut nodes = ids()
+            .into_iter()
+            .map(|id| {
+                let signer = TestSigner::new(id.id());
+                let entry_point = SimpleProtocolEntryPoint::new(ids());
+                let session = Session::<_, SP>::new(&mut rng, session_id.clone(), signer, entry_point)
+                    .expect("session creation does not fail");
+                let accum = session.make_accumulator();
+                (id, Node { session, accum })
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        // Execute the protocol in lockstep until the requested stage is reached.
+        for _ in 0..stage.rounds_before() {
+            let mut messages = Vec::new();
+            for (id, node) in nodes.iter_mut() {
+                for (destination, message) in make_messages(&mut rng, node) {
+                    messages.push((*id, destination, message));
+                }
+            }
+            for (from, destination, message) in messages {
+                let node = nodes.get_mut(&destination).expect("destination exists");
+                deliver(node, &from, message);
+            }
+            nodes = nodes
+                .into_iter()
+                .map(|(id, node)| {
+                    assert_eq!(node.session.can_finalize(&node.accum), CanFinalize::Yes);
+                    let outcome = node
+                        .session
+                        .finalize_round(&mut rng, node.accum)
+                        .expect("finalization does not fail");
+                    let session = match outcome {
+                        RoundOutcome::AnotherRound { session, .. } => session,
+                        RoundOutcome::Finished(_) => panic!("the protocol is not supposed to finish yet"),
+                    };
+                    let accum = session.make_accumulator();
+                    (id, Node { session, accum })
+                })
+                .collect();
+        }
+
+        // The victim makes its own messages too, as it would do on entering the round.
+        let victim_id = TestVerifier::new(VICTIM);
+        let mut incoming = Vec::new();
+        let mut others = BTreeMap::new();
+        for (id, node) in nodes.iter_mut() {
+            for (destination, message) in make_messages(&mut rng, node) {
+                if destination == victim_id {
+                    incoming.push((*id, message));
+                } else {
+                    others.insert((*id, destination), message);
+                }
+            }
+        }
+
+        let victim = nodes.remove(&victim_id).expect("the victim exists");
+
+        Self {
+            rng,
+            victim,
+            incoming,
+            others,
+        }
+    }
+
+    /// Delivers the given message to the victim, followed by all the honest messages of this round,
+    /// and attempts to finalize the round.
+    fn ingest(self, from: &Id, message: Message<Id>) {
+        let Self {
+            mut rng,
+            mut victim,
+            incoming,
+            ..
+        } = self;
+
+        deliver(&mut victim, from, message);
+        for (from, message) in incoming {
+            deliver(&mut victim, &from, message);
+        }
+
+        match victim.session.can_finalize(&victim.accum) {
+            CanFinalize::Yes => {
+                let outcome = victim
+                    .session
+                    .finalize_round(&mut rng, victim.accum)
+                    .expect("finalization does not fail");
+                if let RoundOutcome::AnotherRound {
+                    session,
+                    cached_messages,
+                } = outcome
+                {
+                    let mut accum = session.make_accumulator();
+                    for message in cached_messages {
+                        let processed = session.process_message(message);
+                        session
+                            .add_processed_message(&mut accum, processed)
+                            .expect("adding a processed message does not fail");
+                    }
+                    session.terminate(accum).expect("termination does not fail");
+                }
+            }
+            CanFinalize::NotYet => {
+                victim
+                    .session
+                    .terminate(victim.accum)
+                    .expect("termination does not fail");
+            }
+            CanFinalize::Never => {
+                victim
+                    .session
+                    .terminate_due_to_errors(victim.accum)
+                    .expect("termination does not fail");
+            }
+        }
+    }
+}
+
+/// Splits the input into the claimed sender of the message
+/// (one of the session parties, or someone from outside the session) and the message bytes.
+fn split_sender(data: &[u8]) -> Option<(Id, &[u8])> {
+    let (sender, message_bytes) = data.split_first()?;
+    Some((TestVerifier::new(sender % (PARTIES + 1)), message_bytes))
+}
+
+fn ingest_message(stage: Stage, data: &[u8]) {
+    let Some((from, message_bytes)) = split_sender(data) else {
+        return;
+    };
+    let Ok(message) = <SP as SessionParameters>::WireFormat::deserialize::<Message<Id>>(message_bytes) else {
+        return;
+    };
+    Setup::new(stage).ingest(&from, message);
+}
+
+/// Feeds a message to a session in one of the regular rounds.
+///
+/// The first byte selects the round, the second selects the sender, and the rest is the serialized message.
+pub fn fuzz_message(data: &[u8]) {
+    let Some((stage_selector, data)) = data.split_first() else {
+        return;
+    };
+    let stage = if stage_selector % 2 == 0 {
+        Stage::Round1
+    } else {
+        Stage::Round2
+    };
+    ingest_message(stage, data);
+}
+
+/// Feeds a message to a session in an echo round.
+///
+/// The first byte selects the sender, and the rest is the serialized message.
+pub fn fuzz_echo_round_message(data: &[u8]) {
+    ingest_message(Stage::EchoRound, data)
+}
+
+/// Deserializes and verifies evidence.
+pub fn fuzz_evidence(data: &[u8]) {
+    let Ok(evidence) = <SP as SessionParameters>::WireFormat::deserialize::<Evidence<SimpleProtocol, SP>>(data) else {
+        return;
+    };
+    match evidence.verify(&()) {
+        Ok(()) | Err(EvidenceError::InvalidEvidence(_)) => {}
+        Err(EvidenceError::Local(error)) => panic!("Evidence verification failed with a local error: {error}"),
+    }
+}
+
+/// The fuzzing targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// See [`fuzz_message`].
+    Message,
+    /// See [`fuzz_echo_round_message`].
+    EchoRoundMessage,
+    /// See [`fuzz_evidence`].
+    Evidence,
+}
+
+impl Target {
+    /// All the available targets.
+    pub const ALL: [Self; 3] = [Self::Message, Self::EchoRoundMessage, Self::Evidence];
+
+    /// The name of the target as used by `cargo fuzz`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Message => "message",
+            Self::EchoRoundMessage => "echo_round_message",
+            Self::Evidence => "evidence",
+        }
+    }
+
+    /// Runs the harness for this target.
+    pub fn run(&self, data: &[u8]) {
+        match self {
+            Self::Message => fuzz_message(data),
+            Self::EchoRoundMessage => fuzz_echo_round_message(data),
+            Self::Evidence => fuzz_evidence(data),
+        }
+    }
+
+    /// Returns valid inputs for the target to start fuzzing from.
+    pub fn seeds(&self) -> Vec<Vec<u8>> {
+        match self {
+            Self::Message => [(0u8, Stage::Round1), (1u8, Stage::Round2)]
+                .into_iter()
+                .flat_map(|(stage_selector, stage)| {
+                    message_seeds(stage)
+                        .into_iter()
+                        .map(move |seed| [vec![stage_selector], seed].concat())
+                })
+                .collect(),
+            Self::EchoRoundMessage => message_seeds(Stage::EchoRound),
+            Self::Evidence => evidence_seeds(),
+        }
+    }
+}
+
+fn serialize<T: serde::Serialize>(value: T) -> Vec<u8> {
+    <SP as SessionParameters>::WireFormat::serialize(value)
+        .expect("serialization does not fail")
+        .into()
+}
+
+fn message_seeds(stage: Stage) -> Vec<Vec<u8>> {
+    let Setup { mut rng, incoming, .. } = Setup::new(stage);
+
+    // A validly signed message from a party outside of the session.
+    let outsider = TestSigner::new(PARTIES);
+    let (_from, message) = incoming.first().expect("the victim receives messages").clone();
+    let outsider_message = message
+        .resigned::<SP>(&mut rng, &outsider)
+        .expect("re-signing does not fail");
+
+    incoming
+        .into_iter()
+        .chain([(outsider.verifying_key(), outsider_message)])
+        .map(|(from, message)| [vec![from.id()], serialize(message)].concat())
+        .collect()
+}
+
+fn evidence_seeds() -> Vec<Vec<u8>> {
+    let guilty = TestVerifier::new(0);
+    let other = TestVerifier::new(2);
+
+    // Send the victim the direct message intended for another party,
+    // which constitutes a provable error in both rounds of the example protocol.
+    [Stage::Round1, Stage::Round2]
+        .into_iter()
+        .map(|stage| {
+            let mut setup = Setup::new(stage);
+            let message_to_victim = setup
+                .incoming
+                .iter()
+                .find(|(from, _message)| from == &guilty)
+                .map(|(_from, message)| message.clone())
+                .expect("the guilty party sent a message to the victim");
+            let message_to_other = setup
+                .others
+                .get(&(guilty, other))
+                .expect("the guilty party sent a message to the other party");
+            let tampered = message_to_victim.with_direct_message_of(message_to_other);
+
+            deliver(&mut setup.victim, &guilty, tampered);
+            let report = setup
+                .victim
+                .session
+                .terminate(setup.victim.accum)
+                .expect("termination does not fail");
+            let evidence = report
+                .provable_errors
+                .get(&guilty)
+                .expect("the provable error has been registered");
+            assert!(evidence.verify(&()).is_ok());
+            serialize(evidence)
+        })
+        .collect()
+}
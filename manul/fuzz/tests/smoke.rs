@@ -0,0 +1,72 @@
+//! Runs the fuzzing harnesses on the seed inputs and their deterministic mutations.
+//!
+//! This does not replace an actual fuzzing session, but can be executed with a stable toolchain
+//! and catches regressions in the harnesses themselves.
+
+use manul_fuzz::Target;
+use rand_chacha::ChaCha8Rng;
+use rand_core::{RngCore, SeedableRng};
+
+const MUTATIONS_PER_SEED: usize = 200;
+
+fn position(rng: &mut impl RngCore, len: usize) -> usize {
+    (rng.next_u32() as usize) % len.max(1)
+}
+
+fn mutate(rng: &mut impl RngCore, data: &[u8]) -> Vec<u8> {
+    let mut data = data.to_vec();
+    match rng.next_u32() % 4 {
+        // Flip a random bit
+        0 if !data.is_empty() => {
+            let idx = position(rng, data.len());
+            data[idx] ^= 1 << (rng.next_u32() % 8);
+        }
+        // Replace a random byte
+        1 if !data.is_empty() => {
+            let idx = position(rng, data.len());
+            data[idx] = rng.next_u32() as u8;
+        }
+        // Truncate
+        2 => {
+            let len = position(rng, data.len());
+            data.truncate(len);
+        }
+        // Insert a random byte
+        _ => {
+            let idx = position(rng, data.len() + 1);
+            data.insert(idx.min(data.len()), rng.next_u32() as u8);
+        }
+    }
+    data
+}
+
+fn check_target(target: Target) {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let seeds = target.seeds();
+    assert!(!seeds.is_empty());
+    for seed in seeds {
+        target.run(&seed);
+        let mut data = seed.clone();
+        for _ in 0..MUTATIONS_PER_SEED {
+            // Alternate between fresh single mutations and accumulating ones.
+            let base = if rng.next_u32() % 2 == 0 { &seed } else { &data };
+            data = mutate(&mut rng, base);
+            target.run(&data);
+        }
+    }
+}
+
+#[test]
+fn message() {
+    check_target(Target::Message);
+}
+
+#[test]
+fn echo_round_message() {
+    check_target(Target::EchoRoundMessage);
+}
+
+#[test]
+fn evidence() {
+    check_target(Target::Evidence);
+}
@@ -340,21 +340,21 @@ where
     fn transition_info(&self) -> TransitionInfo {
         match &self.state {
             ChainState::Protocol1 { round, .. } => {
-                let mut tinfo = round.as_ref().transition_info().group_under(1);
+                let mut tinfo = round.transition_info().group_under(1);
                 if tinfo.may_produce_result {
                     tinfo.may_produce_result = false;
                     tinfo.children.insert(T::EntryPoint::entry_round_id().group_under(2));
                 }
                 tinfo
             }
-            ChainState::Protocol2(round) => round.as_ref().transition_info().group_under(2),
+            ChainState::Protocol2(round) => round.transition_info().group_under(2),
         }
     }
 
     fn communication_info(&self) -> CommunicationInfo<Id> {
         match &self.state {
-            ChainState::Protocol1 { round, .. } => round.as_ref().communication_info(),
-            ChainState::Protocol2(round) => round.as_ref().communication_info(),
+            ChainState::Protocol1 { round, .. } => round.communication_info(),
+            ChainState::Protocol2(round) => round.communication_info(),
         }
     }
 
@@ -365,8 +365,8 @@ where
         destination: &Id,
     ) -> Result<(DirectMessage, Option<Artifact>), LocalError> {
         match &self.state {
-            ChainState::Protocol1 { round, .. } => round.as_ref().make_direct_message(rng, format, destination),
-            ChainState::Protocol2(round) => round.as_ref().make_direct_message(rng, format, destination),
+            ChainState::Protocol1 { round, .. } => round.make_direct_message(rng, format, destination),
+            ChainState::Protocol2(round) => round.make_direct_message(rng, format, destination),
         }
     }
 
@@ -376,8 +376,8 @@ where
         format: &BoxedFormat,
     ) -> Result<EchoBroadcast, LocalError> {
         match &self.state {
-            ChainState::Protocol1 { round, .. } => round.as_ref().make_echo_broadcast(rng, format),
-            ChainState::Protocol2(round) => round.as_ref().make_echo_broadcast(rng, format),
+            ChainState::Protocol1 { round, .. } => round.make_echo_broadcast(rng, format),
+            ChainState::Protocol2(round) => round.make_echo_broadcast(rng, format),
         }
     }
 
@@ -387,8 +387,8 @@ where
         format: &BoxedFormat,
     ) -> Result<NormalBroadcast, LocalError> {
         match &self.state {
-            ChainState::Protocol1 { round, .. } => round.as_ref().make_normal_broadcast(rng, format),
-            ChainState::Protocol2(round) => round.as_ref().make_normal_broadcast(rng, format),
+            ChainState::Protocol1 { round, .. } => round.make_normal_broadcast(rng, format),
+            ChainState::Protocol2(round) => round.make_normal_broadcast(rng, format),
         }
     }
 
@@ -399,11 +399,11 @@ where
         message: ProtocolMessage,
     ) -> Result<Payload, ReceiveError<Id, Self::Protocol>> {
         match &self.state {
-            ChainState::Protocol1 { round, .. } => match round.as_ref().receive_message(format, from, message) {
+            ChainState::Protocol1 { round, .. } => match round.receive_message(format, from, message) {
                 Ok(payload) => Ok(payload),
                 Err(err) => Err(err.map(ChainedProtocolError::from_protocol1)),
             },
-            ChainState::Protocol2(round) => match round.as_ref().receive_message(format, from, message) {
+            ChainState::Protocol2(round) => match round.receive_message(format, from, message) {
                 Ok(payload) => Ok(payload),
                 Err(err) => Err(err.map(ChainedProtocolError::from_protocol2)),
             },
@@ -422,7 +422,7 @@ where
                 round,
                 transition,
                 shared_randomness,
-            } => match round.into_boxed().finalize(rng, payloads, artifacts)? {
+            } => match round.finalize(rng, payloads, artifacts)? {
                 FinalizeOutcome::Result(result) => {
                     let entry_point2 = transition.make_entry_point2(result);
                     let round = entry_point2.make_round(rng, &shared_randomness, &id)?;
@@ -443,7 +443,7 @@ where
                     Ok(FinalizeOutcome::AnotherRound(BoxedRound::new_dynamic(chained_round)))
                 }
             },
-            ChainState::Protocol2(round) => match round.into_boxed().finalize(rng, payloads, artifacts)? {
+            ChainState::Protocol2(round) => match round.finalize(rng, payloads, artifacts)? {
                 FinalizeOutcome::Result(result) => Ok(FinalizeOutcome::Result(result)),
                 FinalizeOutcome::AnotherRound(round) => {
                     let chained_round = ChainedRound::<Id, T> {
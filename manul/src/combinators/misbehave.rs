@@ -228,11 +228,11 @@ where
     type Protocol = <M::EntryPoint as EntryPoint<Id>>::Protocol;
 
     fn transition_info(&self) -> TransitionInfo {
-        self.round.as_ref().transition_info()
+        self.round.transition_info()
     }
 
     fn communication_info(&self) -> CommunicationInfo<Id> {
-        self.round.as_ref().communication_info()
+        self.round.communication_info()
     }
 
     fn make_direct_message(
@@ -241,7 +241,7 @@ where
         format: &BoxedFormat,
         destination: &Id,
     ) -> Result<(DirectMessage, Option<Artifact>), LocalError> {
-        let (direct_message, artifact) = self.round.as_ref().make_direct_message(rng, format, destination)?;
+        let (direct_message, artifact) = self.round.make_direct_message(rng, format, destination)?;
         if let Some(behavior) = self.behavior.as_ref() {
             M::modify_direct_message(
                 rng,
@@ -262,7 +262,7 @@ where
         rng: &mut dyn CryptoRngCore,
         format: &BoxedFormat,
     ) -> Result<EchoBroadcast, LocalError> {
-        let echo_broadcast = self.round.as_ref().make_echo_broadcast(rng, format)?;
+        let echo_broadcast = self.round.make_echo_broadcast(rng, format)?;
         if let Some(behavior) = self.behavior.as_ref() {
             M::modify_echo_broadcast(rng, &self.round, behavior, format, echo_broadcast)
         } else {
@@ -275,7 +275,7 @@ where
         rng: &mut dyn CryptoRngCore,
         format: &BoxedFormat,
     ) -> Result<NormalBroadcast, LocalError> {
-        let normal_broadcast = self.round.as_ref().make_normal_broadcast(rng, format)?;
+        let normal_broadcast = self.round.make_normal_broadcast(rng, format)?;
         if let Some(behavior) = self.behavior.as_ref() {
             M::modify_normal_broadcast(rng, &self.round, behavior, format, normal_broadcast)
         } else {
@@ -289,7 +289,7 @@ where
         from: &Id,
         message: ProtocolMessage,
     ) -> Result<Payload, ReceiveError<Id, Self::Protocol>> {
-        self.round.as_ref().receive_message(format, from, message)
+        self.round.receive_message(format, from, message)
     }
 
     fn finalize(
@@ -312,7 +312,7 @@ where
             (self.round, payloads, artifacts)
         };
 
-        let outcome = round.into_boxed().finalize(rng, payloads, artifacts)?;
+        let outcome = round.finalize(rng, payloads, artifacts)?;
         Ok(Self::map_outcome(outcome, self.behavior))
     }
 }
@@ -6,15 +6,39 @@ The [`TestSessionParams`] provides an implementation of the
 which in turn is used to setup [`Session`](crate::session::Session)s to drive the protocol.
 
 The [`run_sync()`] method is helpful to execute a protocol synchronously and collect the outcomes.
+Its counterpart [`run_sync_with_interceptor()`] additionally passes every message
+through a [`MessageInterceptor`], allowing one to test how sessions handle tampered, replayed, or dropped messages.
+[`run_sync_scheduled()`] also takes a [`MessageSchedule`] determining the order of message delivery.
+[`run_sync_with_config()`] creates the sessions with a custom [`SessionConfig`](crate::session::SessionConfig).
+[`run_sync_configured()`] combines all of the above.
+
+The resulting [`ExecutionResult`] has methods to check common properties,
+such as honest nodes agreeing on the result, or malicious nodes being blamed.
+With the `proptest` feature enabled, the [`proptest`] module provides strategies
+for generating the inputs to the above functions.
+With the `futures` feature enabled, the [`futures`] module executes the sessions concurrently
+in the tasks launched by a user-provided [`Spawner`](crate::session::futures::Spawner),
+and with the `tokio` feature enabled the [`tokio`] module does the same within a `tokio` runtime.
 */
 
+mod interceptor;
 mod run_sync;
 mod session_parameters;
 mod wire_format;
 
+#[cfg(feature = "futures")]
+pub mod futures;
+
 #[cfg(feature = "tokio")]
 pub mod tokio;
 
-pub use run_sync::{run_sync, ExecutionResult};
+#[cfg(feature = "proptest")]
+pub mod proptest;
+
+pub use interceptor::{MessageInterceptor, PassThrough};
+pub use run_sync::{
+    run_sync, run_sync_configured, run_sync_scheduled, run_sync_with_config, run_sync_with_interceptor,
+    ExecutionResult, MessageSchedule,
+};
 pub use session_parameters::{TestHasher, TestSessionParams, TestSignature, TestSigner, TestVerifier};
 pub use wire_format::{BinaryFormat, HumanReadableFormat};
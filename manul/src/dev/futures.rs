@@ -0,0 +1,250 @@
+//! Runtime-agnostic development utilities.
+
+use alloc::{boxed::Box, collections::BTreeMap, format, vec::Vec};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    future, SinkExt, StreamExt,
+};
+use rand::Rng;
+use rand_core::CryptoRngCore;
+
+use super::{
+    interceptor::{MessageInterceptor, PassThrough},
+    run_sync::ExecutionResult,
+};
+use crate::{
+    protocol::{AsyncEntryPoint, EntryPoint, Protocol, SyncEntryPoint},
+    session::{
+        futures::{par_run_session, run_session, MessageIn, MessageOut, Spawner},
+        AsyncSessionParameters, LocalError, LocalErrorKind, Session, SessionId, SessionParameters,
+    },
+};
+
+/// Gives up the execution once, letting the other tasks make progress.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+async fn message_dispatcher<SP>(
+    rng: impl CryptoRngCore,
+    txs: BTreeMap<SP::Verifier, mpsc::UnboundedSender<MessageIn<SP>>>,
+    rx: mpsc::UnboundedReceiver<MessageOut<SP>>,
+    interceptor: impl MessageInterceptor<SP>,
+) -> Result<(), LocalError>
+where
+    SP: SessionParameters,
+{
+    let mut rng = rng;
+    let mut interceptor = interceptor;
+    let mut txs = txs;
+
+    let mut rx = rx;
+    let mut messages = Vec::<MessageOut<SP>>::new();
+    loop {
+        let msg = match rx.next().await {
+            Some(msg) => msg,
+            None => return Ok(()),
+        };
+        messages.push(msg);
+
+        while let Ok(msg) = rx.try_recv() {
+            messages.push(msg)
+        }
+
+        while !messages.is_empty() {
+            // Pull a random message from the list,
+            // to increase the chances that they are delivered out of order.
+            let message_idx = rng.gen_range(0..messages.len());
+            let outgoing = messages.swap_remove(message_idx);
+
+            let tx = txs.get_mut(&outgoing.to).ok_or_else(|| {
+                LocalError::new(format!(
+                    "Destination ({:?}) is missing in the map of channels",
+                    outgoing.to
+                ))
+            })?;
+
+            let delivered = interceptor.intercept(&mut rng, &outgoing.from, &outgoing.to, outgoing.message)?;
+            for message in delivered {
+                tx.send(MessageIn {
+                    from: outgoing.from.clone(),
+                    message,
+                })
+                .await
+                .map_err(|err| {
+                    LocalError::new_with_kind(
+                        LocalErrorKind::Transport,
+                        format!("Could not sent an outgoing message: {err}"),
+                    )
+                })?;
+            }
+
+            // Give up execution so that the tasks could process messages.
+            YieldNow(false).await;
+
+            if let Ok(msg) = rx.try_recv() {
+                messages.push(msg);
+            };
+        }
+    }
+}
+
+/// Execute sessions for multiple nodes concurrently, with the tasks launched by `spawner`,
+/// given a vector of the signer and the entry point as a tuple for each node.
+///
+/// If `offload_processing` is `true`, message creation and verification will be launched in separate tasks.
+pub async fn run_async<EP, SP>(
+    rng: &mut (impl 'static + CryptoRngCore + Clone + Send),
+    spawner: &impl Spawner,
+    entry_points: Vec<(SP::Signer, EP)>,
+    offload_processing: bool,
+) -> Result<ExecutionResult<EP::Protocol, SP>, LocalError>
+where
+    EP: EntryPoint<SP::Verifier> + Send,
+    SP: AsyncSessionParameters,
+    <EP::Protocol as Protocol<SP::Verifier>>::ProtocolError: Send + Sync,
+    <EP::Protocol as Protocol<SP::Verifier>>::Result: Send,
+{
+    run_async_with_interceptor(rng, spawner, entry_points, offload_processing, PassThrough).await
+}
+
+/// Execute sessions for multiple nodes concurrently, with the tasks launched by `spawner`,
+/// given a vector of the signer and the entry point as a tuple for each node,
+/// passing every message through the given interceptor before delivering it.
+///
+/// If `offload_processing` is `true`, message creation and verification will be launched in separate tasks.
+pub async fn run_async_with_interceptor<EP, SP>(
+    rng: &mut (impl 'static + CryptoRngCore + Clone + Send),
+    spawner: &impl Spawner,
+    entry_points: Vec<(SP::Signer, EP)>,
+    offload_processing: bool,
+    interceptor: impl 'static + MessageInterceptor<SP> + Send,
+) -> Result<ExecutionResult<EP::Protocol, SP>, LocalError>
+where
+    EP: EntryPoint<SP::Verifier> + Send,
+    SP: AsyncSessionParameters,
+    <EP::Protocol as Protocol<SP::Verifier>>::ProtocolError: Send + Sync,
+    <EP::Protocol as Protocol<SP::Verifier>>::Result: Send,
+{
+    let entry_points = entry_points
+        .into_iter()
+        .map(|(signer, entry_point)| (signer, SyncEntryPoint::new(entry_point)))
+        .collect();
+    let sessions = make_sessions(rng, entry_points).await?;
+    run_sessions(rng, spawner, sessions, offload_processing, interceptor).await
+}
+
+// The sessions are created asynchronously, so that the signers signing asynchronously could be used.
+pub(crate) async fn make_sessions<EP, SP>(
+    rng: &mut (impl CryptoRngCore + Send),
+    entry_points: Vec<(SP::Signer, EP)>,
+) -> Result<Vec<Session<EP::Protocol, SP>>, LocalError>
+where
+    EP: AsyncEntryPoint<SP::Verifier>,
+    SP: AsyncSessionParameters,
+{
+    let session_id = SessionId::random::<SP>(rng);
+    let mut sessions = Vec::with_capacity(entry_points.len());
+    for (signer, entry_point) in entry_points {
+        sessions.push(Session::<_, SP>::new_async(rng, session_id.clone(), signer, entry_point).await?);
+    }
+    Ok(sessions)
+}
+
+/// Spawns the given future, returning the receiver for its output.
+fn spawn_with_output<T: 'static + Send>(
+    spawner: &impl Spawner,
+    future: impl 'static + Future<Output = T> + Send,
+) -> oneshot::Receiver<T> {
+    let (output_tx, output_rx) = oneshot::channel();
+    spawner.spawn(Box::pin(async move {
+        // The receiver is only dropped if the execution has already failed.
+        let _ = output_tx.send(future.await);
+    }));
+    output_rx
+}
+
+pub(crate) async fn run_sessions<P, SP>(
+    rng: &mut (impl 'static + CryptoRngCore + Clone + Send),
+    spawner: &impl Spawner,
+    sessions: Vec<Session<P, SP>>,
+    offload_processing: bool,
+    interceptor: impl 'static + MessageInterceptor<SP> + Send,
+) -> Result<ExecutionResult<P, SP>, LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    P::ProtocolError: Send + Sync,
+    P::Result: Send,
+{
+    let num_parties = sessions.len();
+
+    let (dispatcher_tx, dispatcher_rx) = mpsc::unbounded::<MessageOut<SP>>();
+
+    let channels = (0..num_parties).map(|_| mpsc::unbounded::<MessageIn<SP>>());
+    let (txs, rxs): (Vec<_>, Vec<_>) = channels.unzip();
+    let tx_map = sessions.iter().map(|session| session.verifier()).zip(txs).collect();
+
+    let dispatcher = spawn_with_output(
+        spawner,
+        message_dispatcher(rng.clone(), tx_map, dispatcher_rx, interceptor),
+    );
+
+    let handles = rxs
+        .into_iter()
+        .zip(sessions)
+        .map(|(mut rx, session)| {
+            let mut tx = dispatcher_tx.clone();
+            let mut rng = rng.clone();
+            let task_spawner = spawner.clone();
+
+            let id = session.verifier().clone();
+
+            let node_task = async move {
+                if offload_processing {
+                    par_run_session(&mut rng, &task_spawner, &mut tx, &mut rx, future::pending(), session).await
+                } else {
+                    run_session(&mut rng, &mut tx, &mut rx, future::pending(), session).await
+                }
+            };
+            (id, spawn_with_output(spawner, node_task))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    // Drop the last copy of the dispatcher's incoming channel so that it can finish.
+    drop(dispatcher_tx);
+
+    let mut reports = BTreeMap::new();
+    for (id, handle) in handles {
+        reports.insert(
+            id.clone(),
+            handle
+                .await
+                .map_err(|_| LocalError::new(format!("The task of {id:?} was dropped")))??,
+        );
+    }
+
+    dispatcher
+        .await
+        .map_err(|_| LocalError::new("The message dispatcher task was dropped"))??;
+
+    Ok(ExecutionResult { reports })
+}
@@ -0,0 +1,40 @@
+use alloc::{vec, vec::Vec};
+
+use rand_core::CryptoRngCore;
+
+use crate::session::{LocalError, Message, SessionParameters};
+
+/// A hook placed between the creation of a message by the sender's session and its delivery to the receiver.
+///
+/// It can be used to emulate an adversary in control of the transport (or of the sender's signing key)
+/// that tampers with the signed envelopes, instead of the protocol messages inside them
+/// (for which see [`Misbehaving`](crate::combinators::misbehave::Misbehaving)).
+pub trait MessageInterceptor<SP: SessionParameters> {
+    /// Called for every message sent from `from` to `to`.
+    ///
+    /// Returns the messages that will be delivered to `to` as if sent by `from`, in order.
+    /// Returning an empty vector drops the message, returning several allows one to replay messages.
+    fn intercept(
+        &mut self,
+        rng: &mut dyn CryptoRngCore,
+        from: &SP::Verifier,
+        to: &SP::Verifier,
+        message: Message<SP::Verifier>,
+    ) -> Result<Vec<Message<SP::Verifier>>, LocalError>;
+}
+
+/// An interceptor that delivers every message unchanged.
+#[derive(Debug, Clone, Copy)]
+pub struct PassThrough;
+
+impl<SP: SessionParameters> MessageInterceptor<SP> for PassThrough {
+    fn intercept(
+        &mut self,
+        _rng: &mut dyn CryptoRngCore,
+        _from: &SP::Verifier,
+        _to: &SP::Verifier,
+        message: Message<SP::Verifier>,
+    ) -> Result<Vec<Message<SP::Verifier>>, LocalError> {
+        Ok(vec![message])
+    }
+}
@@ -0,0 +1,58 @@
+/*!
+[`proptest`] strategies for stating properties of protocol executions.
+
+The generated values are meant to be fed to [`run_sync_scheduled`](super::run_sync_scheduled),
+and the outcome checked with the assertion methods of [`ExecutionResult`](super::ExecutionResult),
+for example [`check_agreement`](super::ExecutionResult::check_agreement)
+or [`check_attribution`](super::ExecutionResult::check_attribution).
+*/
+
+use alloc::{collections::BTreeSet, vec::Vec};
+use core::fmt::Debug;
+
+use proptest::{
+    collection::{btree_set, SizeRange},
+    prelude::{any, Strategy},
+};
+use signature::Keypair;
+
+use super::{MessageSchedule, TestSigner, TestVerifier};
+
+/// Generates sets of signers with distinct IDs, their number being within `party_counts`.
+pub fn signers(party_counts: impl Into<SizeRange>) -> impl Strategy<Value = Vec<TestSigner>> {
+    btree_set(any::<u8>(), party_counts).prop_map(|ids| ids.into_iter().map(TestSigner::new).collect())
+}
+
+/// Generates entry points for each signer produced by `signers`.
+///
+/// `make_entry_point` is called with the IDs of all the parties and the ID of the party the entry point is for,
+/// and returns a strategy for the entry point (use [`Just`](proptest::strategy::Just)
+/// if it does not need any random input).
+pub fn entry_points<EP, S, F>(
+    signers: impl Strategy<Value = Vec<TestSigner>>,
+    make_entry_point: F,
+) -> impl Strategy<Value = Vec<(TestSigner, EP)>>
+where
+    EP: Debug,
+    S: Strategy<Value = EP>,
+    F: Fn(&BTreeSet<TestVerifier>, &TestVerifier) -> S,
+{
+    signers.prop_flat_map(move |signers| {
+        let ids = signers
+            .iter()
+            .map(|signer| signer.verifying_key())
+            .collect::<BTreeSet<_>>();
+        signers
+            .into_iter()
+            .map(|signer| make_entry_point(&ids, &signer.verifying_key()).prop_map(move |ep| (signer, ep)))
+            .collect::<Vec<_>>()
+    })
+}
+
+/// Generates message delivery schedules.
+pub fn message_schedules() -> impl Strategy<Value = MessageSchedule> {
+    (any::<u64>(), any::<bool>()).prop_map(|(seed, preserve_sender_order)| MessageSchedule {
+        seed,
+        preserve_sender_order,
+    })
+}
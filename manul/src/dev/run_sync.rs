@@ -1,18 +1,27 @@
-use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec::Vec,
+};
 
 use rand::Rng;
-use rand_core::CryptoRngCore;
+use rand_chacha::ChaCha8Rng;
+use rand_core::{CryptoRngCore, SeedableRng};
 use signature::Keypair;
 use tracing::{debug, trace, warn};
 
+use super::interceptor::{MessageInterceptor, PassThrough};
 use crate::{
     protocol::{EntryPoint, Protocol},
     session::{
-        CanFinalize, LocalError, Message, RoundAccumulator, RoundOutcome, Session, SessionId, SessionOutcome,
-        SessionParameters, SessionReport,
+        CanFinalize, LocalError, Message, RoundAccumulator, RoundOutcome, Session, SessionConfig, SessionId,
+        SessionOutcome, SessionParameters, SessionReport,
     },
 };
 
+// `Session` is large, but there is only one of these per node.
+#[allow(clippy::large_enum_variant)]
 enum State<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     InProgress {
         session: Session<P, SP>,
@@ -179,18 +188,111 @@ pub fn run_sync<EP, SP>(
     rng: &mut impl CryptoRngCore,
     entry_points: Vec<(SP::Signer, EP)>,
 ) -> Result<ExecutionResult<EP::Protocol, SP>, LocalError>
+where
+    EP: EntryPoint<SP::Verifier>,
+    SP: SessionParameters,
+{
+    run_sync_with_interceptor(rng, entry_points, &mut PassThrough)
+}
+
+/// Execute sessions for multiple nodes in a single thread,
+/// given a vector of the signer and the entry point as a tuple for each node,
+/// passing every message through the given interceptor before delivering it.
+pub fn run_sync_with_interceptor<EP, SP>(
+    rng: &mut impl CryptoRngCore,
+    entry_points: Vec<(SP::Signer, EP)>,
+    interceptor: &mut impl MessageInterceptor<SP>,
+) -> Result<ExecutionResult<EP::Protocol, SP>, LocalError>
+where
+    EP: EntryPoint<SP::Verifier>,
+    SP: SessionParameters,
+{
+    let schedule = MessageSchedule::random(rng);
+    run_sync_scheduled(rng, entry_points, schedule, interceptor)
+}
+
+/// Execute sessions for multiple nodes in a single thread,
+/// given a vector of the signer and the entry point as a tuple for each node,
+/// with every session created with the given configuration.
+pub fn run_sync_with_config<EP, SP>(
+    rng: &mut impl CryptoRngCore,
+    entry_points: Vec<(SP::Signer, EP)>,
+    config: SessionConfig<SP::Verifier>,
+) -> Result<ExecutionResult<EP::Protocol, SP>, LocalError>
+where
+    EP: EntryPoint<SP::Verifier>,
+    SP: SessionParameters,
+{
+    let schedule = MessageSchedule::random(rng);
+    run_sync_configured(rng, entry_points, config, schedule, &mut PassThrough)
+}
+
+/// The order in which [`run_sync_scheduled`] delivers the messages.
+///
+/// The same schedule applied to the same protocol execution results in the same delivery order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageSchedule {
+    /// The seed for the RNG picking the next message to deliver.
+    pub seed: u64,
+    /// If `true`, the messages from each sender are delivered in the order they were sent,
+    /// otherwise the order is completely random.
+    pub preserve_sender_order: bool,
+}
+
+impl MessageSchedule {
+    /// Creates a random schedule preserving the order of messages from each sender.
+    pub fn random(rng: &mut impl CryptoRngCore) -> Self {
+        Self {
+            seed: rng.next_u64(),
+            preserve_sender_order: true,
+        }
+    }
+}
+
+/// Execute sessions for multiple nodes in a single thread,
+/// given a vector of the signer and the entry point as a tuple for each node,
+/// delivering the messages in the order determined by `schedule`,
+/// and passing every message through the given interceptor before delivering it.
+pub fn run_sync_scheduled<EP, SP>(
+    rng: &mut impl CryptoRngCore,
+    entry_points: Vec<(SP::Signer, EP)>,
+    schedule: MessageSchedule,
+    interceptor: &mut impl MessageInterceptor<SP>,
+) -> Result<ExecutionResult<EP::Protocol, SP>, LocalError>
+where
+    EP: EntryPoint<SP::Verifier>,
+    SP: SessionParameters,
+{
+    run_sync_configured(rng, entry_points, SessionConfig::default(), schedule, interceptor)
+}
+
+/// Execute sessions for multiple nodes in a single thread,
+/// given a vector of the signer and the entry point as a tuple for each node,
+/// with every session created with the given configuration,
+/// delivering the messages in the order determined by `schedule`,
+/// and passing every message through the given interceptor before delivering it.
+///
+/// This is the most general form of the functions above.
+pub fn run_sync_configured<EP, SP>(
+    rng: &mut impl CryptoRngCore,
+    entry_points: Vec<(SP::Signer, EP)>,
+    config: SessionConfig<SP::Verifier>,
+    schedule: MessageSchedule,
+    interceptor: &mut impl MessageInterceptor<SP>,
+) -> Result<ExecutionResult<EP::Protocol, SP>, LocalError>
 where
     EP: EntryPoint<SP::Verifier>,
     SP: SessionParameters,
 {
     let session_id = SessionId::random::<SP>(rng);
 
-    let mut messages = Messages::new(true);
+    let mut schedule_rng = ChaCha8Rng::seed_from_u64(schedule.seed);
+    let mut messages = Messages::new(schedule.preserve_sender_order);
     let mut states = BTreeMap::new();
 
     for (signer, entry_point) in entry_points {
         let verifier = signer.verifying_key();
-        let session = Session::<_, SP>::new(rng, session_id.clone(), signer, entry_point)?;
+        let session = Session::<_, SP>::new_with_config(rng, session_id.clone(), signer, entry_point, config.clone())?;
         let mut accum = session.make_accumulator();
 
         let destinations = session.message_destinations();
@@ -210,9 +312,9 @@ where
     }
 
     let messages_len = messages.len();
-    loop {
+    while !messages.is_empty() {
         // Pick a random message and deliver it
-        let message = messages.pop(rng);
+        let message = messages.pop(&mut schedule_rng);
 
         debug!(
             "Delivering message from {:?} to {:?} ({}/{})",
@@ -229,29 +331,29 @@ where
             );
             panic!("Expected the message destination to be one of the sessions",);
         }
-        let state = state.expect("Checked above");
-        let new_state = if let State::InProgress { session, accum } = state {
-            let mut accum = accum;
-            let preprocessed = session.preprocess_message(&mut accum, &message.from, message.message)?;
-
-            if let Some(verified) = preprocessed.ok() {
-                let processed = session.process_message(verified);
-                session.add_processed_message(&mut accum, processed)?;
-            }
+        let mut state = state.expect("Checked above");
 
-            let (new_state, new_messages) = propagate(rng, session, accum)?;
-            messages.extend(new_messages);
-            new_state
-        } else {
-            state
-        };
-        states.insert(message.to.clone(), new_state);
+        let delivered = interceptor.intercept(rng, &message.from, &message.to, message.message)?;
+        for delivered_message in delivered {
+            state = if let State::InProgress { session, accum } = state {
+                let mut accum = accum;
+                let preprocessed = session.preprocess_message(&mut accum, &message.from, delivered_message)?;
+
+                if let Some(verified) = preprocessed.ok() {
+                    let processed = session.process_message(verified);
+                    session.add_processed_message(&mut accum, processed)?;
+                }
 
-        if messages.is_empty() {
-            trace!("All messages delivered, exiting loop");
-            break;
+                let (new_state, new_messages) = propagate(rng, session, accum)?;
+                messages.extend(new_messages);
+                new_state
+            } else {
+                state
+            };
         }
+        states.insert(message.to.clone(), state);
     }
+    trace!("All messages delivered, exiting loop");
 
     let mut reports = BTreeMap::new();
     for (verifier, state) in states {
@@ -302,4 +404,80 @@ where
             Err(report_strings.join("\n\n"))
         }
     }
+
+    /// Returns the nodes blamed by the node `id`
+    /// (that is, for which it registered provable or unprovable errors, or any other offenses).
+    pub fn blamed_by(&self, id: &SP::Verifier) -> BTreeSet<SP::Verifier> {
+        self.reports
+            .get(id)
+            .map(|report| {
+                report
+                    .provable_errors
+                    .keys()
+                    .chain(report.unprovable_errors.keys())
+                    .chain(report.offenses.keys())
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Checks that all the nodes except for `malicious` finished with a result, and the results are the same.
+    ///
+    /// Returns the agreed upon result, or a string with a description of the problem.
+    pub fn check_agreement(&self, malicious: &BTreeSet<SP::Verifier>) -> Result<&P::Result, String>
+    where
+        P::Result: PartialEq,
+    {
+        let mut agreed: Option<(&SP::Verifier, &P::Result)> = None;
+        for (id, report) in self.reports.iter().filter(|(id, _report)| !malicious.contains(id)) {
+            let result = match &report.outcome {
+                SessionOutcome::Result(result) => result,
+                _ => return Err(format!("{id:?} did not finish with a result:\n{}", report.brief())),
+            };
+            match agreed {
+                None => agreed = Some((id, result)),
+                Some((agreed_id, agreed_result)) if agreed_result != result => {
+                    return Err(format!(
+                        "Results differ: {agreed_id:?} got {agreed_result:?}, {id:?} got {result:?}"
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+        agreed
+            .map(|(_id, result)| result)
+            .ok_or_else(|| "No honest nodes in the execution".into())
+    }
+
+    /// Checks that the nodes except for `malicious` only blamed the nodes from `malicious`.
+    ///
+    /// Returns a string with a description of the problem if the check fails.
+    pub fn check_no_false_blame(&self, malicious: &BTreeSet<SP::Verifier>) -> Result<(), String> {
+        for id in self.reports.keys().filter(|id| !malicious.contains(id)) {
+            let falsely_blamed = self
+                .blamed_by(id)
+                .difference(malicious)
+                .cloned()
+                .collect::<BTreeSet<_>>();
+            if !falsely_blamed.is_empty() {
+                return Err(format!("{id:?} blamed honest nodes {falsely_blamed:?}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every node except for `malicious` blamed at least one of the nodes from `malicious`,
+    /// and did not blame anyone else.
+    ///
+    /// Returns a string with a description of the problem if the check fails.
+    pub fn check_attribution(&self, malicious: &BTreeSet<SP::Verifier>) -> Result<(), String> {
+        self.check_no_false_blame(malicious)?;
+        for id in self.reports.keys().filter(|id| !malicious.contains(id)) {
+            if self.blamed_by(id).is_empty() {
+                return Err(format!("{id:?} did not blame any of {malicious:?}"));
+            }
+        }
+        Ok(())
+    }
 }
@@ -2,7 +2,7 @@ use digest::generic_array::typenum;
 use rand_core::CryptoRngCore;
 use serde::{Deserialize, Serialize};
 
-use crate::session::{SessionParameters, WireFormat};
+use crate::session::{AsyncSessionParameters, SessionParameters, WireFormat};
 
 /// A simple signer for testing purposes.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -98,7 +98,7 @@ impl digest::OutputSizeUser for TestHasher {
     type OutputSize = typenum::U32;
 }
 
-/// An implementation of [`SessionParameters`] using the testing signer/verifier types.
+/// An implementation of [`SessionParameters`] and [`AsyncSessionParameters`] using the testing signer/verifier types.
 #[derive(Debug, Clone, Copy)]
 pub struct TestSessionParams<S>(core::marker::PhantomData<S>);
 
@@ -110,6 +110,8 @@ impl<F: WireFormat> SessionParameters for TestSessionParams<F> {
     type WireFormat = F;
 }
 
+impl<F: WireFormat> AsyncSessionParameters for TestSessionParams<F> {}
+
 #[cfg(test)]
 mod tests {
     use impls::impls;
@@ -3,7 +3,10 @@ use alloc::{boxed::Box, string::ToString};
 use serde::Serialize;
 use serde_persistent_deserializer::{AsTransientDeserializer, PersistentDeserializer};
 
-use crate::{protocol::LocalError, session::WireFormat};
+use crate::{
+    protocol::{LocalError, LocalErrorKind},
+    session::WireFormat,
+};
 
 /// A binary format to use in tests.
 #[derive(Debug, Clone, Copy)]
@@ -25,7 +28,7 @@ impl WireFormat for BinaryFormat {
     fn serialize<T: Serialize>(value: T) -> Result<Box<[u8]>, LocalError> {
         postcard::to_allocvec(&value)
             .map(|vec| vec.into())
-            .map_err(|err| LocalError::new(err.to_string()))
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Serialization, err.to_string()))
     }
 
     type Deserializer<'de> = PersistentDeserializer<PostcardDeserializer<'de>>;
@@ -57,7 +60,7 @@ impl WireFormat for HumanReadableFormat {
     fn serialize<T: Serialize>(value: T) -> Result<Box<[u8]>, LocalError> {
         serde_json::to_vec(&value)
             .map(|vec| vec.into())
-            .map_err(|err| LocalError::new(err.to_string()))
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Serialization, err.to_string()))
     }
 
     type Deserializer<'de> = PersistentDeserializer<JSONDeserializer<'de>>;
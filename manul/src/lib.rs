@@ -1,4 +1,4 @@
-#![cfg_attr(not(feature = "tokio"), no_std)]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
 #![doc = include_str!("../GUIDE.md")]
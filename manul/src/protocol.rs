@@ -5,12 +5,15 @@ A protocol is a directed acyclic graph with the nodes being objects of types imp
 (to be specific, "acyclic" means that the values returned in the `id` field of [`TransitionInfo`]
 should not repeat during the protocol execution; the types might).
 The starting point is a type that implements [`EntryPoint`].
+Rounds that need to perform I/O while processing messages or finalizing can implement [`AsyncRound`] instead
+(and their entry points, [`AsyncEntryPoint`]).
 All the rounds must have their associated type [`Round::Protocol`] set to the same [`Protocol`] instance
 to be executed by a [`Session`](`crate::session::Session`).
 
 For more details, see the documentation of the mentioned traits.
 */
 
+mod async_round;
 mod boxed_format;
 mod boxed_round;
 mod errors;
@@ -18,18 +21,20 @@ mod message;
 mod round;
 mod round_id;
 
+pub use async_round::{AsyncEntryPoint, AsyncRound, BoxFuture, SyncEntryPoint};
 pub use boxed_format::BoxedFormat;
 pub use boxed_round::BoxedRound;
 pub use errors::{
-    DeserializationError, DirectMessageError, EchoBroadcastError, LocalError, MessageValidationError,
-    NormalBroadcastError, ProtocolValidationError, ReceiveError, RemoteError,
+    DeserializationError, DirectMessageError, EchoBroadcastError, LocalError, LocalErrorKind, MessageValidationError,
+    NormalBroadcastError, ProtocolValidationError, ReceiveError, RemoteError, RemoteErrorKind,
 };
 pub use message::{DirectMessage, EchoBroadcast, NormalBroadcast, ProtocolMessage, ProtocolMessagePart};
 pub use round::{
     Artifact, CommunicationInfo, EchoRoundParticipation, EntryPoint, FinalizeOutcome, NoProtocolErrors, PartyId,
-    Payload, Protocol, ProtocolError, RequiredMessageParts, RequiredMessages, Round,
+    Payload, Protocol, ProtocolError, RequiredMessageParts, RequiredMessages, Round, VerifiableProtocol,
 };
 pub use round_id::{RoundId, TransitionInfo};
 
 pub(crate) use errors::ReceiveErrorType;
 pub(crate) use message::ProtocolMessagePartHashable;
+pub(crate) use round_id::RoundKind;
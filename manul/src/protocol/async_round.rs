@@ -0,0 +1,162 @@
+use alloc::{boxed::Box, collections::BTreeMap};
+use core::{fmt::Debug, future::Future, pin::Pin};
+
+use rand_core::CryptoRngCore;
+
+use super::{
+    boxed_format::BoxedFormat,
+    boxed_round::BoxedRound,
+    errors::{LocalError, ReceiveError},
+    message::{DirectMessage, EchoBroadcast, NormalBroadcast, ProtocolMessage, ProtocolMessagePart},
+    round::{Artifact, CommunicationInfo, DynTypeId, EntryPoint, FinalizeOutcome, PartyId, Payload, Protocol},
+    round_id::{RoundId, TransitionInfo},
+};
+
+/// A boxed future returned by the methods of [`AsyncRound`] and [`AsyncEntryPoint`].
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// An asynchronous counterpart of [`EntryPoint`], for protocols whose starting round
+/// needs to perform I/O (for example, consult a key store) while being created.
+///
+/// Synchronous entry points can be used where an asynchronous one is expected by wrapping them in [`SyncEntryPoint`].
+pub trait AsyncEntryPoint<Id: PartyId>: Send {
+    /// The protocol implemented by the round this entry points returns.
+    type Protocol: Protocol<Id>;
+
+    /// Returns the ID of the round returned by [`Self::make_round`].
+    fn entry_round_id() -> RoundId;
+
+    /// Creates the starting round.
+    ///
+    /// See [`EntryPoint::make_round`] for the meaning of the parameters.
+    fn make_round<'a>(
+        self,
+        rng: &'a mut (dyn CryptoRngCore + Send),
+        shared_randomness: &'a [u8],
+        id: &'a Id,
+    ) -> BoxFuture<'a, Result<BoxedRound<Id, Self::Protocol>, LocalError>>
+    where
+        Self: 'a;
+}
+
+/// An adapter allowing a synchronous [`EntryPoint`] to be used where an [`AsyncEntryPoint`] is expected.
+///
+/// Note that no adapter is needed for the rounds: rounds implementing [`Round`](`crate::protocol::Round`)
+/// are executed by asynchronous sessions as they are.
+#[derive(Debug, Clone)]
+pub struct SyncEntryPoint<EP>(EP);
+
+impl<EP> SyncEntryPoint<EP> {
+    /// Wraps a synchronous entry point.
+    pub fn new(entry_point: EP) -> Self {
+        Self(entry_point)
+    }
+}
+
+impl<Id, EP> AsyncEntryPoint<Id> for SyncEntryPoint<EP>
+where
+    Id: PartyId,
+    EP: EntryPoint<Id> + Send,
+{
+    type Protocol = EP::Protocol;
+
+    fn entry_round_id() -> RoundId {
+        EP::entry_round_id()
+    }
+
+    fn make_round<'a>(
+        self,
+        rng: &'a mut (dyn CryptoRngCore + Send),
+        shared_randomness: &'a [u8],
+        id: &'a Id,
+    ) -> BoxFuture<'a, Result<BoxedRound<Id, Self::Protocol>, LocalError>>
+    where
+        Self: 'a,
+    {
+        Box::pin(async move { self.0.make_round(rng, shared_randomness, id) })
+    }
+}
+
+/**
+An asynchronous counterpart of [`Round`](`crate::protocol::Round`),
+for rounds that need to perform I/O (for example, consult a key store, an HSM, or a remote prover)
+while processing messages or finalizing.
+
+The messages are still created synchronously; the methods doing that, as well as the ones returning
+the round information, have the same meaning as their counterparts in [`Round`](`crate::protocol::Round`).
+
+Asynchronous rounds are wrapped with [`BoxedRound::new_async`] and can only be executed
+by the asynchronous methods of [`Session`](`crate::session::Session`)
+(as used by the runners in [`session::tokio`](`crate::session::tokio`)), which support synchronous rounds as well.
+They are not supported by the combinators in [`combinators`](`crate::combinators`).
+*/
+pub trait AsyncRound<Id: PartyId>: 'static + Debug + Send + Sync + DynTypeId {
+    /// The protocol this round is a part of.
+    type Protocol: Protocol<Id>;
+
+    /// Returns the information about the position of this round in the state transition graph.
+    ///
+    /// See [`Round::transition_info`](`crate::protocol::Round::transition_info`).
+    fn transition_info(&self) -> TransitionInfo;
+
+    /// Returns the information about the communication this rounds engages in with other nodes.
+    ///
+    /// See [`Round::communication_info`](`crate::protocol::Round::communication_info`).
+    fn communication_info(&self) -> CommunicationInfo<Id>;
+
+    /// Returns the direct message to the given destination and (maybe) an accompanying artifact.
+    ///
+    /// See [`Round::make_direct_message`](`crate::protocol::Round::make_direct_message`).
+    fn make_direct_message(
+        &self,
+        #[allow(unused_variables)] rng: &mut dyn CryptoRngCore,
+        #[allow(unused_variables)] format: &BoxedFormat,
+        #[allow(unused_variables)] destination: &Id,
+    ) -> Result<(DirectMessage, Option<Artifact>), LocalError> {
+        Ok((DirectMessage::none(), None))
+    }
+
+    /// Returns the echo broadcast for this round.
+    ///
+    /// See [`Round::make_echo_broadcast`](`crate::protocol::Round::make_echo_broadcast`).
+    fn make_echo_broadcast(
+        &self,
+        #[allow(unused_variables)] rng: &mut dyn CryptoRngCore,
+        #[allow(unused_variables)] format: &BoxedFormat,
+    ) -> Result<EchoBroadcast, LocalError> {
+        Ok(EchoBroadcast::none())
+    }
+
+    /// Returns the normal broadcast for this round.
+    ///
+    /// See [`Round::make_normal_broadcast`](`crate::protocol::Round::make_normal_broadcast`).
+    fn make_normal_broadcast(
+        &self,
+        #[allow(unused_variables)] rng: &mut dyn CryptoRngCore,
+        #[allow(unused_variables)] format: &BoxedFormat,
+    ) -> Result<NormalBroadcast, LocalError> {
+        Ok(NormalBroadcast::none())
+    }
+
+    /// Processes a received message and generates the payload that will be used in [`finalize`](`Self::finalize`).
+    ///
+    /// See [`Round::receive_message`](`crate::protocol::Round::receive_message`).
+    fn receive_message<'a>(
+        &'a self,
+        format: &'a BoxedFormat,
+        from: &'a Id,
+        message: ProtocolMessage,
+    ) -> BoxFuture<'a, Result<Payload, ReceiveError<Id, Self::Protocol>>>;
+
+    /// Attempts to finalize the round, producing the next round or the result.
+    ///
+    /// See [`Round::finalize`](`crate::protocol::Round::finalize`).
+    ///
+    /// If the round is followed by an echo round, it is finalized after the echo round succeeds.
+    fn finalize<'a>(
+        self: Box<Self>,
+        rng: &'a mut (dyn CryptoRngCore + Send),
+        payloads: BTreeMap<Id, Payload>,
+        artifacts: BTreeMap<Id, Artifact>,
+    ) -> BoxFuture<'a, Result<FinalizeOutcome<Id, Self::Protocol>, LocalError>>;
+}
@@ -1,42 +1,73 @@
-use alloc::{boxed::Box, format};
+use alloc::{boxed::Box, collections::BTreeMap, format};
+
+use rand_core::CryptoRngCore;
 
 use super::{
-    errors::LocalError,
-    round::{PartyId, Protocol, Round},
-    round_id::RoundId,
+    async_round::AsyncRound,
+    boxed_format::BoxedFormat,
+    errors::{LocalError, ReceiveError},
+    message::{DirectMessage, EchoBroadcast, NormalBroadcast, ProtocolMessage},
+    round::{Artifact, CommunicationInfo, FinalizeOutcome, PartyId, Payload, Protocol, Round},
+    round_id::{RoundId, TransitionInfo},
 };
 
+#[derive_where::derive_where(Debug)]
+enum DynRound<Id: PartyId, P: Protocol<Id>> {
+    Sync(Box<dyn Round<Id, Protocol = P>>),
+    Async(Box<dyn AsyncRound<Id, Protocol = P>>),
+    // An asynchronous round that was finalized synchronously (e.g. by an echo round wrapping it),
+    // and is yet to be actually finalized by the session.
+    Deferred {
+        round: Box<dyn AsyncRound<Id, Protocol = P>>,
+        payloads: BTreeMap<Id, Payload>,
+        artifacts: BTreeMap<Id, Artifact>,
+    },
+}
+
 /// A wrapped new round that may be returned by [`Round::finalize`]
 /// or [`EntryPoint::make_round`](`crate::protocol::EntryPoint::make_round`).
 #[derive_where::derive_where(Debug)]
-pub struct BoxedRound<Id: PartyId, P: Protocol<Id>>(Box<dyn Round<Id, Protocol = P>>);
+pub struct BoxedRound<Id: PartyId, P: Protocol<Id>>(DynRound<Id, P>);
 
 impl<Id: PartyId, P: Protocol<Id>> BoxedRound<Id, P> {
     /// Wraps an object implementing the dynamic round trait ([`Round`](`crate::protocol::Round`)).
     pub fn new_dynamic<R: Round<Id, Protocol = P>>(round: R) -> Self {
-        Self(Box::new(round))
+        Self(DynRound::Sync(Box::new(round)))
+    }
+
+    /// Wraps an object implementing the asynchronous round trait ([`AsyncRound`](`crate::protocol::AsyncRound`)).
+    pub fn new_async<R: AsyncRound<Id, Protocol = P>>(round: R) -> Self {
+        Self(DynRound::Async(Box::new(round)))
     }
 
-    pub(crate) fn as_ref(&self) -> &dyn Round<Id, Protocol = P> {
-        self.0.as_ref()
+    /// Returns `true` if the wrapped round is asynchronous.
+    pub fn is_async(&self) -> bool {
+        !matches!(self.0, DynRound::Sync(_))
     }
 
-    pub(crate) fn into_boxed(self) -> Box<dyn Round<Id, Protocol = P>> {
-        self.0
+    /// Returns `true` if this is an asynchronous round that was finalized synchronously,
+    /// and has to be finalized with [`finalize_deferred`](`Self::finalize_deferred`).
+    pub(crate) fn is_deferred(&self) -> bool {
+        matches!(self.0, DynRound::Deferred { .. })
     }
 
     fn boxed_type_is<T: 'static>(&self) -> bool {
-        core::any::TypeId::of::<T>() == self.0.get_type_id()
+        let type_id = match &self.0 {
+            DynRound::Sync(round) => round.get_type_id(),
+            DynRound::Async(round) | DynRound::Deferred { round, .. } => round.get_type_id(),
+        };
+        core::any::TypeId::of::<T>() == type_id
     }
 
     /// Attempts to extract an object of a concrete type, preserving the original on failure.
     pub fn try_downcast<T: Round<Id>>(self) -> Result<T, Self> {
-        if self.boxed_type_is::<T>() {
-            // Safety: This is safe since we just checked that we are casting to the correct type.
-            let boxed_downcast = unsafe { Box::<T>::from_raw(Box::into_raw(self.0) as *mut T) };
-            Ok(*boxed_downcast)
-        } else {
-            Err(self)
+        match self.0 {
+            DynRound::Sync(round) if core::any::TypeId::of::<T>() == round.get_type_id() => {
+                // Safety: This is safe since we just checked that we are casting to the correct type.
+                let boxed_downcast = unsafe { Box::<T>::from_raw(Box::into_raw(round) as *mut T) };
+                Ok(*boxed_downcast)
+            }
+            round => Err(Self(round)),
         }
     }
 
@@ -52,15 +83,16 @@ impl<Id: PartyId, P: Protocol<Id>> BoxedRound<Id, P> {
     ///
     /// Fails if the wrapped type is not `T`.
     pub fn downcast_ref<T: Round<Id>>(&self) -> Result<&T, LocalError> {
-        if self.boxed_type_is::<T>() {
-            let ptr: *const dyn Round<Id, Protocol = P> = self.0.as_ref();
-            // Safety: This is safe since we just checked that we are casting to the correct type.
-            Ok(unsafe { &*(ptr as *const T) })
-        } else {
-            Err(LocalError::new(format!(
+        match &self.0 {
+            DynRound::Sync(round) if self.boxed_type_is::<T>() => {
+                let ptr: *const dyn Round<Id, Protocol = P> = round.as_ref();
+                // Safety: This is safe since we just checked that we are casting to the correct type.
+                Ok(unsafe { &*(ptr as *const T) })
+            }
+            _ => Err(LocalError::new(format!(
                 "Failed to downcast into type {}",
                 core::any::type_name::<T>()
-            )))
+            ))),
         }
     }
 
@@ -69,6 +101,132 @@ impl<Id: PartyId, P: Protocol<Id>> BoxedRound<Id, P> {
         // This constructs a new `TransitionInfo` object, so calling this method inside `Session`
         // has mild performance drawbacks.
         // This is mostly exposed for the sake of users writing `Misbehave` impls for testing.
-        self.0.transition_info().id()
+        self.transition_info().id()
+    }
+
+    // The methods below dispatch the calls to the wrapped round.
+    // The synchronous `receive_message()` fails for asynchronous rounds,
+    // and the synchronous `finalize()` defers their finalization
+    // (so that the rounds wrapping them, such as the echo rounds, could be finalized synchronously).
+
+    pub(crate) fn transition_info(&self) -> TransitionInfo {
+        match &self.0 {
+            DynRound::Sync(round) => round.transition_info(),
+            DynRound::Async(round) | DynRound::Deferred { round, .. } => round.transition_info(),
+        }
+    }
+
+    pub(crate) fn communication_info(&self) -> CommunicationInfo<Id> {
+        match &self.0 {
+            DynRound::Sync(round) => round.communication_info(),
+            DynRound::Async(round) | DynRound::Deferred { round, .. } => round.communication_info(),
+        }
+    }
+
+    pub(crate) fn make_direct_message(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        format: &BoxedFormat,
+        destination: &Id,
+    ) -> Result<(DirectMessage, Option<Artifact>), LocalError> {
+        match &self.0 {
+            DynRound::Sync(round) => round.make_direct_message(rng, format, destination),
+            DynRound::Async(round) | DynRound::Deferred { round, .. } => {
+                round.make_direct_message(rng, format, destination)
+            }
+        }
+    }
+
+    pub(crate) fn make_echo_broadcast(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        format: &BoxedFormat,
+    ) -> Result<EchoBroadcast, LocalError> {
+        match &self.0 {
+            DynRound::Sync(round) => round.make_echo_broadcast(rng, format),
+            DynRound::Async(round) | DynRound::Deferred { round, .. } => round.make_echo_broadcast(rng, format),
+        }
+    }
+
+    pub(crate) fn make_normal_broadcast(
+        &self,
+        rng: &mut dyn CryptoRngCore,
+        format: &BoxedFormat,
+    ) -> Result<NormalBroadcast, LocalError> {
+        match &self.0 {
+            DynRound::Sync(round) => round.make_normal_broadcast(rng, format),
+            DynRound::Async(round) | DynRound::Deferred { round, .. } => round.make_normal_broadcast(rng, format),
+        }
+    }
+
+    pub(crate) fn receive_message(
+        &self,
+        format: &BoxedFormat,
+        from: &Id,
+        message: ProtocolMessage,
+    ) -> Result<Payload, ReceiveError<Id, P>> {
+        match &self.0 {
+            DynRound::Sync(round) => round.receive_message(format, from, message),
+            DynRound::Async(_) | DynRound::Deferred { .. } => {
+                Err(LocalError::new("Asynchronous rounds cannot receive messages synchronously").into())
+            }
+        }
+    }
+
+    pub(crate) fn finalize(
+        self,
+        rng: &mut dyn CryptoRngCore,
+        payloads: BTreeMap<Id, Payload>,
+        artifacts: BTreeMap<Id, Artifact>,
+    ) -> Result<FinalizeOutcome<Id, P>, LocalError> {
+        match self.0 {
+            DynRound::Sync(round) => round.finalize(rng, payloads, artifacts),
+            DynRound::Async(round) => Ok(FinalizeOutcome::AnotherRound(Self(DynRound::Deferred {
+                round,
+                payloads,
+                artifacts,
+            }))),
+            DynRound::Deferred { .. } => Err(LocalError::new("The round has already been finalized")),
+        }
+    }
+
+    pub(crate) async fn receive_message_async(
+        &self,
+        format: &BoxedFormat,
+        from: &Id,
+        message: ProtocolMessage,
+    ) -> Result<Payload, ReceiveError<Id, P>> {
+        match &self.0 {
+            DynRound::Sync(round) => round.receive_message(format, from, message),
+            DynRound::Async(round) => round.receive_message(format, from, message).await,
+            DynRound::Deferred { .. } => Err(LocalError::new("The round has already been finalized").into()),
+        }
+    }
+
+    pub(crate) async fn finalize_async(
+        self,
+        rng: &mut (dyn CryptoRngCore + Send),
+        payloads: BTreeMap<Id, Payload>,
+        artifacts: BTreeMap<Id, Artifact>,
+    ) -> Result<FinalizeOutcome<Id, P>, LocalError> {
+        match self.0 {
+            DynRound::Sync(round) => round.finalize(rng, payloads, artifacts),
+            DynRound::Async(round) => round.finalize(rng, payloads, artifacts).await,
+            DynRound::Deferred { .. } => Err(LocalError::new("The round has already been finalized")),
+        }
+    }
+
+    pub(crate) async fn finalize_deferred(
+        self,
+        rng: &mut (dyn CryptoRngCore + Send),
+    ) -> Result<FinalizeOutcome<Id, P>, LocalError> {
+        match self.0 {
+            DynRound::Deferred {
+                round,
+                payloads,
+                artifacts,
+            } => round.finalize(rng, payloads, artifacts).await,
+            _ => Err(LocalError::new("The round's finalization was not deferred")),
+        }
     }
 }
@@ -1,30 +1,121 @@
 use alloc::{boxed::Box, format, string::String};
 use core::fmt::Debug;
 
+use serde::{Deserialize, Serialize};
+
 use super::round::Protocol;
 use crate::session::EchoRoundError;
 
+/// The kind of a [`LocalError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LocalErrorKind {
+    /// A misuse of the API or a bug in the code.
+    Internal,
+    /// A value could not be serialized or deserialized by the wire format.
+    Serialization,
+    /// The signer failed to sign a message.
+    Signing,
+    /// The transport failed to deliver a message, or to authenticate a peer.
+    Transport,
+}
+
 /// An error indicating a local problem, most likely a misuse of the API or a bug in the code.
 #[derive(displaydoc::Display, Debug, Clone)]
-#[displaydoc("Local error: {0}")]
-pub struct LocalError(String);
+#[displaydoc("Local error: {message}")]
+pub struct LocalError {
+    kind: LocalErrorKind,
+    message: String,
+}
 
 impl LocalError {
-    /// Creates a new error from anything castable to string.
+    /// Creates a new error of the kind [`LocalErrorKind::Internal`] from anything castable to string.
     pub fn new(message: impl Into<String>) -> Self {
-        Self(message.into())
+        Self::new_with_kind(LocalErrorKind::Internal, message)
+    }
+
+    /// Creates a new error of the given kind from anything castable to string.
+    pub fn new_with_kind(kind: LocalErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> LocalErrorKind {
+        self.kind
     }
+
+    /// Returns the human-readable description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// The kind of a [`RemoteError`], identifying the check the received message failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RemoteErrorKind {
+    /// The sender had already been banned for an earlier error.
+    BannedSender,
+    /// The message exceeds one of the configured [`MessageLimits`](`crate::session::MessageLimits`).
+    LimitExceeded,
+    /// The parts of the message have different metadata.
+    MismatchedMetadata,
+    /// The message belongs to a different session.
+    WrongSession,
+    /// The sender is not expected to send messages in the round the message is for.
+    UnexpectedSender,
+    /// The message is for a round that has not been expected at this point.
+    UnexpectedRound,
+    /// The message is for a round that has already been finalized without waiting for it.
+    LateMessage,
+    /// A signature of the message could not be deserialized.
+    InvalidSignature,
+    /// A signature of the message does not match its contents or its sender.
+    SignatureMismatch,
+    /// A different message for the same round has already been received from the sender.
+    ConflictingMessages,
+    /// An echo round message does not contain the expected set of echoed messages.
+    InvalidEchoRoundMessage,
+    /// An echo round message attached to the message is missing, unexpected, or is for the wrong round.
+    InvalidAttachedEchoRoundMessage,
+    /// A protocol error that could not be proven because the required echo round message was not received.
+    ProtocolErrorWithoutEvidence,
+    /// An error reported by the protocol itself
+    /// (via [`RemoteError::new`] or [`ReceiveError::unprovable`]).
+    Protocol,
 }
 
 /// An error indicating a problem whose reason is another node sending invalid data.
-#[derive(displaydoc::Display, Debug, Clone)]
-#[displaydoc("Remote error: {0}")]
-pub struct RemoteError(String);
+#[derive(displaydoc::Display, Debug, Clone, Serialize, Deserialize)]
+#[displaydoc("Remote error: {message}")]
+pub struct RemoteError {
+    kind: RemoteErrorKind,
+    message: String,
+}
 
 impl RemoteError {
-    /// Creates a new error from anything castable to string.
+    /// Creates a new error of the kind [`RemoteErrorKind::Protocol`] from anything castable to string.
     pub fn new(message: impl Into<String>) -> Self {
-        Self(message.into())
+        Self::new_with_kind(RemoteErrorKind::Protocol, message)
+    }
+
+    /// Creates a new error of the given kind from anything castable to string.
+    pub fn new_with_kind(kind: RemoteErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> RemoteErrorKind {
+        self.kind
+    }
+
+    /// Returns the human-readable description of the error.
+    pub fn message(&self) -> &str {
+        &self.message
     }
 }
 
@@ -51,6 +142,9 @@ pub(crate) enum ReceiveErrorType<Id, P: Protocol<Id>> {
     // via constructors and From impls.
     /// An echo round error occurred.
     Echo(Box<EchoRoundError<Id>>),
+    /// The message is valid, but cannot be used to finalize the round,
+    /// and its sender cannot be blamed for it.
+    Disregarded,
 }
 
 impl<Id, P: Protocol<Id>> ReceiveError<Id, P> {
@@ -64,6 +158,16 @@ impl<Id, P: Protocol<Id>> ReceiveError<Id, P> {
         Self(ReceiveErrorType::Unprovable(RemoteError::new(message.into())))
     }
 
+    /// An unprovable error of the given kind occurred.
+    pub(crate) fn unprovable_with_kind(kind: RemoteErrorKind, message: impl Into<String>) -> Self {
+        Self(ReceiveErrorType::Unprovable(RemoteError::new_with_kind(kind, message)))
+    }
+
+    /// The message cannot be used to finalize the round, but its sender cannot be blamed for it.
+    pub(crate) fn disregarded() -> Self {
+        Self(ReceiveErrorType::Disregarded)
+    }
+
     /// A provable error occurred.
     pub fn protocol(error: P::ProtocolError) -> Self {
         Self(ReceiveErrorType::Protocol(error))
@@ -92,6 +196,7 @@ impl<Id, P: Protocol<Id>> ReceiveErrorType<Id, P> {
             Self::InvalidNormalBroadcast(err) => ReceiveErrorType::InvalidNormalBroadcast(err),
             Self::Unprovable(err) => ReceiveErrorType::Unprovable(err),
             Self::Echo(err) => ReceiveErrorType::Echo(err),
+            Self::Disregarded => ReceiveErrorType::Disregarded,
             Self::Protocol(err) => ReceiveErrorType::Protocol(f(err)),
         }
     }
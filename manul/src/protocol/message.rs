@@ -149,6 +149,13 @@ pub(crate) trait ProtocolMessagePartHashable: ProtocolMessagePart + HasPartKind
         };
         digest.finalize()
     }
+
+    /// Returns the size of the serialized payload in bytes.
+    fn payload_size(&self) -> usize {
+        self.maybe_message()
+            .as_ref()
+            .map_or(0, |payload| payload.as_ref().len())
+    }
 }
 
 impl<T: ProtocolMessagePart + HasPartKind> ProtocolMessagePartHashable for T {}
@@ -9,7 +9,7 @@ use core::{
 };
 
 use rand_core::CryptoRngCore;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use super::{
     boxed_format::BoxedFormat,
@@ -37,6 +37,22 @@ pub struct CommunicationInfo<Id> {
     /// (and the corresponding [`receive_message`](`Round::receive_message`) finished successfully).
     pub expecting_messages_from: BTreeSet<Id>,
 
+    /// Returns the set of node IDs that may or may not send messages to this round
+    /// (e.g. only if they have something to contribute).
+    ///
+    /// The execution layer will not wait for the messages from these nodes,
+    /// but the ones that are received and processed successfully before the round is finalized
+    /// will have their payloads passed to [`finalize`](`Round::finalize`)
+    /// along with the ones from [`expecting_messages_from`](`Self::expecting_messages_from`).
+    /// The nodes that are present in both sets are treated as expected.
+    ///
+    /// Since different nodes may finalize the round having received different sets of optional messages,
+    /// the echo broadcasts from these nodes are not included in the echo round following this round,
+    /// and the protocol must not rely on every node receiving them.
+    /// Consequently, a node sending optional messages with an echo broadcast should not expect it
+    /// to be echoed back, that is, use [`EchoRoundParticipation::Send`].
+    pub optional_messages_from: BTreeSet<Id>,
+
     /// Returns the specific way the node participates in the echo round following this round.
     ///
     /// Returns [`EchoRoundParticipation::Default`] by default; this works fine when every node
@@ -51,6 +67,7 @@ impl<Id: PartyId> CommunicationInfo<Id> {
         Self {
             message_destinations: other_parties.clone(),
             expecting_messages_from: other_parties.clone(),
+            optional_messages_from: BTreeSet::new(),
             echo_round_participation: EchoRoundParticipation::Default,
         }
     }
@@ -232,6 +249,28 @@ pub trait ProtocolError<Id>: Display + Debug + Clone + Serialize + for<'de> Dese
     ) -> Result<(), ProtocolValidationError>;
 }
 
+/// A protocol whose evidence can be verified by a tool that selects it by name at runtime
+/// (for example, an evidence verification CLI).
+pub trait VerifiableProtocol<Id>: Protocol<Id> {
+    /// The name uniquely identifying the protocol.
+    const NAME: &'static str;
+
+    /// The version of the protocol.
+    ///
+    /// Should be changed whenever the changes in the protocol make the evidence produced by the previous version
+    /// unverifiable (or verifiable with a different result).
+    const VERSION: u32;
+
+    /// Deserializes the data needed to verify the evidence (see [`ProtocolError::AssociatedData`]).
+    ///
+    /// If the associated data is not supplied, this will be called with a deserializer of a unit value.
+    fn deserialize_associated_data<'de, D>(
+        deserializer: D,
+    ) -> Result<<Self::ProtocolError as ProtocolError<Id>>::AssociatedData, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
 #[derive(displaydoc::Display, Debug, Clone, Copy, Serialize, Deserialize)]
 /// A stub type indicating that this protocol does not generate any provable errors.
 pub struct NoProtocolErrors;
@@ -384,7 +423,7 @@ mod sealed {
     impl<T: 'static> DynTypeId for T {}
 }
 
-use sealed::DynTypeId;
+pub(crate) use sealed::DynTypeId;
 
 /**
 A type representing a single round of a protocol.
@@ -6,11 +6,32 @@ use tinyvec::TinyVec;
 
 use super::errors::LocalError;
 
+/// The kind of the round, distinguishing the protocol rounds from the auxiliary ones
+/// inserted by the session to ensure the consistency of echo broadcasts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub(crate) enum RoundKind {
+    /// A round defined by the protocol.
+    Protocol,
+    /// An echo round where every node echoes the hashes of all the broadcasts it received.
+    Echo,
+    /// An echo round where every node echoes a Merkle root of the hashes of the broadcasts it received.
+    MerkleEcho,
+    /// A step of the resolution following a [`RoundKind::MerkleEcho`] round, where the nodes whose roots
+    /// did not match descend their trees to find the disputed leaves.
+    MerkleResolution(u8),
+    /// The echo phase of a Bracha reliable broadcast, where every node echoes the hashes
+    /// of all the broadcasts it received.
+    BrachaEcho,
+    /// The ready phase of a Bracha reliable broadcast, where every node that received enough consistent echoes
+    /// announces the broadcasts it is ready to deliver.
+    BrachaReady,
+}
+
 /// A round identifier.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct RoundId {
     round_nums: TinyVec<[u8; 4]>,
-    is_echo: bool,
+    kind: RoundKind,
 }
 
 impl Display for RoundId {
@@ -22,8 +43,13 @@ impl Display for RoundId {
                 write!(f, "-")?;
             }
         }
-        if self.is_echo {
-            write!(f, " (echo)")?;
+        match self.kind {
+            RoundKind::Protocol => {}
+            RoundKind::Echo => write!(f, " (echo)")?,
+            RoundKind::MerkleEcho => write!(f, " (Merkle echo)")?,
+            RoundKind::MerkleResolution(step) => write!(f, " (Merkle echo resolution, step {step})")?,
+            RoundKind::BrachaEcho => write!(f, " (Bracha echo)")?,
+            RoundKind::BrachaReady => write!(f, " (Bracha ready)")?,
         }
         Ok(())
     }
@@ -36,7 +62,7 @@ impl RoundId {
         round_nums.push(round_num);
         Self {
             round_nums,
-            is_echo: false,
+            kind: RoundKind::Protocol,
         }
     }
 
@@ -50,7 +76,7 @@ impl RoundId {
         round_nums.push(round_num);
         Self {
             round_nums,
-            is_echo: self.is_echo,
+            kind: self.kind,
         }
     }
 
@@ -66,45 +92,82 @@ impl RoundId {
             let group = round_nums.pop().expect("vector size greater than 1");
             let round_id = Self {
                 round_nums,
-                is_echo: self.is_echo,
+                kind: self.kind,
             };
             Ok((group, round_id))
         }
     }
 
-    /// Returns `true` if this is an ID of an echo broadcast round.
+    /// Returns `true` if this is an ID of one of the auxiliary rounds
+    /// ensuring the consistency of echo broadcasts.
     pub(crate) fn is_echo(&self) -> bool {
-        self.is_echo
+        self.kind != RoundKind::Protocol
     }
 
-    /// Returns the identifier of the echo round corresponding to the given non-echo round.
-    ///
-    /// Panics if `self` is already an echo round identifier.
-    pub(crate) fn echo(&self) -> Result<Self, LocalError> {
-        // If this panic happens, there is something wrong with the internal logic
+    pub(crate) fn kind(&self) -> RoundKind {
+        self.kind
+    }
+
+    fn auxiliary(&self, kind: RoundKind) -> Result<Self, LocalError> {
+        // If this error happens, there is something wrong with the internal logic
         // of managing echo-broadcast rounds.
-        if self.is_echo {
+        if self.is_echo() {
             Err(LocalError::new("This is already an echo round ID"))
         } else {
             Ok(Self {
                 round_nums: self.round_nums.clone(),
-                is_echo: true,
+                kind,
             })
         }
     }
 
+    /// Returns the identifier of the echo round corresponding to the given non-echo round.
+    ///
+    /// Returns an error if `self` is already an echo round identifier.
+    pub(crate) fn echo(&self) -> Result<Self, LocalError> {
+        self.auxiliary(RoundKind::Echo)
+    }
+
+    /// Returns the identifier of the Merkle echo round corresponding to the given non-echo round.
+    ///
+    /// Returns an error if `self` is already an echo round identifier.
+    pub(crate) fn merkle_echo(&self) -> Result<Self, LocalError> {
+        self.auxiliary(RoundKind::MerkleEcho)
+    }
+
+    /// Returns the identifier of the given step of the Merkle echo resolution corresponding to the given non-echo round.
+    ///
+    /// Returns an error if `self` is already an echo round identifier.
+    pub(crate) fn merkle_resolution(&self, step: u8) -> Result<Self, LocalError> {
+        self.auxiliary(RoundKind::MerkleResolution(step))
+    }
+
+    /// Returns the identifier of the Bracha echo round corresponding to the given non-echo round.
+    ///
+    /// Returns an error if `self` is already an echo round identifier.
+    pub(crate) fn bracha_echo(&self) -> Result<Self, LocalError> {
+        self.auxiliary(RoundKind::BrachaEcho)
+    }
+
+    /// Returns the identifier of the Bracha ready round corresponding to the given non-echo round.
+    ///
+    /// Returns an error if `self` is already an echo round identifier.
+    pub(crate) fn bracha_ready(&self) -> Result<Self, LocalError> {
+        self.auxiliary(RoundKind::BrachaReady)
+    }
+
     /// Returns the identifier of the non-echo round corresponding to the given echo round.
     ///
-    /// Panics if `self` is already a non-echo round identifier.
+    /// Returns an error if `self` is already a non-echo round identifier.
     pub(crate) fn non_echo(&self) -> Result<Self, LocalError> {
-        // If this panic happens, there is something wrong with the internal logic
+        // If this error happens, there is something wrong with the internal logic
         // of managing echo-broadcast rounds.
-        if !self.is_echo {
+        if !self.is_echo() {
             Err(LocalError::new("This is already an non-echo round ID"))
         } else {
             Ok(Self {
                 round_nums: self.round_nums.clone(),
-                is_echo: false,
+                kind: RoundKind::Protocol,
             })
         }
     }
@@ -168,15 +231,18 @@ impl TransitionInfo {
     /// This includes: the child rounds (if some nodes already finalized this round),
     /// the parent rounds (if those nodes still are not finalized while we already are),
     /// and the sibling rounds (if some nodes went on a different path).
-    pub(crate) fn simultaneous_rounds(&self, followed_by_echo_round: bool) -> Result<BTreeSet<RoundId>, LocalError> {
-        let mut result = if followed_by_echo_round {
-            BTreeSet::from([self.id.echo()?])
-        } else {
+    ///
+    /// `echo_round_ids` are the IDs of the rounds the session inserts after this one
+    /// to ensure the consistency of echo broadcasts (if any).
+    pub(crate) fn simultaneous_rounds(&self, echo_round_ids: BTreeSet<RoundId>) -> BTreeSet<RoundId> {
+        let mut result = if echo_round_ids.is_empty() {
             self.parents.clone()
+        } else {
+            echo_round_ids
         };
         result.extend(self.siblings.iter().cloned());
         result.extend(self.children.iter().cloned());
-        Ok(result)
+        result
     }
 
     pub(crate) fn id(&self) -> RoundId {
@@ -194,6 +260,67 @@ impl TransitionInfo {
         })
     }
 
+    /// Returns the corresponding transition info for the Merkle echo round following this one.
+    pub(crate) fn merkle_echo(self) -> Result<Self, LocalError> {
+        let mut children = self.children;
+        children.insert(self.id.merkle_resolution(0)?);
+        Ok(Self {
+            id: self.id.merkle_echo()?,
+            parents: [self.id.clone()].into(),
+            siblings: [].into(),
+            children,
+            may_produce_result: self.may_produce_result,
+        })
+    }
+
+    /// Returns the corresponding transition info for the given step of the Merkle echo resolution
+    /// that may follow the Merkle echo round after this one.
+    pub(crate) fn merkle_resolution(self, step: u8) -> Result<Self, LocalError> {
+        let parent = match step.checked_sub(1) {
+            Some(previous_step) => self.id.merkle_resolution(previous_step)?,
+            None => self.id.merkle_echo()?,
+        };
+        let next_step = step
+            .checked_add(1)
+            .ok_or_else(|| LocalError::new("Too many Merkle echo resolution steps"))?;
+        let mut children = self.children;
+        children.insert(self.id.merkle_resolution(next_step)?);
+        Ok(Self {
+            id: self.id.merkle_resolution(step)?,
+            parents: [parent].into(),
+            siblings: [].into(),
+            children,
+            may_produce_result: self.may_produce_result,
+        })
+    }
+
+    /// Returns the corresponding transition info for the Bracha echo round following this one.
+    pub(crate) fn bracha_echo(self) -> Result<Self, LocalError> {
+        // Since the Bracha rounds can be finalized without receiving messages from every node,
+        // other nodes may already be in the round following the main one.
+        let mut children = self.children;
+        children.insert(self.id.bracha_ready()?);
+        Ok(Self {
+            id: self.id.bracha_echo()?,
+            parents: [self.id.clone()].into(),
+            siblings: [].into(),
+            children,
+            may_produce_result: self.may_produce_result,
+        })
+    }
+
+    /// Returns the corresponding transition info for the Bracha ready round
+    /// following the Bracha echo round after this one.
+    pub(crate) fn bracha_ready(self) -> Result<Self, LocalError> {
+        Ok(Self {
+            id: self.id.bracha_ready()?,
+            parents: [self.id.bracha_echo()?].into(),
+            siblings: [].into(),
+            children: self.children,
+            may_produce_result: self.may_produce_result,
+        })
+    }
+
     /// Creates a [`TransitionInfo`] for a non-terminating round (`round_num`) in a linear sequence
     /// of rounds starting with 1.
     ///
@@ -7,24 +7,51 @@ to, what kind of message and what to do next etc). The rest of the API from this
 types: setup and parametrization, errors and outcomes.
 */
 
+mod ban_policy;
+mod bracha;
+mod driver;
 mod echo;
 mod evidence;
+mod evidence_bundle;
+mod limits;
+mod merkle;
+mod merkle_echo;
 mod message;
+mod party_encoding;
+mod piggyback;
+mod relay;
+mod retransmission;
 #[allow(clippy::module_inception)]
 mod session;
 mod transcript;
+#[cfg(feature = "std")]
+mod transport;
 mod wire_format;
 
+#[cfg(feature = "futures")]
+pub mod futures;
+#[cfg(feature = "tcp")]
+pub mod tcp;
+#[cfg(feature = "std")]
+pub mod thread_pool;
 #[cfg(feature = "tokio")]
 pub mod tokio;
 
-pub use crate::protocol::{LocalError, RemoteError};
+pub use crate::protocol::{LocalError, LocalErrorKind, RemoteError, RemoteErrorKind};
+pub use ban_policy::{BanDecision, BanPolicy, ErrorBudgetBanPolicy, StrictBanPolicy};
+pub use driver::{CreatedMessage, DriverAction, DriverEvent, MessageCreator, MessageProcessor, SessionDriver};
 pub use evidence::{Evidence, EvidenceError};
+pub use evidence_bundle::EvidenceBundle;
+pub use limits::MessageLimits;
 pub use message::{Message, VerifiedMessage};
+pub use party_encoding::{PartyEncoding, PartyRef};
+pub use relay::{Relay, RelayClient, RelayEnvelope, RelayError};
+pub use retransmission::{Outbox, ResendRequest, ResendRequestError};
 pub use session::{
-    CanFinalize, PreprocessOutcome, RoundAccumulator, RoundOutcome, Session, SessionId, SessionParameters,
+    AsyncSessionParameters, BroadcastConsistency, CanFinalize, PreprocessOutcome, RoundAccumulator, RoundOutcome,
+    Session, SessionConfig, SessionId, SessionParameters,
 };
-pub use transcript::{SessionOutcome, SessionReport};
+pub use transcript::{Offense, OffenseKind, SessionOutcome, SessionReport};
 pub use wire_format::WireFormat;
 
 pub(crate) use echo::EchoRoundError;
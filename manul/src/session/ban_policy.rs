@@ -0,0 +1,73 @@
+use core::fmt::Debug;
+
+use crate::protocol::RemoteError;
+
+/// The way an unprovable error affects its sender, as decided by a [`BanPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BanDecision {
+    /// The error is not held against the sender.
+    Ignore,
+    /// The error is counted against the sender's budget ([`BanPolicy::error_budget`]),
+    /// and the sender is banned once the budget is exceeded.
+    Count,
+    /// The sender is banned immediately.
+    Ban,
+}
+
+/// A policy deciding which unprovable errors get their sender banned for the rest of the session.
+///
+/// A banned sender is recorded in the [`SessionReport`](`super::SessionReport`) along with the error,
+/// and any further messages from it are rejected.
+/// The errors that did not lead to a ban are only recorded in [`SessionReport::offenses`](`super::SessionReport::offenses`).
+///
+/// Provable errors always result in a ban.
+/// Since the policy only affects the local node, different nodes in a session may use different policies.
+pub trait BanPolicy<Id>: Debug + Send + Sync {
+    /// Classifies an unprovable error caused by `from`
+    /// (normally, based on its [`kind`](`RemoteError::kind`)).
+    fn classify(&self, from: &Id, error: &RemoteError) -> BanDecision;
+
+    /// Returns the number of errors classified as [`BanDecision::Count`]
+    /// each sender is allowed during the session without being banned.
+    ///
+    /// By default, it is zero, so the first counted error bans its sender.
+    fn error_budget(&self) -> usize {
+        0
+    }
+}
+
+/// A [`BanPolicy`] banning the sender of any unprovable error.
+///
+/// This is the default policy.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrictBanPolicy;
+
+impl<Id> BanPolicy<Id> for StrictBanPolicy {
+    fn classify(&self, _from: &Id, _error: &RemoteError) -> BanDecision {
+        BanDecision::Ban
+    }
+}
+
+/// A [`BanPolicy`] allowing each sender a fixed number of unprovable errors,
+/// banning it on the next one.
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorBudgetBanPolicy {
+    budget: usize,
+}
+
+impl ErrorBudgetBanPolicy {
+    /// Creates a policy allowing each sender `budget` unprovable errors during the session.
+    pub fn new(budget: usize) -> Self {
+        Self { budget }
+    }
+}
+
+impl<Id> BanPolicy<Id> for ErrorBudgetBanPolicy {
+    fn classify(&self, _from: &Id, _error: &RemoteError) -> BanDecision {
+        BanDecision::Count
+    }
+
+    fn error_budget(&self) -> usize {
+        self.budget
+    }
+}
@@ -0,0 +1,145 @@
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use core::fmt::Debug;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::{
+    echo::EchoRound,
+    merkle::{MerkleHash, MerkleTree},
+    session::SessionParameters,
+    LocalError,
+};
+use crate::protocol::{
+    Artifact, BoxedFormat, CommunicationInfo, FinalizeOutcome, NormalBroadcast, Payload, Protocol, ProtocolMessage,
+    ProtocolMessagePart, ReceiveError, Round, RoundId, RoundKind, TransitionInfo,
+};
+
+/// Returns the minimum number of messages from other nodes needed to finalize the round `round_id`
+/// (where `expecting` is the number of nodes the messages are expected from),
+/// or `None` if the messages from all of them are needed.
+pub(crate) fn min_messages(round_id: &RoundId, expecting: usize) -> Option<usize> {
+    // The total number of nodes, including this one.
+    let nodes = expecting + 1;
+    // The maximum number of faulty nodes the reliable broadcast can tolerate.
+    let faulty = (nodes - 1) / 3;
+    match round_id.kind() {
+        // Together with this node, `n - f` echoes
+        RoundKind::BrachaEcho => Some(nodes - faulty - 1),
+        // Together with this node, `2f + 1` ready messages
+        RoundKind::BrachaReady => Some(2 * faulty),
+        _ => None,
+    }
+}
+
+/// The message sent in the Bracha ready round.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ReadyMessage {
+    /// The root of the Merkle tree built over the hashes of all the echo broadcasts
+    /// the sender is ready to deliver, in the order of their sender IDs.
+    view: MerkleHash,
+}
+
+/// The ready phase of the Bracha reliable broadcast, following an [`EchoRound`].
+///
+/// Every node announces the set of echo broadcasts it is ready to deliver,
+/// and finalizes the round once enough other nodes announced the same set.
+#[derive_where::derive_where(Debug)]
+pub struct BrachaReadyRound<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    echo_round: EchoRound<P, SP>,
+    view: MerkleHash,
+}
+
+impl<P, SP> BrachaReadyRound<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    pub(super) fn new(echo_round: EchoRound<P, SP>) -> Result<Self, LocalError> {
+        // The view commits to all the echo broadcasts we received in the main round, and our own one.
+        let leaves = echo_round
+            .echo_broadcasts()
+            .iter()
+            .map(|(id, echo_broadcast)| MerkleHash::leaf::<SP>(id, &echo_broadcast.to_signed_hash::<SP>()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let view = MerkleTree::new::<SP>(leaves).root().clone();
+        Ok(Self { echo_round, view })
+    }
+}
+
+impl<P, SP> Round<SP::Verifier> for BrachaReadyRound<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    type Protocol = P;
+
+    fn transition_info(&self) -> TransitionInfo {
+        // See the comment in `EchoRound::transition_info()`
+        self.echo_round
+            .main_transition_info()
+            .bracha_ready()
+            .expect("the main round is not an echo round")
+    }
+
+    fn communication_info(&self) -> CommunicationInfo<SP::Verifier> {
+        self.echo_round.communication_info()
+    }
+
+    fn make_normal_broadcast(
+        &self,
+        _rng: &mut dyn CryptoRngCore,
+        format: &BoxedFormat,
+    ) -> Result<NormalBroadcast, LocalError> {
+        debug!("{:?}: making a Bracha ready message", self.echo_round.verifier());
+        NormalBroadcast::new(
+            format,
+            ReadyMessage {
+                view: self.view.clone(),
+            },
+        )
+    }
+
+    fn receive_message(
+        &self,
+        format: &BoxedFormat,
+        from: &SP::Verifier,
+        message: ProtocolMessage,
+    ) -> Result<Payload, ReceiveError<SP::Verifier, Self::Protocol>> {
+        debug!(
+            "{:?}: received a Bracha ready message from {:?}",
+            self.echo_round.verifier(),
+            from
+        );
+
+        message.echo_broadcast.assert_is_none()?;
+        message.direct_message.assert_is_none()?;
+
+        let message = message.normal_broadcast.deserialize::<ReadyMessage>(format)?;
+
+        // We cannot tell who is at fault here: `from` may be lying,
+        // or someone may have sent different echo broadcasts to us and `from`
+        // without it being detected in the echo phase.
+        // So the message is not held against `from`, it just does not count towards the ready quorum.
+        if message.view != self.view {
+            debug!(
+                "{:?}: {:?} is ready to deliver a different set of echo broadcasts",
+                self.echo_round.verifier(),
+                from
+            );
+            return Err(ReceiveError::disregarded());
+        }
+
+        Ok(Payload::empty())
+    }
+
+    fn finalize(
+        self: Box<Self>,
+        rng: &mut dyn CryptoRngCore,
+        _payloads: BTreeMap<SP::Verifier, Payload>,
+        _artifacts: BTreeMap<SP::Verifier, Artifact>,
+    ) -> Result<FinalizeOutcome<SP::Verifier, Self::Protocol>, LocalError> {
+        self.echo_round.finalize_main_round(rng)
+    }
+}
@@ -0,0 +1,443 @@
+//! A sans-IO state machine driving a [`Session`] through its rounds.
+
+use alloc::{sync::Arc, vec::Vec};
+
+use rand_core::CryptoRngCore;
+use tracing::{debug, trace};
+
+use super::{
+    message::{Message, VerifiedMessage},
+    retransmission::{Outbox, ResendRequest},
+    session::{
+        CanFinalize, ProcessedArtifact, ProcessedMessage, RoundAccumulator, RoundOutcome, Session, SessionParameters,
+    },
+    transcript::SessionReport,
+    LocalError,
+};
+use crate::protocol::Protocol;
+
+/// An event the [`SessionDriver`] reacts to.
+#[derive(Debug)]
+pub enum DriverEvent<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    /// A timer event, or any other occasion to let the driver make progress without new input.
+    ///
+    /// The first event handled by the driver starts the session, so a tick can be used for that.
+    Tick,
+    /// A message was received from a remote node.
+    MessageReceived {
+        /// The verifying key of the party the message originated from.
+        ///
+        /// It is assumed that the message's authentication info has been checked at this point.
+        from: SP::Verifier,
+        /// The received message.
+        message: Message<SP::Verifier>,
+    },
+    /// A message requested with [`DriverAction::CreateMessage`] was created.
+    MessageCreated(CreatedMessage<SP>),
+    /// A message passed with [`DriverAction::ProcessMessage`] was processed.
+    MessageProcessed(ProcessedMessage<P, SP>),
+    /// The session was cancelled by the user.
+    ///
+    /// The driver terminates the session as soon as the outstanding work is reported back.
+    Cancel,
+    /// The messages for the current round are taking too long to arrive.
+    ///
+    /// If retransmission is enabled, the driver requests the missing messages
+    /// with [`DriverAction::SendResendRequest`]; otherwise the event is ignored.
+    ResendTimeout,
+    /// A request to resend a message was received from a remote node.
+    ///
+    /// If retransmission is enabled and the request is valid, the driver answers it with [`DriverAction::Send`];
+    /// otherwise the request is ignored.
+    /// This event can be handled even after the session is finished.
+    ResendRequested(ResendRequest<SP>),
+}
+
+/// An action the user of a [`SessionDriver`] must execute.
+#[derive(Debug)]
+pub enum DriverAction<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    /// Create a message with [`MessageCreator::create`] (possibly in a separate task)
+    /// and report it back with [`DriverEvent::MessageCreated`].
+    CreateMessage(MessageCreator<P, SP>),
+    /// Send the message to the given node.
+    ///
+    /// Note that the caller is responsible for encrypting the message and attaching authentication info.
+    Send {
+        /// The verifying key of the party the message is intended for.
+        to: SP::Verifier,
+        /// The message to be sent.
+        message: Message<SP::Verifier>,
+    },
+    /// Process the message with [`MessageProcessor::process`] (possibly in a separate task)
+    /// and report it back with [`DriverEvent::MessageProcessed`].
+    ProcessMessage(MessageProcessor<P, SP>),
+    /// Send the request to resend a lost message to the given node,
+    /// which is expected to report it with [`DriverEvent::ResendRequested`] to its own driver.
+    SendResendRequest {
+        /// The verifying key of the party the message is requested from.
+        to: SP::Verifier,
+        /// The request to be sent.
+        request: ResendRequest<SP>,
+    },
+    /// The session is finished.
+    ///
+    /// No events can be handled after that.
+    Finished(SessionReport<P, SP>),
+}
+
+/// A pending creation of a message for one of the destinations of the current round.
+#[derive(Debug)]
+pub struct MessageCreator<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    session: Arc<Session<P, SP>>,
+    destination: SP::Verifier,
+}
+
+impl<P, SP> MessageCreator<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    /// Returns the verifying key of the party the message is intended for.
+    pub fn destination(&self) -> &SP::Verifier {
+        &self.destination
+    }
+
+    /// Creates and signs the message.
+    pub fn create(self, rng: &mut impl CryptoRngCore) -> Result<CreatedMessage<SP>, LocalError> {
+        let (message, artifact) = self.session.make_message(rng, &self.destination)?;
+        Ok(CreatedMessage {
+            destination: self.destination,
+            message,
+            artifact,
+        })
+    }
+}
+
+/// A message created by [`MessageCreator::create`].
+#[derive(Debug)]
+pub struct CreatedMessage<SP: SessionParameters> {
+    destination: SP::Verifier,
+    message: Message<SP::Verifier>,
+    artifact: ProcessedArtifact<SP>,
+}
+
+/// A pending processing of a received message.
+#[derive(Debug)]
+pub struct MessageProcessor<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    session: Arc<Session<P, SP>>,
+    message: VerifiedMessage<SP::Verifier>,
+}
+
+impl<P, SP> MessageProcessor<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    /// Processes the message.
+    pub fn process(self) -> ProcessedMessage<P, SP> {
+        self.session.process_message(self.message)
+    }
+}
+
+/// What to do with the current round once all the outstanding work is reported back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Conclusion {
+    Finalize,
+    Terminate,
+}
+
+#[derive(Debug)]
+struct RoundState<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    session: Arc<Session<P, SP>>,
+    accum: RoundAccumulator<P, SP>,
+    // Messages for this round that were received during the previous one,
+    // to be processed after the round is started.
+    cached_messages: Vec<VerifiedMessage<SP::Verifier>>,
+    started: bool,
+    pending_creations: usize,
+    pending_processing: usize,
+    conclusion: Option<Conclusion>,
+}
+
+impl<P, SP> RoundState<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    fn new(session: Session<P, SP>, cached_messages: Vec<VerifiedMessage<SP::Verifier>>) -> Self {
+        let accum = session.make_accumulator();
+        Self {
+            session: Arc::new(session),
+            accum,
+            cached_messages,
+            started: false,
+            pending_creations: 0,
+            pending_processing: 0,
+            conclusion: None,
+        }
+    }
+
+    fn start(&mut self, actions: &mut Vec<DriverAction<P, SP>>) {
+        debug!(
+            "{:?}: *** starting round {:?} ***",
+            self.session.verifier(),
+            self.session.round_id()
+        );
+        self.started = true;
+
+        for destination in self.session.message_destinations() {
+            actions.push(DriverAction::CreateMessage(MessageCreator {
+                session: self.session.clone(),
+                destination: destination.clone(),
+            }));
+            self.pending_creations += 1;
+        }
+
+        for message in core::mem::take(&mut self.cached_messages) {
+            debug!("{:?}: Applying a cached message", self.session.verifier());
+            self.process(message, actions);
+        }
+    }
+
+    fn process(&mut self, message: VerifiedMessage<SP::Verifier>, actions: &mut Vec<DriverAction<P, SP>>) {
+        actions.push(DriverAction::ProcessMessage(MessageProcessor {
+            session: self.session.clone(),
+            message,
+        }));
+        self.pending_processing += 1;
+    }
+
+    fn has_pending_work(&self) -> bool {
+        self.pending_creations > 0 || self.pending_processing > 0
+    }
+}
+
+// There is only one of these in existence at a time, so the size difference is not a concern.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+enum DriverState<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    Running(RoundState<P, SP>),
+    Finished,
+}
+
+/// A sans-IO state machine driving a [`Session`] through its rounds,
+/// which can be embedded into any runtime or event loop.
+///
+/// The user feeds [`DriverEvent`]s to [`handle`](`Self::handle`) and executes the returned [`DriverAction`]s.
+/// The messages are created and processed by the user (possibly in parallel),
+/// with every [`DriverAction::CreateMessage`] and [`DriverAction::ProcessMessage`] reported back
+/// with the corresponding event, while the round accumulator is kept in the driver.
+/// Before the round is finalized, the driver waits for all such actions to be reported back,
+/// sending out the created messages and discarding the results of processing.
+///
+/// If created with [`new_with_retransmission`](`Self::new_with_retransmission`),
+/// the driver also keeps the sent messages in an [`Outbox`] to answer the requests of the nodes that lost them,
+/// and requests the messages lost by the transport on [`DriverEvent::ResendTimeout`].
+///
+/// Only synchronous rounds are supported.
+#[derive(Debug)]
+pub struct SessionDriver<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    state: DriverState<P, SP>,
+    outbox: Option<Outbox<SP>>,
+}
+
+impl<P, SP> SessionDriver<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    /// Creates a driver for the given session.
+    ///
+    /// The session is started by the first handled event.
+    pub fn new(session: Session<P, SP>) -> Self {
+        Self {
+            state: DriverState::Running(RoundState::new(session, Vec::new())),
+            outbox: None,
+        }
+    }
+
+    /// Creates a driver for the given session, with the retransmission of lost messages enabled.
+    ///
+    /// The session is started by the first handled event.
+    pub fn new_with_retransmission(session: Session<P, SP>) -> Self {
+        let outbox = Outbox::new(session.verifier(), session.session_id().clone());
+        Self {
+            state: DriverState::Running(RoundState::new(session, Vec::new())),
+            outbox: Some(outbox),
+        }
+    }
+
+    /// Returns `true` if [`DriverAction::Finished`] has been returned.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, DriverState::Finished)
+    }
+
+    /// Handles an event, returning the actions to be executed by the user.
+    pub fn handle(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        event: DriverEvent<P, SP>,
+    ) -> Result<Vec<DriverAction<P, SP>>, LocalError> {
+        let mut actions = Vec::new();
+
+        let DriverState::Running(round) = &mut self.state else {
+            // The other nodes may still need the messages of the last round.
+            return match event {
+                DriverEvent::ResendRequested(request) => {
+                    respond(self.outbox.as_ref(), &request, &mut actions)?;
+                    Ok(actions)
+                }
+                _ => Err(LocalError::new("The session is already finished")),
+            };
+        };
+
+        if !round.started {
+            round.start(&mut actions);
+        }
+
+        match event {
+            DriverEvent::Tick => {}
+            DriverEvent::MessageReceived { from, message } => {
+                // Perform quick checks before proceeding with the verification.
+                match round.session.preprocess_message(&mut round.accum, &from, message)?.ok() {
+                    // The messages for the current round are not needed if it is already concluded.
+                    Some(preprocessed) if round.conclusion.is_none() => {
+                        debug!("{:?}: Applying a message from {:?}", round.session.verifier(), from);
+                        round.process(preprocessed, &mut actions)
+                    }
+                    _ => trace!(
+                        "{:?}: Pre-processing complete. Current state: {:?}",
+                        round.session.verifier(),
+                        round.accum
+                    ),
+                }
+            }
+            DriverEvent::MessageCreated(created) => {
+                round.pending_creations = round
+                    .pending_creations
+                    .checked_sub(1)
+                    .ok_or_else(|| LocalError::new("Got a created message that was not requested"))?;
+                round.session.add_artifact(&mut round.accum, created.artifact)?;
+                if let Some(outbox) = self.outbox.as_mut() {
+                    outbox.record(&created.destination, &created.message);
+                }
+                actions.push(DriverAction::Send {
+                    to: created.destination,
+                    message: created.message,
+                });
+            }
+            DriverEvent::MessageProcessed(processed) => {
+                round.pending_processing = round
+                    .pending_processing
+                    .checked_sub(1)
+                    .ok_or_else(|| LocalError::new("Got a processed message that was not requested"))?;
+                if round.conclusion.is_none() {
+                    round.session.add_processed_message(&mut round.accum, processed)?;
+                }
+            }
+            DriverEvent::Cancel => round.conclusion = Some(Conclusion::Terminate),
+            DriverEvent::ResendTimeout => {
+                if self.outbox.is_some() && round.conclusion.is_none() {
+                    for id in round.session.missing_messages(&round.accum) {
+                        debug!(
+                            "{:?}: Requesting {:?} to resend its message",
+                            round.session.verifier(),
+                            id
+                        );
+                        let request = round.session.make_resend_request(rng, &id)?;
+                        actions.push(DriverAction::SendResendRequest { to: id, request });
+                    }
+                }
+            }
+            DriverEvent::ResendRequested(request) => respond(self.outbox.as_ref(), &request, &mut actions)?,
+        }
+
+        self.advance(rng, &mut actions)?;
+
+        Ok(actions)
+    }
+
+    /// Finalizes the rounds for as long as it is possible.
+    fn advance(
+        &mut self,
+        rng: &mut impl CryptoRngCore,
+        actions: &mut Vec<DriverAction<P, SP>>,
+    ) -> Result<(), LocalError> {
+        loop {
+            let DriverState::Running(round) = &mut self.state else {
+                return Ok(());
+            };
+
+            if round.conclusion.is_none() {
+                round.conclusion = match round.session.can_finalize(&round.accum) {
+                    CanFinalize::Yes => Some(Conclusion::Finalize),
+                    CanFinalize::NotYet => None,
+                    // Due to already registered invalid messages from nodes,
+                    // even if the remaining nodes send correct messages, it won't be enough.
+                    // Terminating.
+                    CanFinalize::Never => Some(Conclusion::Terminate),
+                };
+            }
+
+            let Some(conclusion) = round.conclusion else {
+                return Ok(());
+            };
+            if round.has_pending_work() {
+                return Ok(());
+            }
+
+            let DriverState::Running(round) = core::mem::replace(&mut self.state, DriverState::Finished) else {
+                return Err(LocalError::new("The session state changed unexpectedly"));
+            };
+            let session = Arc::into_inner(round.session)
+                .ok_or_else(|| LocalError::new("The session is still referenced by a message creator or processor"))?;
+
+            let outcome = match conclusion {
+                Conclusion::Terminate => RoundOutcome::Finished(session.terminate_due_to_errors(round.accum)?),
+                Conclusion::Finalize => {
+                    debug!("{:?}: Finalizing the round", session.verifier());
+                    session.finalize_round(rng, round.accum)?
+                }
+            };
+
+            match outcome {
+                RoundOutcome::Finished(report) => {
+                    actions.push(DriverAction::Finished(report));
+                    return Ok(());
+                }
+                RoundOutcome::AnotherRound {
+                    session,
+                    cached_messages,
+                } => {
+                    let mut round = RoundState::new(session, cached_messages);
+                    round.start(actions);
+                    self.state = DriverState::Running(round);
+                }
+            }
+        }
+    }
+}
+
+/// Answers a resend request from the outbox, if there is one.
+fn respond<P, SP>(
+    outbox: Option<&Outbox<SP>>,
+    request: &ResendRequest<SP>,
+    actions: &mut Vec<DriverAction<P, SP>>,
+) -> Result<(), LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    let Some(outbox) = outbox else {
+        trace!("Retransmission is disabled, ignoring a resend request");
+        return Ok(());
+    };
+
+    if let Some(message) = outbox.answer(request)? {
+        actions.push(DriverAction::Send {
+            to: request.requester().clone(),
+            message,
+        });
+    }
+    Ok(())
+}
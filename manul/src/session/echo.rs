@@ -12,15 +12,19 @@ use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 use super::{
+    bracha::{BrachaReadyRound, ReadyMessage},
+    merkle::MerkleProof,
+    merkle_echo::{MerkleEchoMessage, MerkleResolutionMessage},
     message::{MessageVerificationError, SignedMessageHash, SignedMessagePart},
-    session::{EchoRoundInfo, SessionParameters},
+    party_encoding::{PartiesDigest, PartyEncoding, PartyRef},
+    session::{BroadcastConsistency, EchoRoundInfo, SessionParameters},
     LocalError,
 };
 use crate::{
     protocol::{
         Artifact, BoxedFormat, BoxedRound, CommunicationInfo, DirectMessage, EchoBroadcast, EchoRoundParticipation,
-        FinalizeOutcome, MessageValidationError, NormalBroadcast, Payload, Protocol, ProtocolMessage,
-        ProtocolMessagePart, ReceiveError, Round, TransitionInfo,
+        FinalizeOutcome, MessageValidationError, NormalBroadcast, PartyId, Payload, Protocol, ProtocolMessage,
+        ProtocolMessagePart, ReceiveError, RemoteErrorKind, Round, RoundId, RoundKind, TransitionInfo,
     },
     utils::SerializableMap,
 };
@@ -44,6 +48,20 @@ pub(crate) enum EchoRoundError<Id> {
         we_received: SignedMessagePart<EchoBroadcast>,
         echoed_to_us: SignedMessageHash,
     },
+    /// The leaf revealed at the end of the Merkle echo resolution
+    /// is not a part of the Merkle root the sender committed to in the Merkle echo round.
+    ///
+    /// This is the fault of the sender of the revealed leaf.
+    InconsistentMerkleRoot,
+    /// The hash revealed at the end of the Merkle echo resolution is invalid.
+    ///
+    /// This is the fault of the sender of the revealed leaf.
+    /// The attached proof shows that the invalid hash is a part of the Merkle root the sender committed to.
+    InvalidMerkleLeaf {
+        sender: Id,
+        leaf: SignedMessageHash,
+        proof: MerkleProof,
+    },
 }
 
 impl<Id> EchoRoundError<Id> {
@@ -53,24 +71,176 @@ impl<Id> EchoRoundError<Id> {
             Self::MismatchedBroadcasts { .. } => {
                 "The echoed message is different from the originally received one".into()
             }
+            Self::InconsistentMerkleRoot => {
+                "The revealed message hash does not correspond to the previously committed Merkle root".into()
+            }
+            Self::InvalidMerkleLeaf { .. } => "Invalid message received among the ones committed to".into(),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct EchoRoundMessage<SP: SessionParameters> {
+    /// The commitment to the party list the keys of `message_hashes` refer to
+    /// (if the party indices are used).
+    pub(super) parties_digest: Option<PartiesDigest>,
     /// Signatures of echo broadcasts from respective nodes.
-    pub(super) message_hashes: SerializableMap<SP::Verifier, SignedMessageHash>,
+    pub(super) message_hashes: SerializableMap<PartyRef<SP::Verifier>, SignedMessageHash>,
+}
+
+impl<SP> EchoRoundMessage<SP>
+where
+    SP: SessionParameters,
+{
+    /// Creates the message echoing the hashes of all the given broadcasts except our own.
+    pub(super) fn new(
+        verifier: &SP::Verifier,
+        echo_broadcasts: &BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>>,
+        party_encoding: &PartyEncoding<SP::Verifier>,
+        parties_digest: &Option<PartiesDigest>,
+    ) -> Result<Self, LocalError> {
+        // Don't send our own message the second time
+        if !echo_broadcasts.contains_key(verifier) {
+            return Err(LocalError::new(format!(
+                "Expected {:?} to be in the set of all echo messages",
+                verifier
+            )));
+        }
+
+        let message_hashes = echo_broadcasts
+            .iter()
+            .filter(|(id, _echo_broadcast)| *id != verifier)
+            .map(|(id, echo_broadcast)| (party_encoding.encode(id), echo_broadcast.to_signed_hash::<SP>()))
+            .collect::<BTreeMap<_, _>>()
+            .into();
+
+        Ok(Self {
+            parties_digest: parties_digest.clone(),
+            message_hashes,
+        })
+    }
+
+    /// Returns the message hashes keyed by the full party IDs,
+    /// or `None` if the party references cannot be decoded with the given encoding.
+    pub(super) fn decode_message_hashes<'a>(
+        &'a self,
+        party_encoding: &'a PartyEncoding<SP::Verifier>,
+        parties_digest: &Option<PartiesDigest>,
+    ) -> Option<BTreeMap<&'a SP::Verifier, &'a SignedMessageHash>> {
+        if &self.parties_digest != parties_digest {
+            return None;
+        }
+        self.message_hashes
+            .iter()
+            .map(|(party, hash)| party_encoding.decode(party).map(|id| (id, hash)))
+            .collect()
+    }
+}
+
+/// The echo broadcasts received in a round, and the means to check that other nodes received the same ones.
+#[derive_where::derive_where(Debug)]
+pub(crate) struct EchoState<SP: SessionParameters> {
+    verifier: SP::Verifier,
+    echo_broadcasts: BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>>,
+    echo_round_info: EchoRoundInfo<SP::Verifier>,
+    party_encoding: PartyEncoding<SP::Verifier>,
+    parties_digest: Option<PartiesDigest>,
+}
+
+impl<SP> EchoState<SP>
+where
+    SP: SessionParameters,
+{
+    pub fn new(
+        verifier: SP::Verifier,
+        echo_broadcasts: BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>>,
+        echo_round_info: EchoRoundInfo<SP::Verifier>,
+        party_encoding: PartyEncoding<SP::Verifier>,
+    ) -> Result<Self, LocalError> {
+        let parties_digest = party_encoding.digest::<SP>()?;
+        Ok(Self {
+            verifier,
+            echo_broadcasts,
+            echo_round_info,
+            party_encoding,
+            parties_digest,
+        })
+    }
+
+    pub fn verifier(&self) -> &SP::Verifier {
+        &self.verifier
+    }
+
+    pub fn echo_broadcasts(&self) -> &BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>> {
+        &self.echo_broadcasts
+    }
+
+    pub fn echo_round_info(&self) -> &EchoRoundInfo<SP::Verifier> {
+        &self.echo_round_info
+    }
+
+    /// Creates the normal broadcast with the hashes of the received echo broadcasts.
+    pub fn make_normal_broadcast(&self, format: &BoxedFormat) -> Result<NormalBroadcast, LocalError> {
+        let message = EchoRoundMessage::<SP>::new(
+            &self.verifier,
+            &self.echo_broadcasts,
+            &self.party_encoding,
+            &self.parties_digest,
+        )?;
+        NormalBroadcast::new(format, message)
+    }
+
+    /// Checks the normal broadcast with the hashes of the echo broadcasts `from` received.
+    pub fn receive_normal_broadcast<P: Protocol<SP::Verifier>>(
+        &self,
+        format: &BoxedFormat,
+        from: &SP::Verifier,
+        normal_broadcast: &NormalBroadcast,
+    ) -> Result<(), ReceiveError<SP::Verifier, P>> {
+        let message = normal_broadcast.deserialize::<EchoRoundMessage<SP>>(format)?;
+        let message_hashes = message
+            .decode_message_hashes(&self.party_encoding, &self.parties_digest)
+            .ok_or_else(|| {
+                ReceiveError::unprovable_with_kind(
+                    RemoteErrorKind::InvalidEchoRoundMessage,
+                    "The echoed messages refer to parties of a different session",
+                )
+            })?;
+
+        check_echoed_keys(&self.echo_round_info.expected_echos, from, &message_hashes)?;
+
+        // Check that every entry is equal to what we received previously (in the main round).
+        // If there's a difference, it's a provable fault,
+        // since we have both messages signed by `from`.
+
+        for (sender, echo) in message_hashes {
+            // We expect the key to be there since
+            // `message.echo_broadcasts.keys()` is within `self.destinations`
+            // which was constructed as `self.echo_broadcasts.keys()`.
+            let previously_received_echo = self
+                .echo_broadcasts
+                .get(sender)
+                .expect("the key is present by construction");
+
+            check_echoed_hash::<P, SP>(sender, echo, previously_received_echo, || {
+                Ok(EchoRoundError::InvalidEcho(sender.clone()))
+            })?;
+        }
+
+        Ok(())
+    }
 }
 
 /// Each protocol round can contain one `EchoRound` with "echo messages" that are sent to all
 /// participants. The execution layer of the protocol guarantees that all participants have received
 /// the messages.
+///
+/// In the [`BroadcastConsistency::Bracha`] mode this is the echo phase of the reliable broadcast,
+/// followed by a [`BrachaReadyRound`].
 #[derive_where::derive_where(Debug)]
 pub struct EchoRound<P: Protocol<SP::Verifier>, SP: SessionParameters> {
-    verifier: SP::Verifier,
-    echo_broadcasts: BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>>,
-    echo_round_info: EchoRoundInfo<SP::Verifier>,
+    consistency: BroadcastConsistency,
+    echo: EchoState<SP>,
     communication_info: CommunicationInfo<SP::Verifier>,
     main_round: BoxedRound<SP::Verifier, P>,
     payloads: BTreeMap<SP::Verifier, Payload>,
@@ -83,25 +253,29 @@ where
     SP: SessionParameters,
 {
     pub fn new(
-        verifier: SP::Verifier,
-        echo_broadcasts: BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>>,
-        echo_round_info: EchoRoundInfo<SP::Verifier>,
+        consistency: BroadcastConsistency,
+        echo: EchoState<SP>,
         main_round: BoxedRound<SP::Verifier, P>,
         payloads: BTreeMap<SP::Verifier, Payload>,
         artifacts: BTreeMap<SP::Verifier, Artifact>,
     ) -> Self {
-        debug!("{:?}: initialized echo round with {:?}", verifier, echo_round_info);
+        let echo_round_info = echo.echo_round_info();
+        debug!(
+            "{:?}: initialized echo round with {:?}",
+            echo.verifier(),
+            echo_round_info
+        );
 
         let communication_info = CommunicationInfo {
             message_destinations: echo_round_info.message_destinations.clone(),
             expecting_messages_from: echo_round_info.expecting_messages_from.clone(),
+            optional_messages_from: BTreeSet::new(),
             echo_round_participation: EchoRoundParticipation::Default,
         };
 
         Self {
-            verifier,
-            echo_broadcasts,
-            echo_round_info,
+            consistency,
+            echo,
             communication_info,
             main_round,
             payloads,
@@ -109,26 +283,141 @@ where
         }
     }
 
-    // Since the echo round doesn't have its own `Protocol`, these methods live here.
+    pub(super) fn verifier(&self) -> &SP::Verifier {
+        self.echo.verifier()
+    }
+
+    pub(super) fn echo_broadcasts(&self) -> &BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>> {
+        self.echo.echo_broadcasts()
+    }
 
-    pub fn verify_direct_message_is_invalid(message: &DirectMessage) -> Result<(), MessageValidationError> {
-        // We don't send any direct messages in the echo round
-        message.verify_is_some()
+    pub(super) fn main_transition_info(&self) -> TransitionInfo {
+        self.main_round.transition_info()
+    }
+
+    pub(super) fn finalize_main_round(
+        self,
+        rng: &mut dyn CryptoRngCore,
+    ) -> Result<FinalizeOutcome<SP::Verifier, P>, LocalError> {
+        self.main_round.finalize(rng, self.payloads, self.artifacts)
+    }
+
+    // Since the echo rounds don't have their own `Protocol`, these methods live here.
+    // They cover all the kinds of auxiliary rounds the session may insert after a protocol round.
+
+    pub fn verify_direct_message_is_invalid(
+        format: &BoxedFormat,
+        round_id: &RoundId,
+        message: &DirectMessage,
+    ) -> Result<(), MessageValidationError> {
+        match round_id.kind() {
+            // In the Merkle echo resolution rounds the steps of the search are sent directly
+            RoundKind::MerkleResolution(_) => message.verify_is_not::<MerkleResolutionMessage<SP::Verifier>>(format),
+            // We don't send any direct messages in the other echo rounds
+            _ => message.verify_is_some(),
+        }
     }
 
     pub fn verify_echo_broadcast_is_invalid(message: &EchoBroadcast) -> Result<(), MessageValidationError> {
-        // We don't send any echo broadcasts in the echo round
+        // We don't send any echo broadcasts in the echo rounds
         message.verify_is_some()
     }
 
     pub fn verify_normal_broadcast_is_invalid(
         format: &BoxedFormat,
+        round_id: &RoundId,
         message: &NormalBroadcast,
     ) -> Result<(), MessageValidationError> {
-        message.verify_is_not::<EchoRoundMessage<SP>>(format)
+        match round_id.kind() {
+            RoundKind::MerkleEcho => message.verify_is_not::<MerkleEchoMessage>(format),
+            // We don't send any normal broadcasts in the Merkle echo resolution rounds
+            RoundKind::MerkleResolution(_) => message.verify_is_some(),
+            RoundKind::BrachaReady => message.verify_is_not::<ReadyMessage>(format),
+            _ => message.verify_is_not::<EchoRoundMessage<SP>>(format),
+        }
     }
 }
 
+/// Checks that the echo pack sent by `from` contains exactly the entries from `expected_echos`
+/// (except for `from` itself).
+///
+/// A mismatch is an unprovable fault.
+pub(super) fn check_echoed_keys<Id: PartyId, P: Protocol<Id>, T>(
+    expected_echos: &BTreeSet<Id>,
+    from: &Id,
+    message_hashes: &BTreeMap<&Id, T>,
+) -> Result<(), ReceiveError<Id, P>> {
+    let mut expected_keys = expected_echos.clone();
+
+    // We don't expect the node to send its echo the second time.
+    expected_keys.remove(from);
+
+    let message_keys = message_hashes.keys().map(|id| (*id).clone()).collect::<BTreeSet<_>>();
+
+    let missing_keys = expected_keys.difference(&message_keys).collect::<Vec<_>>();
+    if !missing_keys.is_empty() {
+        return Err(ReceiveError::unprovable_with_kind(
+            RemoteErrorKind::InvalidEchoRoundMessage,
+            format!("Missing echoed messages from: {:?}", missing_keys),
+        ));
+    }
+
+    let extra_keys = message_keys.difference(&expected_keys).collect::<Vec<_>>();
+    if !extra_keys.is_empty() {
+        return Err(ReceiveError::unprovable_with_kind(
+            RemoteErrorKind::InvalidEchoRoundMessage,
+            format!("Unexpected echoed messages from: {:?}", extra_keys),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that the hash of the broadcast from `sender` echoed to us
+/// corresponds to the broadcast we received from `sender` ourselves.
+///
+/// `invalid_echo` creates the error blaming the node that echoed the hash,
+/// in case the hash itself is invalid.
+pub(super) fn check_echoed_hash<P, SP>(
+    sender: &SP::Verifier,
+    echo: &SignedMessageHash,
+    previously_received_echo: &SignedMessagePart<EchoBroadcast>,
+    invalid_echo: impl FnOnce() -> Result<EchoRoundError<SP::Verifier>, LocalError>,
+) -> Result<(), ReceiveError<SP::Verifier, P>>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    let verified_echo = match echo.clone().verify::<SP>(sender) {
+        Ok(echo) => echo,
+        Err(MessageVerificationError::Local(error)) => return Err(error.into()),
+        // This means `from` sent us an incorrectly signed message.
+        // Provable fault of `from`.
+        Err(MessageVerificationError::InvalidSignature) => return Err(invalid_echo()?.into()),
+        Err(MessageVerificationError::SignatureMismatch) => return Err(invalid_echo()?.into()),
+    };
+
+    // `from` sent us a correctly signed message but from another round or another session.
+    // Provable fault of `from`.
+    if verified_echo.metadata() != previously_received_echo.metadata() {
+        return Err(invalid_echo()?.into());
+    }
+
+    // `sender` sent us and `from` messages with different payloads,
+    // but with correct signatures and the same metadata.
+    // Provable fault of `sender`.
+    if !verified_echo.is_hash_of::<SP, _>(previously_received_echo) {
+        return Err(EchoRoundError::MismatchedBroadcasts {
+            guilty_party: sender.clone(),
+            we_received: previously_received_echo.clone(),
+            echoed_to_us: echo.clone(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
 impl<P, SP> Round<SP::Verifier> for EchoRound<P, SP>
 where
     P: Protocol<SP::Verifier>,
@@ -141,11 +430,12 @@ where
         // which cannot be an echo round.
         // Returning an error here would require allowing the trait method to fail,
         // which is not needed by any protocol implementors.
-        self.main_round
-            .as_ref()
-            .transition_info()
-            .echo()
-            .expect("the main round is not an echo round")
+        let transition_info = self.main_transition_info();
+        match self.consistency {
+            BroadcastConsistency::Bracha => transition_info.bracha_echo(),
+            _ => transition_info.echo(),
+        }
+        .expect("the main round is not an echo round")
     }
 
     fn communication_info(&self) -> CommunicationInfo<SP::Verifier> {
@@ -157,25 +447,8 @@ where
         _rng: &mut dyn CryptoRngCore,
         format: &BoxedFormat,
     ) -> Result<NormalBroadcast, LocalError> {
-        debug!("{:?}: making an echo round message", self.verifier);
-
-        // Don't send our own message the second time
-        let mut echo_broadcasts = self.echo_broadcasts.clone();
-        if echo_broadcasts.remove(&self.verifier).is_none() {
-            return Err(LocalError::new(format!(
-                "Expected {:?} to be in the set of all echo messages",
-                self.verifier
-            )));
-        }
-
-        let message_hashes = echo_broadcasts
-            .iter()
-            .map(|(id, echo_broadcast)| (id.clone(), echo_broadcast.to_signed_hash::<SP>()))
-            .collect::<BTreeMap<_, _>>()
-            .into();
-
-        let message = EchoRoundMessage::<SP> { message_hashes };
-        NormalBroadcast::new(format, message)
+        debug!("{:?}: making an echo round message", self.verifier());
+        self.echo.make_normal_broadcast(format)
     }
 
     fn receive_message(
@@ -184,83 +457,13 @@ where
         from: &SP::Verifier,
         message: ProtocolMessage,
     ) -> Result<Payload, ReceiveError<SP::Verifier, Self::Protocol>> {
-        debug!("{:?}: received an echo message from {:?}", self.verifier, from);
+        debug!("{:?}: received an echo message from {:?}", self.verifier(), from);
 
         message.echo_broadcast.assert_is_none()?;
         message.direct_message.assert_is_none()?;
 
-        let message = message.normal_broadcast.deserialize::<EchoRoundMessage<SP>>(format)?;
-
-        // Check that the received message contains entries from `expected_echos`.
-        // It is an unprovable fault.
-
-        let mut expected_keys = self.echo_round_info.expected_echos.clone();
-
-        // We don't expect the node to send its echo the second time.
-        expected_keys.remove(from);
-
-        let message_keys = message.message_hashes.keys().cloned().collect::<BTreeSet<_>>();
-
-        let missing_keys = expected_keys.difference(&message_keys).collect::<Vec<_>>();
-        if !missing_keys.is_empty() {
-            return Err(ReceiveError::unprovable(format!(
-                "Missing echoed messages from: {:?}",
-                missing_keys
-            )));
-        }
-
-        let extra_keys = message_keys.difference(&expected_keys).collect::<Vec<_>>();
-        if !extra_keys.is_empty() {
-            return Err(ReceiveError::unprovable(format!(
-                "Unexpected echoed messages from: {:?}",
-                extra_keys
-            )));
-        }
-
-        // Check that every entry is equal to what we received previously (in the main round).
-        // If there's a difference, it's a provable fault,
-        // since we have both messages signed by `from`.
-
-        for (sender, echo) in message.message_hashes.iter() {
-            // We expect the key to be there since
-            // `message.echo_broadcasts.keys()` is within `self.destinations`
-            // which was constructed as `self.echo_broadcasts.keys()`.
-            let previously_received_echo = self
-                .echo_broadcasts
-                .get(sender)
-                .expect("the key is present by construction");
-
-            let verified_echo = match echo.clone().verify::<SP>(sender) {
-                Ok(echo) => echo,
-                Err(MessageVerificationError::Local(error)) => return Err(error.into()),
-                // This means `from` sent us an incorrectly signed message.
-                // Provable fault of `from`.
-                Err(MessageVerificationError::InvalidSignature) => {
-                    return Err(EchoRoundError::InvalidEcho(sender.clone()).into())
-                }
-                Err(MessageVerificationError::SignatureMismatch) => {
-                    return Err(EchoRoundError::InvalidEcho(sender.clone()).into())
-                }
-            };
-
-            // `from` sent us a correctly signed message but from another round or another session.
-            // Provable fault of `from`.
-            if verified_echo.metadata() != previously_received_echo.metadata() {
-                return Err(EchoRoundError::InvalidEcho(sender.clone()).into());
-            }
-
-            // `sender` sent us and `from` messages with different payloads,
-            // but with correct signatures and the same metadata.
-            // Provable fault of `sender`.
-            if !verified_echo.is_hash_of::<SP, _>(previously_received_echo) {
-                return Err(EchoRoundError::MismatchedBroadcasts {
-                    guilty_party: sender.clone(),
-                    we_received: previously_received_echo.clone(),
-                    echoed_to_us: echo.clone(),
-                }
-                .into());
-            }
-        }
+        self.echo
+            .receive_normal_broadcast(format, from, &message.normal_broadcast)?;
 
         Ok(Payload::empty())
     }
@@ -271,8 +474,12 @@ where
         _payloads: BTreeMap<SP::Verifier, Payload>,
         _artifacts: BTreeMap<SP::Verifier, Artifact>,
     ) -> Result<FinalizeOutcome<SP::Verifier, Self::Protocol>, LocalError> {
-        self.main_round
-            .into_boxed()
-            .finalize(rng, self.payloads, self.artifacts)
+        match self.consistency {
+            BroadcastConsistency::Bracha => {
+                let round = BrachaReadyRound::new(*self)?;
+                Ok(FinalizeOutcome::AnotherRound(BoxedRound::new_dynamic(round)))
+            }
+            _ => self.finalize_main_round(rng),
+        }
     }
 }
@@ -2,6 +2,7 @@ use alloc::{
     collections::{BTreeMap, BTreeSet},
     format,
     string::{String, ToString},
+    vec::Vec,
 };
 use core::fmt::Debug;
 
@@ -9,16 +10,19 @@ use serde::{Deserialize, Serialize};
 
 use super::{
     echo::{EchoRound, EchoRoundError, EchoRoundMessage},
-    message::{MessageVerificationError, SignedMessageHash, SignedMessagePart},
-    session::{SessionId, SessionParameters},
+    merkle::{MerkleHash, MerkleProof, MerkleTree},
+    merkle_echo::{MerkleEchoMessage, MerkleResolutionMessage},
+    message::{MessageMetadata, MessageVerificationError, SignedMessageHash, SignedMessagePart},
+    party_encoding::{PartyEncoding, PartyRef},
+    session::{SessionConfig, SessionId, SessionParameters},
     transcript::Transcript,
     LocalError,
 };
 use crate::{
     protocol::{
         BoxedFormat, DirectMessage, DirectMessageError, EchoBroadcast, EchoBroadcastError, MessageValidationError,
-        NormalBroadcast, NormalBroadcastError, Protocol, ProtocolError, ProtocolMessage, ProtocolMessagePart,
-        ProtocolMessagePartHashable, ProtocolValidationError, RoundId,
+        NormalBroadcast, NormalBroadcastError, PartyId, Protocol, ProtocolError, ProtocolMessage, ProtocolMessagePart,
+        ProtocolMessagePartHashable, ProtocolValidationError, RoundId, RoundKind,
     },
     utils::SerializableMap,
 };
@@ -73,8 +77,7 @@ impl From<ProtocolValidationError> for EvidenceError {
 }
 
 /// A self-contained evidence of malicious behavior by a node.
-#[derive_where::derive_where(Debug)]
-#[derive(Clone, Serialize, Deserialize)]
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
 pub struct Evidence<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     guilty_party: SP::Verifier,
     description: String,
@@ -86,14 +89,18 @@ where
     P: Protocol<SP::Verifier>,
     SP: SessionParameters,
 {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new_protocol_error(
         verifier: &SP::Verifier,
         echo_broadcast: SignedMessagePart<EchoBroadcast>,
         normal_broadcast: SignedMessagePart<NormalBroadcast>,
         direct_message: SignedMessagePart<DirectMessage>,
+        echo_round_message: Option<&SignedMessagePart<NormalBroadcast>>,
         error: P::ProtocolError,
         transcript: &Transcript<P, SP>,
+        config: &SessionConfig<SP::Verifier>,
     ) -> Result<Self, LocalError> {
+        let party_encoding = config.party_encoding();
         let required_messages = error.required_messages();
 
         let echo_broadcast = if required_messages.this_round.echo_broadcast {
@@ -133,17 +140,34 @@ where
         let mut other_echo_broadcasts = BTreeMap::new();
         if let Some(required_combined_echos) = required_messages.combined_echos {
             for round_id in required_combined_echos {
-                echo_hashes.insert(
-                    round_id.clone(),
-                    transcript.get_normal_broadcast(&round_id.echo()?, verifier)?,
-                );
+                let echo_round_id = config
+                    .echo_round_id(&round_id)?
+                    .ok_or_else(|| LocalError::new(format!("No echo round follows {round_id}")))?;
+                // The echo round of the previous round may have been carried out along with this one,
+                // in which case its message is not in the transcript yet.
+                let echo_round_message = match echo_round_message {
+                    Some(message) if message.metadata().round_id() == &echo_round_id => message.clone(),
+                    _ => transcript.get_normal_broadcast(&echo_round_id, verifier)?,
+                };
+                echo_hashes.insert(round_id.clone(), echo_round_message);
                 other_echo_broadcasts.insert(
                     round_id.clone(),
-                    transcript.get_other_echo_broadcasts(&round_id, verifier)?.into(),
+                    transcript
+                        .get_other_echo_broadcasts(&round_id, verifier)?
+                        .into_iter()
+                        .map(|(id, echo_broadcast)| (party_encoding.encode(&id), echo_broadcast))
+                        .collect(),
                 );
             }
         }
 
+        // The party list is only needed to decode the combined echos
+        let party_encoding = if echo_hashes.is_empty() {
+            PartyEncoding::full()
+        } else {
+            party_encoding.clone()
+        };
+
         let description = format!("Protocol error: {error}");
 
         Ok(Self {
@@ -159,6 +183,7 @@ where
                 normal_broadcasts: normal_broadcasts.into(),
                 other_echo_broadcasts: other_echo_broadcasts.into(),
                 echo_hashes: echo_hashes.into(),
+                party_encoding,
             }),
         })
     }
@@ -166,7 +191,10 @@ where
     pub(crate) fn new_echo_round_error(
         verifier: &SP::Verifier,
         normal_broadcast: SignedMessagePart<NormalBroadcast>,
+        direct_message: SignedMessagePart<DirectMessage>,
         error: EchoRoundError<SP::Verifier>,
+        transcript: &Transcript<P, SP>,
+        party_encoding: &PartyEncoding<SP::Verifier>,
     ) -> Result<Self, LocalError> {
         let description = format!("Echo round error: {}", error.description());
         match error {
@@ -175,7 +203,8 @@ where
                 description,
                 evidence: EvidenceEnum::InvalidEchoPack(InvalidEchoPackEvidence {
                     normal_broadcast,
-                    invalid_echo_sender: from,
+                    invalid_echo_sender: party_encoding.encode(&from),
+                    party_encoding: party_encoding.clone(),
                 }),
             }),
             EchoRoundError::MismatchedBroadcasts {
@@ -190,9 +219,44 @@ where
                     echoed_to_us,
                 }),
             }),
+            EchoRoundError::InconsistentMerkleRoot => {
+                let root_message = Self::get_merkle_root_message(verifier, &direct_message, transcript)?;
+                Ok(Self {
+                    guilty_party: verifier.clone(),
+                    description,
+                    evidence: EvidenceEnum::InconsistentMerkleRoot(InconsistentMerkleRootEvidence {
+                        root_message,
+                        reveal_message: direct_message,
+                        party_encoding: party_encoding.clone(),
+                    }),
+                })
+            }
+            EchoRoundError::InvalidMerkleLeaf { sender, leaf, proof } => {
+                let root_message = Self::get_merkle_root_message(verifier, &direct_message, transcript)?;
+                Ok(Self {
+                    guilty_party: verifier.clone(),
+                    description,
+                    evidence: EvidenceEnum::InvalidMerkleLeaf(InvalidMerkleLeafEvidence {
+                        root_message,
+                        sender,
+                        leaf,
+                        proof,
+                    }),
+                })
+            }
         }
     }
 
+    /// Returns the Merkle echo round message the given resolution round message refers to.
+    fn get_merkle_root_message(
+        verifier: &SP::Verifier,
+        resolution_message: &SignedMessagePart<DirectMessage>,
+        transcript: &Transcript<P, SP>,
+    ) -> Result<SignedMessagePart<NormalBroadcast>, LocalError> {
+        let round_id = resolution_message.metadata().round_id().non_echo()?.merkle_echo()?;
+        transcript.get_normal_broadcast(&round_id, verifier)
+    }
+
     pub(crate) fn new_invalid_direct_message(
         verifier: &SP::Verifier,
         direct_message: SignedMessagePart<DirectMessage>,
@@ -217,6 +281,22 @@ where
         }
     }
 
+    pub(crate) fn new_conflicting_echo_broadcasts(
+        verifier: &SP::Verifier,
+        echo_broadcast: SignedMessagePart<EchoBroadcast>,
+        earlier_echo_broadcast: SignedMessageHash,
+    ) -> Self {
+        Self {
+            guilty_party: verifier.clone(),
+            description: "Sent conflicting echo broadcasts for the same round".into(),
+            // This is the same kind of offense as sending different echo broadcasts to different nodes.
+            evidence: EvidenceEnum::MismatchedBroadcasts(MismatchedBroadcastsEvidence {
+                we_received: echo_broadcast,
+                echoed_to_us: earlier_echo_broadcast,
+            }),
+        }
+    }
+
     pub(crate) fn new_invalid_normal_broadcast(
         verifier: &SP::Verifier,
         normal_broadcast: SignedMessagePart<NormalBroadcast>,
@@ -239,6 +319,34 @@ where
         &self.description
     }
 
+    fn metadata(&self) -> Option<&MessageMetadata> {
+        let metadata = match &self.evidence {
+            EvidenceEnum::Protocol(evidence) => evidence.metadata()?,
+            EvidenceEnum::InvalidDirectMessage(evidence) => evidence.0.metadata(),
+            EvidenceEnum::InvalidEchoBroadcast(evidence) => evidence.0.metadata(),
+            EvidenceEnum::InvalidNormalBroadcast(evidence) => evidence.0.metadata(),
+            EvidenceEnum::InvalidEchoPack(evidence) => evidence.normal_broadcast.metadata(),
+            EvidenceEnum::MismatchedBroadcasts(evidence) => evidence.we_received.metadata(),
+            EvidenceEnum::InconsistentMerkleRoot(evidence) => evidence.root_message.metadata(),
+            EvidenceEnum::InvalidMerkleLeaf(evidence) => evidence.root_message.metadata(),
+        };
+        Some(metadata)
+    }
+
+    /// Returns the ID of the round in which the offense was committed.
+    ///
+    /// Returns `None` if the evidence is malformed (in which case it would not pass verification either).
+    pub fn round_id(&self) -> Option<&RoundId> {
+        self.metadata().map(|metadata| metadata.round_id())
+    }
+
+    /// Returns the ID of the session in which the offense was committed.
+    ///
+    /// Returns `None` if the evidence is malformed (in which case it would not pass verification either).
+    pub fn session_id(&self) -> Option<&SessionId> {
+        self.metadata().map(|metadata| metadata.session_id())
+    }
+
     /// Attempts to verify that the attached data constitutes enough evidence
     /// to prove the malicious behavior of [`Self::guilty_party`].
     ///
@@ -255,12 +363,15 @@ where
             EvidenceEnum::InvalidNormalBroadcast(evidence) => evidence.verify::<P, SP>(&self.guilty_party, &format),
             EvidenceEnum::InvalidEchoPack(evidence) => evidence.verify(&self.guilty_party, &format),
             EvidenceEnum::MismatchedBroadcasts(evidence) => evidence.verify::<SP>(&self.guilty_party),
+            EvidenceEnum::InconsistentMerkleRoot(evidence) => evidence.verify(&self.guilty_party, &format),
+            EvidenceEnum::InvalidMerkleLeaf(evidence) => evidence.verify(&self.guilty_party, &format),
         }
     }
 }
 
-#[derive_where::derive_where(Debug)]
-#[derive(Clone, Serialize, Deserialize)]
+// Evidence is only created when a node misbehaves, so the size difference is not a concern.
+#[allow(clippy::large_enum_variant)]
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
 enum EvidenceEnum<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     Protocol(ProtocolEvidence<SP::Verifier, P>),
     InvalidDirectMessage(InvalidDirectMessageEvidence),
@@ -268,13 +379,15 @@ enum EvidenceEnum<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     InvalidNormalBroadcast(InvalidNormalBroadcastEvidence),
     InvalidEchoPack(InvalidEchoPackEvidence<SP>),
     MismatchedBroadcasts(MismatchedBroadcastsEvidence),
+    InconsistentMerkleRoot(InconsistentMerkleRootEvidence<SP>),
+    InvalidMerkleLeaf(InvalidMerkleLeafEvidence<SP>),
 }
 
-#[derive_where::derive_where(Debug)]
-#[derive(Clone, Serialize, Deserialize)]
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
 pub struct InvalidEchoPackEvidence<SP: SessionParameters> {
     normal_broadcast: SignedMessagePart<NormalBroadcast>,
-    invalid_echo_sender: SP::Verifier,
+    invalid_echo_sender: PartyRef<SP::Verifier>,
+    party_encoding: PartyEncoding<SP::Verifier>,
 }
 
 impl<SP> InvalidEchoPackEvidence<SP>
@@ -284,6 +397,14 @@ where
     fn verify(&self, verifier: &SP::Verifier, format: &BoxedFormat) -> Result<(), EvidenceError> {
         let verified = self.normal_broadcast.clone().verify::<SP>(verifier)?;
         let deserialized = verified.payload().deserialize::<EchoRoundMessage<SP>>(format)?;
+        if deserialized.parties_digest != self.party_encoding.digest::<SP>().map_err(EvidenceError::Local)? {
+            return Err(EvidenceError::InvalidEvidence(
+                "The attached party list is not the one used in the attached message".into(),
+            ));
+        }
+        let invalid_echo_sender = self.party_encoding.decode(&self.invalid_echo_sender).ok_or_else(|| {
+            EvidenceError::InvalidEvidence(format!("Invalid party reference {:?}", self.invalid_echo_sender))
+        })?;
         let invalid_echo = deserialized
             .message_hashes
             .get(&self.invalid_echo_sender)
@@ -294,7 +415,7 @@ where
                 ))
             })?;
 
-        let verified_echo = match invalid_echo.clone().verify::<SP>(&self.invalid_echo_sender) {
+        let verified_echo = match invalid_echo.clone().verify::<SP>(invalid_echo_sender) {
             Ok(echo) => echo,
             Err(MessageVerificationError::Local(error)) => return Err(EvidenceError::Local(error)),
             // The message was indeed incorrectly signed - fault proven
@@ -304,7 +425,7 @@ where
 
         // `from` sent us a correctly signed message but from another round or another session.
         // Provable fault of `from`.
-        if verified_echo.metadata() != self.normal_broadcast.metadata() {
+        if !is_echoed_from_round(verified_echo.metadata(), self.normal_broadcast.metadata()) {
             return Ok(());
         }
 
@@ -314,6 +435,138 @@ where
     }
 }
 
+/// Returns `true` if the echoed message metadata corresponds to the main round
+/// of the echo round message the echo was sent in.
+fn is_echoed_from_round(echoed: &MessageMetadata, echo_round_message: &MessageMetadata) -> bool {
+    echoed.session_id() == echo_round_message.session_id()
+        && echo_round_message
+            .round_id()
+            .non_echo()
+            .is_ok_and(|round_id| &round_id == echoed.round_id())
+}
+
+/// Verifies that `root_message` is a Merkle echo round message signed by `verifier`
+/// and returns the committed root.
+fn verify_merkle_root_message<SP>(
+    verifier: &SP::Verifier,
+    format: &BoxedFormat,
+    root_message: &SignedMessagePart<NormalBroadcast>,
+) -> Result<MerkleHash, EvidenceError>
+where
+    SP: SessionParameters,
+{
+    if root_message.metadata().round_id().kind() != RoundKind::MerkleEcho {
+        return Err(EvidenceError::InvalidEvidence(
+            "The attached message is not a Merkle echo round message".into(),
+        ));
+    }
+    let verified = root_message.clone().verify::<SP>(verifier)?;
+    let deserialized = verified.payload().deserialize::<MerkleEchoMessage>(format)?;
+    Ok(deserialized.root)
+}
+
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
+pub struct InconsistentMerkleRootEvidence<SP: SessionParameters> {
+    root_message: SignedMessagePart<NormalBroadcast>,
+    reveal_message: SignedMessagePart<DirectMessage>,
+    party_encoding: PartyEncoding<SP::Verifier>,
+}
+
+impl<SP> InconsistentMerkleRootEvidence<SP>
+where
+    SP: SessionParameters,
+{
+    fn verify(&self, verifier: &SP::Verifier, format: &BoxedFormat) -> Result<(), EvidenceError> {
+        let root = verify_merkle_root_message::<SP>(verifier, format, &self.root_message)?;
+
+        let root_metadata = self.root_message.metadata();
+        let metadata = self.reveal_message.metadata();
+        if metadata.session_id() != root_metadata.session_id()
+            || !matches!(metadata.round_id().kind(), RoundKind::MerkleResolution(_))
+            || metadata.round_id().non_echo().ok() != root_metadata.round_id().non_echo().ok()
+        {
+            return Err(EvidenceError::InvalidEvidence(
+                "The attached revealed leaf does not correspond to the attached Merkle echo round message".into(),
+            ));
+        }
+
+        let verified = self.reveal_message.clone().verify::<SP>(verifier)?;
+        let deserialized = verified
+            .payload()
+            .deserialize::<MerkleResolutionMessage<SP::Verifier>>(format)
+            .map_err(|error| {
+                EvidenceError::InvalidEvidence(format!(
+                    "Failed to deserialize the Merkle echo resolution message: {error}"
+                ))
+            })?;
+        let MerkleResolutionMessage::Reveal(Some(revealed)) = deserialized else {
+            return Err(EvidenceError::InvalidEvidence(
+                "The attached message does not reveal a leaf".into(),
+            ));
+        };
+
+        let parties_digest = self.party_encoding.digest::<SP>().map_err(EvidenceError::Local)?;
+        let sender = (revealed.parties_digest == parties_digest)
+            .then(|| self.party_encoding.decode(&revealed.sender))
+            .flatten()
+            .ok_or_else(|| {
+                EvidenceError::InvalidEvidence(
+                    "The attached party list is not the one used in the revealed leaf".into(),
+                )
+            })?;
+
+        let leaf = MerkleHash::leaf::<SP>(sender, &revealed.hash).map_err(EvidenceError::Local)?;
+        if revealed.proof.root::<SP>(&leaf).as_ref() != Some(&root) {
+            return Ok(());
+        }
+
+        Err(EvidenceError::InvalidEvidence(
+            "The revealed leaf is a part of the committed Merkle root".into(),
+        ))
+    }
+}
+
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
+pub struct InvalidMerkleLeafEvidence<SP: SessionParameters> {
+    root_message: SignedMessagePart<NormalBroadcast>,
+    sender: SP::Verifier,
+    leaf: SignedMessageHash,
+    proof: MerkleProof,
+}
+
+impl<SP> InvalidMerkleLeafEvidence<SP>
+where
+    SP: SessionParameters,
+{
+    fn verify(&self, verifier: &SP::Verifier, format: &BoxedFormat) -> Result<(), EvidenceError> {
+        let root = verify_merkle_root_message::<SP>(verifier, format, &self.root_message)?;
+
+        let leaf = MerkleHash::leaf::<SP>(&self.sender, &self.leaf).map_err(EvidenceError::Local)?;
+        if self.proof.root::<SP>(&leaf).as_ref() != Some(&root) {
+            return Err(EvidenceError::InvalidEvidence(
+                "The attached hash is not a part of the committed Merkle root".into(),
+            ));
+        }
+
+        let verified_leaf = match self.leaf.clone().verify::<SP>(&self.sender) {
+            Ok(leaf) => leaf,
+            Err(MessageVerificationError::Local(error)) => return Err(EvidenceError::Local(error)),
+            // The committed hash was indeed incorrectly signed - fault proven
+            Err(MessageVerificationError::InvalidSignature) => return Ok(()),
+            Err(MessageVerificationError::SignatureMismatch) => return Ok(()),
+        };
+
+        // The committed hash is correctly signed, but belongs to another round or another session.
+        if !is_echoed_from_round(verified_leaf.metadata(), self.root_message.metadata()) {
+            return Ok(());
+        }
+
+        Err(EvidenceError::InvalidEvidence(
+            "There is nothing wrong with the committed hash".into(),
+        ))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MismatchedBroadcastsEvidence {
     we_received: SignedMessagePart<EchoBroadcast>,
@@ -351,7 +604,11 @@ impl InvalidDirectMessageEvidence {
         let payload = verified_direct_message.payload();
 
         if self.0.metadata().round_id().is_echo() {
-            Ok(EchoRound::<P, SP>::verify_direct_message_is_invalid(payload)?)
+            Ok(EchoRound::<P, SP>::verify_direct_message_is_invalid(
+                format,
+                self.0.metadata().round_id(),
+                payload,
+            )?)
         } else {
             Ok(P::verify_direct_message_is_invalid(
                 format,
@@ -399,7 +656,11 @@ impl InvalidNormalBroadcastEvidence {
         let payload = verified_normal_broadcast.payload();
 
         if self.0.metadata().round_id().is_echo() {
-            Ok(EchoRound::<P, SP>::verify_normal_broadcast_is_invalid(format, payload)?)
+            Ok(EchoRound::<P, SP>::verify_normal_broadcast_is_invalid(
+                format,
+                self.0.metadata().round_id(),
+                payload,
+            )?)
         } else {
             Ok(P::verify_normal_broadcast_is_invalid(
                 format,
@@ -410,9 +671,8 @@ impl InvalidNormalBroadcastEvidence {
     }
 }
 
-#[derive_where::derive_where(Debug)]
-#[derive(Clone, Serialize, Deserialize)]
-struct ProtocolEvidence<Id: Debug + Clone + Ord, P: Protocol<Id>> {
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
+struct ProtocolEvidence<Id: PartyId, P: Protocol<Id>> {
     error: P::ProtocolError,
     direct_message: Option<SignedMessagePart<DirectMessage>>,
     echo_broadcast: Option<SignedMessagePart<EchoBroadcast>>,
@@ -420,8 +680,9 @@ struct ProtocolEvidence<Id: Debug + Clone + Ord, P: Protocol<Id>> {
     direct_messages: SerializableMap<RoundId, SignedMessagePart<DirectMessage>>,
     echo_broadcasts: SerializableMap<RoundId, SignedMessagePart<EchoBroadcast>>,
     normal_broadcasts: SerializableMap<RoundId, SignedMessagePart<NormalBroadcast>>,
-    other_echo_broadcasts: SerializableMap<RoundId, SerializableMap<Id, SignedMessagePart<EchoBroadcast>>>,
+    other_echo_broadcasts: SerializableMap<RoundId, SerializableMap<PartyRef<Id>, SignedMessagePart<EchoBroadcast>>>,
     echo_hashes: SerializableMap<RoundId, SignedMessagePart<NormalBroadcast>>,
+    party_encoding: PartyEncoding<Id>,
 }
 
 fn verify_message_parts<SP, T>(
@@ -474,9 +735,103 @@ where
 
 impl<Id, P> ProtocolEvidence<Id, P>
 where
-    Id: Debug + Clone + Ord,
+    Id: PartyId,
     P: Protocol<Id>,
 {
+    fn metadata(&self) -> Option<&MessageMetadata> {
+        // At least one part of the message that triggered the error will be present,
+        // as enforced by `RequiredMessageParts` invariant.
+        if let Some(message) = &self.direct_message {
+            Some(message.metadata())
+        } else if let Some(message) = &self.echo_broadcast {
+            Some(message.metadata())
+        } else {
+            self.normal_broadcast.as_ref().map(|message| message.metadata())
+        }
+    }
+
+    fn check_required_messages(&self) -> Result<(), EvidenceError> {
+        let required_messages = self.error.required_messages();
+
+        let this_round = required_messages.this_round;
+        if (this_round.echo_broadcast && self.echo_broadcast.is_none())
+            || (this_round.normal_broadcast && self.normal_broadcast.is_none())
+            || (this_round.direct_message && self.direct_message.is_none())
+        {
+            return Err(EvidenceError::InvalidEvidence(
+                "Missing a required part of the trigger message".into(),
+            ));
+        }
+
+        for (round_id, required) in required_messages.previous_rounds.iter().flatten() {
+            if (required.echo_broadcast && !self.echo_broadcasts.contains_key(round_id))
+                || (required.normal_broadcast && !self.normal_broadcasts.contains_key(round_id))
+                || (required.direct_message && !self.direct_messages.contains_key(round_id))
+            {
+                return Err(EvidenceError::InvalidEvidence(format!(
+                    "Missing a required part of the {round_id} message"
+                )));
+            }
+        }
+
+        for round_id in required_messages.combined_echos.iter().flatten() {
+            if !self.echo_hashes.contains_key(round_id) {
+                return Err(EvidenceError::InvalidEvidence(format!(
+                    "Missing {round_id} combined echos"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verifies that the echo broadcasts are the ones committed to in the Merkle echo round.
+    fn verify_merkle_echo_broadcasts<SP>(
+        &self,
+        session_id: &SessionId,
+        round_id: &RoundId,
+        root: &MerkleHash,
+        signed_echo_broadcasts: &SerializableMap<PartyRef<Id>, SignedMessagePart<EchoBroadcast>>,
+    ) -> Result<BTreeMap<Id, EchoBroadcast>, EvidenceError>
+    where
+        SP: SessionParameters<Verifier = Id>,
+    {
+        // The tree is built in the order of the full IDs, so decode the references first.
+        let mut decoded_echo_broadcasts = BTreeMap::new();
+        for (other_party, echo_broadcast) in signed_echo_broadcasts.iter() {
+            let other_verifier = self
+                .party_encoding
+                .decode(other_party)
+                .ok_or_else(|| EvidenceError::InvalidEvidence(format!("Invalid party reference {other_party:?}")))?;
+            decoded_echo_broadcasts.insert(other_verifier, echo_broadcast);
+        }
+
+        let mut leaves = Vec::new();
+        let mut echo_messages = BTreeMap::new();
+        for (other_verifier, echo_broadcast) in decoded_echo_broadcasts {
+            let metadata = echo_broadcast.metadata();
+            if metadata.session_id() != session_id || metadata.round_id() != round_id {
+                return Err(EvidenceError::InvalidEvidence("Invalid echo broadcast metadata".into()));
+            }
+
+            leaves.push(
+                MerkleHash::leaf::<SP>(other_verifier, &echo_broadcast.to_signed_hash::<SP>())
+                    .map_err(EvidenceError::Local)?,
+            );
+
+            let verified_echo_broadcast = echo_broadcast.clone().verify::<SP>(other_verifier)?;
+            echo_messages.insert(other_verifier.clone(), verified_echo_broadcast.into_payload());
+        }
+
+        if MerkleTree::new::<SP>(leaves).root() != root {
+            return Err(EvidenceError::InvalidEvidence(
+                "The attached echo broadcasts do not correspond to the committed Merkle root".into(),
+            ));
+        }
+
+        Ok(echo_messages)
+    }
+
     fn verify<SP>(
         &self,
         verifier: &SP::Verifier,
@@ -488,18 +843,13 @@ where
     {
         // Find the message part from the message that triggered the error
         // and use it as a source of RoundID and SessionID.
-        // At least one part of that message will be required, as enforced by `RequiredMessageParts` invariant.
-        let metadata = if let Some(message) = &self.direct_message {
-            message.metadata()
-        } else if let Some(message) = &self.echo_broadcast {
-            message.metadata()
-        } else if let Some(message) = &self.normal_broadcast {
-            message.metadata()
-        } else {
-            return Err(EvidenceError::InvalidEvidence(
-                "At least one part of the trigger message must be present".into(),
-            ));
-        };
+        let metadata = self.metadata().ok_or_else(|| {
+            EvidenceError::InvalidEvidence("At least one part of the trigger message must be present".into())
+        })?;
+
+        // The protocol can rely on the messages it requested being present,
+        // so check that the evidence contains all of them.
+        self.check_required_messages()?;
 
         let session_id = metadata.session_id();
         let round_id = metadata.round_id();
@@ -512,6 +862,7 @@ where
         let mut echo_broadcasts = verify_message_parts::<SP, _>(verifier, session_id, &self.echo_broadcasts)?;
         let mut normal_broadcasts = verify_message_parts::<SP, _>(verifier, session_id, &self.normal_broadcasts)?;
 
+        let parties_digest = self.party_encoding.digest::<SP>().map_err(EvidenceError::Local)?;
         let mut combined_echos = BTreeMap::new();
         for (round_id, echo_hashes) in self.echo_hashes.iter() {
             let metadata = echo_hashes.metadata();
@@ -526,17 +877,38 @@ where
             }
 
             let verified_echo_hashes = echo_hashes.clone().verify::<SP>(verifier)?;
-            let echo_round_payload = verified_echo_hashes
-                .payload()
-                .deserialize::<EchoRoundMessage<SP>>(format)?;
 
             let signed_echo_broadcasts = self
                 .other_echo_broadcasts
                 .get(round_id)
                 .ok_or_else(|| EvidenceError::InvalidEvidence(format!("Missing {round_id} echo broadcasts")))?;
 
+            if metadata.round_id().kind() == RoundKind::MerkleEcho {
+                let root = verified_echo_hashes
+                    .payload()
+                    .deserialize::<MerkleEchoMessage>(format)?
+                    .root;
+                let echo_messages =
+                    self.verify_merkle_echo_broadcasts::<SP>(session_id, round_id, &root, signed_echo_broadcasts)?;
+                combined_echos.insert(round_id.clone(), echo_messages);
+                continue;
+            }
+
+            let echo_round_payload = verified_echo_hashes
+                .payload()
+                .deserialize::<EchoRoundMessage<SP>>(format)?;
+            if echo_round_payload.parties_digest != parties_digest {
+                return Err(EvidenceError::InvalidEvidence(
+                    "The attached party list is not the one used in the attached echo round message".into(),
+                ));
+            }
+
             let mut echo_messages = BTreeMap::new();
-            for (other_verifier, echo_hash) in echo_round_payload.message_hashes.iter() {
+            for (other_party, echo_hash) in echo_round_payload.message_hashes.iter() {
+                let other_verifier = self.party_encoding.decode(other_party).ok_or_else(|| {
+                    EvidenceError::InvalidEvidence(format!("Invalid party reference {other_party:?}"))
+                })?;
+
                 let metadata = echo_hash.metadata();
                 if metadata.session_id() != session_id || metadata.round_id() != round_id {
                     return Err(EvidenceError::InvalidEvidence("Invalid echo hash metadata".into()));
@@ -544,7 +916,7 @@ where
 
                 let verified_echo_hash = echo_hash.clone().verify::<SP>(other_verifier)?;
 
-                let echo_broadcast = signed_echo_broadcasts.get(other_verifier).ok_or_else(|| {
+                let echo_broadcast = signed_echo_broadcasts.get(other_party).ok_or_else(|| {
                     EvidenceError::InvalidEvidence(format!("Missing {round_id} echo broadcast from {other_verifier:?}"))
                 })?;
 
@@ -605,3 +977,168 @@ where
         )?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::BTreeMap, string::String};
+
+    use impls::impls;
+    use rand_core::OsRng;
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use signature::Keypair;
+
+    use super::{is_echoed_from_round, Evidence, EvidenceEnum, EvidenceError, ProtocolEvidence};
+    use crate::{
+        dev::{BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
+        protocol::{
+            BoxedFormat, DirectMessage, EchoBroadcast, MessageValidationError, NormalBroadcast, Protocol,
+            ProtocolError, ProtocolMessage, ProtocolMessagePart, ProtocolValidationError, RequiredMessageParts,
+            RequiredMessages, RoundId,
+        },
+        session::{
+            message::{MessageMetadata, SignedMessagePart},
+            party_encoding::PartyEncoding,
+            SessionId,
+        },
+    };
+
+    type SP = TestSessionParams<BinaryFormat>;
+
+    // Not serializable by itself
+    #[derive(Debug)]
+    struct TestProtocol;
+
+    /// Invalid message in round 2
+    #[derive(displaydoc::Display, Debug, Clone, Serialize, Deserialize)]
+    struct TestProtocolError;
+
+    impl ProtocolError<TestVerifier> for TestProtocolError {
+        type AssociatedData = ();
+
+        fn required_messages(&self) -> RequiredMessages {
+            RequiredMessages::new(
+                RequiredMessageParts::direct_message(),
+                Some([(RoundId::new(1), RequiredMessageParts::echo_broadcast())].into()),
+                Some([RoundId::new(1)].into()),
+            )
+        }
+
+        fn verify_messages_constitute_error(
+            &self,
+            _format: &BoxedFormat,
+            _guilty_party: &TestVerifier,
+            _shared_randomness: &[u8],
+            _associated_data: &Self::AssociatedData,
+            _message: ProtocolMessage,
+            _previous_messages: BTreeMap<RoundId, ProtocolMessage>,
+            _combined_echos: BTreeMap<RoundId, BTreeMap<TestVerifier, EchoBroadcast>>,
+        ) -> Result<(), ProtocolValidationError> {
+            panic!("The protocol must not be given the evidence with missing required messages")
+        }
+    }
+
+    impl Protocol<TestVerifier> for TestProtocol {
+        type Result = ();
+        type ProtocolError = TestProtocolError;
+
+        fn verify_direct_message_is_invalid(
+            _format: &BoxedFormat,
+            _round_id: &RoundId,
+            _message: &DirectMessage,
+        ) -> Result<(), MessageValidationError> {
+            unimplemented!()
+        }
+
+        fn verify_echo_broadcast_is_invalid(
+            _format: &BoxedFormat,
+            _round_id: &RoundId,
+            _message: &EchoBroadcast,
+        ) -> Result<(), MessageValidationError> {
+            unimplemented!()
+        }
+
+        fn verify_normal_broadcast_is_invalid(
+            _format: &BoxedFormat,
+            _round_id: &RoundId,
+            _message: &NormalBroadcast,
+        ) -> Result<(), MessageValidationError> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn serialization_bounds() {
+        // The evidence only contains the protocol error, so the protocol type itself
+        // does not have to be serializable.
+        assert!(impls!(Evidence<TestProtocol, SP>: Serialize & DeserializeOwned));
+    }
+
+    #[test]
+    fn echoed_from_round() {
+        let session_id = SessionId::from_seed::<SP>(b"session");
+        let other_session_id = SessionId::from_seed::<SP>(b"other session");
+        let round_id = RoundId::new(1);
+        let echoed = MessageMetadata::new(&session_id, &round_id);
+
+        for echo_round_id in [round_id.echo().unwrap(), round_id.merkle_echo().unwrap()] {
+            let echo_round_message = MessageMetadata::new(&session_id, &echo_round_id);
+            assert!(is_echoed_from_round(&echoed, &echo_round_message));
+
+            // The echoed message must be from the main round, not from the echo round itself
+            let from_echo_round = MessageMetadata::new(&session_id, &echo_round_id);
+            assert!(!is_echoed_from_round(&from_echo_round, &echo_round_message));
+
+            let from_other_round = MessageMetadata::new(&session_id, &RoundId::new(2));
+            assert!(!is_echoed_from_round(&from_other_round, &echo_round_message));
+
+            let from_other_session = MessageMetadata::new(&other_session_id, &round_id);
+            assert!(!is_echoed_from_round(&from_other_session, &echo_round_message));
+        }
+    }
+
+    #[test]
+    fn missing_required_messages() {
+        let signer = TestSigner::new(0);
+        let session_id = SessionId::from_seed::<SP>(b"session");
+        let format = BoxedFormat::new::<BinaryFormat>();
+
+        let direct_message = DirectMessage::new(&format, 2u8).unwrap();
+        let direct_message =
+            SignedMessagePart::new::<SP>(&mut OsRng, &signer, &session_id, &RoundId::new(2), direct_message).unwrap();
+        let echo_broadcast = EchoBroadcast::new(&format, 1u8).unwrap();
+        let echo_broadcast =
+            SignedMessagePart::new::<SP>(&mut OsRng, &signer, &session_id, &RoundId::new(1), echo_broadcast).unwrap();
+        let echo_hashes = NormalBroadcast::new(&format, 1u8).unwrap();
+        let echo_round_id = RoundId::new(1).echo().unwrap();
+        let echo_hashes =
+            SignedMessagePart::new::<SP>(&mut OsRng, &signer, &session_id, &echo_round_id, echo_hashes).unwrap();
+
+        let make_evidence = |with_echo_broadcast: bool, with_echo_hashes: bool| Evidence::<TestProtocol, SP> {
+            guilty_party: signer.verifying_key(),
+            description: String::from("Invalid message"),
+            evidence: EvidenceEnum::Protocol(ProtocolEvidence {
+                error: TestProtocolError,
+                direct_message: Some(direct_message.clone()),
+                echo_broadcast: None,
+                normal_broadcast: None,
+                direct_messages: BTreeMap::new().into(),
+                echo_broadcasts: BTreeMap::from_iter(
+                    with_echo_broadcast.then(|| (RoundId::new(1), echo_broadcast.clone())),
+                )
+                .into(),
+                normal_broadcasts: BTreeMap::new().into(),
+                other_echo_broadcasts: BTreeMap::new().into(),
+                echo_hashes: BTreeMap::from_iter(with_echo_hashes.then(|| (RoundId::new(1), echo_hashes.clone())))
+                    .into(),
+                party_encoding: PartyEncoding::full(),
+            }),
+        };
+
+        // Instead of passing the incomplete evidence to the protocol (which would panic here),
+        // the verification rejects it.
+        for (with_echo_broadcast, with_echo_hashes) in [(false, true), (true, false), (false, false)] {
+            let evidence = make_evidence(with_echo_broadcast, with_echo_hashes);
+            assert!(matches!(evidence.verify(&()), Err(EvidenceError::InvalidEvidence(_))));
+        }
+    }
+}
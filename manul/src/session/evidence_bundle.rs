@@ -0,0 +1,401 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+};
+
+use digest::Digest;
+use rand_core::CryptoRngCore;
+use signature::{DigestVerifier, Keypair, RandomizedDigestSigner};
+
+use super::{
+    evidence::{Evidence, EvidenceError},
+    message::SerializedSignature,
+    session::{SessionId, SessionParameters},
+    transcript::SessionReport,
+    wire_format::WireFormat,
+    LocalError, LocalErrorKind,
+};
+use crate::{
+    protocol::{ProtocolError, VerifiableProtocol},
+    utils::SerializableMap,
+};
+
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
+struct BundleContents<P: VerifiableProtocol<SP::Verifier>, SP: SessionParameters> {
+    protocol_name: String,
+    protocol_version: u32,
+    session_id: SessionId,
+    parties: BTreeSet<SP::Verifier>,
+    reporter: SP::Verifier,
+    evidence: SerializableMap<SP::Verifier, Evidence<P, SP>>,
+}
+
+impl<P, SP> BundleContents<P, SP>
+where
+    P: VerifiableProtocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    fn digest(&self) -> Result<SP::Digest, LocalError> {
+        Ok(SP::Digest::new_with_prefix(b"EvidenceBundleDigest").chain_update(SP::WireFormat::serialize(self)?))
+    }
+}
+
+/// All the evidence of malicious behavior collected by a node during a session,
+/// bound to the context of that session and signed by the node.
+///
+/// In addition to verifying each [`Evidence`], [`verify`](`Self::verify`) checks that all of it
+/// was produced in the session the bundle claims, against the parties the bundle lists,
+/// and for the same protocol (and its version) the verifier expects.
+///
+/// Note that the list of parties is only asserted by the reporter: it is covered by the reporter's signature,
+/// but is not bound to the session ID. If the verifier knows the actual parties of the session,
+/// it should compare them with [`parties`](`Self::parties`).
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceBundle<P: VerifiableProtocol<SP::Verifier>, SP: SessionParameters> {
+    contents: BundleContents<P, SP>,
+    signature: SerializedSignature,
+}
+
+impl<P, SP> EvidenceBundle<P, SP>
+where
+    P: VerifiableProtocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    /// Creates a bundle of all the provable errors in the `report`
+    /// produced by the node with the given `signer` executing a session with the ID `session_id`
+    /// among `parties`.
+    pub fn from_report(
+        rng: &mut impl CryptoRngCore,
+        signer: &SP::Signer,
+        session_id: &SessionId,
+        parties: &BTreeSet<SP::Verifier>,
+        report: &SessionReport<P, SP>,
+    ) -> Result<Self, LocalError> {
+        let reporter = signer.verifying_key();
+        if !parties.contains(&reporter) {
+            return Err(LocalError::new(format!(
+                "The reporter {reporter:?} is not one of the session parties"
+            )));
+        }
+
+        for (guilty_party, evidence) in report.provable_errors.iter() {
+            if evidence.session_id() != Some(session_id) {
+                return Err(LocalError::new(format!(
+                    "The evidence against {guilty_party:?} was not produced in the session {session_id:?}"
+                )));
+            }
+        }
+
+        let contents = BundleContents {
+            protocol_name: P::NAME.into(),
+            protocol_version: P::VERSION,
+            session_id: session_id.clone(),
+            parties: parties.clone(),
+            reporter,
+            evidence: report.provable_errors.clone().into(),
+        };
+        Self::sign(rng, signer, contents)
+    }
+
+    fn sign(
+        rng: &mut impl CryptoRngCore,
+        signer: &SP::Signer,
+        contents: BundleContents<P, SP>,
+    ) -> Result<Self, LocalError> {
+        let signature = signer
+            .try_sign_digest_with_rng(rng, contents.digest()?)
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Signing, format!("Failed to sign: {:?}", err)))?;
+        Ok(Self {
+            contents,
+            signature: SerializedSignature::new::<SP>(signature)?,
+        })
+    }
+
+    /// Returns the name of the protocol the bundle was produced for.
+    pub fn protocol_name(&self) -> &str {
+        &self.contents.protocol_name
+    }
+
+    /// Returns the version of the protocol the bundle was produced for.
+    pub fn protocol_version(&self) -> u32 {
+        self.contents.protocol_version
+    }
+
+    /// Returns the ID of the session the bundle was produced in.
+    pub fn session_id(&self) -> &SessionId {
+        &self.contents.session_id
+    }
+
+    /// Returns the parties of the session the bundle was produced in, as asserted by the reporter.
+    ///
+    /// The list is not checked against the session ID by [`verify`](`Self::verify`),
+    /// so a dishonest reporter can present any set of parties including itself and the accused nodes.
+    pub fn parties(&self) -> &BTreeSet<SP::Verifier> {
+        &self.contents.parties
+    }
+
+    /// Returns the verifier of the node that produced the bundle.
+    pub fn reporter(&self) -> &SP::Verifier {
+        &self.contents.reporter
+    }
+
+    /// Returns the evidence in the bundle, keyed by the guilty party.
+    pub fn evidence(&self) -> &BTreeMap<SP::Verifier, Evidence<P, SP>> {
+        &self.contents.evidence
+    }
+
+    /// Attempts to verify the bundle and every evidence in it.
+    ///
+    /// Returns `Ok(())` if all of it is valid.
+    pub fn verify(
+        &self,
+        associated_data: &<P::ProtocolError as ProtocolError<SP::Verifier>>::AssociatedData,
+    ) -> Result<(), EvidenceError> {
+        let contents = &self.contents;
+
+        if contents.protocol_name != P::NAME || contents.protocol_version != P::VERSION {
+            return Err(EvidenceError::InvalidEvidence(format!(
+                "The bundle was produced for the protocol {} v{}, expected {} v{}",
+                contents.protocol_name,
+                contents.protocol_version,
+                P::NAME,
+                P::VERSION
+            )));
+        }
+
+        if !contents.parties.contains(&contents.reporter) {
+            return Err(EvidenceError::InvalidEvidence(
+                "The reporter is not one of the session parties".into(),
+            ));
+        }
+
+        let signature = self.signature.deserialize::<SP>()?;
+        contents
+            .reporter
+            .verify_digest(contents.digest().map_err(EvidenceError::Local)?, &signature)
+            .map_err(|_| EvidenceError::InvalidEvidence("Invalid bundle signature".into()))?;
+
+        for (guilty_party, evidence) in contents.evidence.iter() {
+            if evidence.guilty_party() != guilty_party {
+                return Err(EvidenceError::InvalidEvidence(format!(
+                    "The evidence filed against {guilty_party:?} accuses {:?}",
+                    evidence.guilty_party()
+                )));
+            }
+            if guilty_party == &contents.reporter || !contents.parties.contains(guilty_party) {
+                return Err(EvidenceError::InvalidEvidence(format!(
+                    "{guilty_party:?} is not one of the other session parties"
+                )));
+            }
+            if evidence.session_id() != Some(&contents.session_id) {
+                return Err(EvidenceError::InvalidEvidence(format!(
+                    "The evidence against {guilty_party:?} was not produced in the session of the bundle"
+                )));
+            }
+            evidence.verify(associated_data)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        collections::{BTreeMap, BTreeSet},
+        string::String,
+    };
+
+    use rand_core::OsRng;
+    use serde::{Deserialize, Deserializer};
+    use signature::Keypair;
+
+    use super::{BundleContents, EvidenceBundle};
+    use crate::{
+        dev::{BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
+        protocol::{
+            BoxedFormat, DirectMessage, DirectMessageError, EchoBroadcast, MessageValidationError, NoProtocolErrors,
+            NormalBroadcast, Protocol, ProtocolMessagePart, RoundId, VerifiableProtocol,
+        },
+        session::{
+            evidence::{Evidence, EvidenceError},
+            message::SignedMessagePart,
+            transcript::{SessionOutcome, SessionReport},
+            SessionId,
+        },
+    };
+
+    type SP = TestSessionParams<BinaryFormat>;
+
+    #[derive(Debug)]
+    struct TestProtocol;
+
+    impl Protocol<TestVerifier> for TestProtocol {
+        type Result = ();
+        type ProtocolError = NoProtocolErrors;
+
+        fn verify_direct_message_is_invalid(
+            format: &BoxedFormat,
+            _round_id: &RoundId,
+            message: &DirectMessage,
+        ) -> Result<(), MessageValidationError> {
+            message.verify_is_not::<bool>(format)
+        }
+
+        fn verify_echo_broadcast_is_invalid(
+            _format: &BoxedFormat,
+            _round_id: &RoundId,
+            _message: &EchoBroadcast,
+        ) -> Result<(), MessageValidationError> {
+            unimplemented!()
+        }
+
+        fn verify_normal_broadcast_is_invalid(
+            _format: &BoxedFormat,
+            _round_id: &RoundId,
+            _message: &NormalBroadcast,
+        ) -> Result<(), MessageValidationError> {
+            unimplemented!()
+        }
+    }
+
+    impl VerifiableProtocol<TestVerifier> for TestProtocol {
+        const NAME: &'static str = "test";
+        const VERSION: u32 = 1;
+
+        fn deserialize_associated_data<'de, D>(deserializer: D) -> Result<(), D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            <()>::deserialize(deserializer)
+        }
+    }
+
+    fn parties() -> BTreeSet<TestVerifier> {
+        (0..3).map(|id| TestSigner::new(id).verifying_key()).collect()
+    }
+
+    // An evidence of `TestSigner::new(id)` sending a direct message that cannot be deserialized.
+    fn make_evidence(id: u8, session_id: &SessionId) -> Evidence<TestProtocol, SP> {
+        let format = BoxedFormat::new::<BinaryFormat>();
+        let message = DirectMessage::new(&format, [99u8]).unwrap();
+        let message =
+            SignedMessagePart::new::<SP>(&mut OsRng, &TestSigner::new(id), session_id, &RoundId::new(1), message)
+                .unwrap();
+        Evidence::new_invalid_direct_message(
+            &TestSigner::new(id).verifying_key(),
+            message,
+            DirectMessageError::from(String::from("Invalid message")),
+        )
+    }
+
+    fn make_report(session_id: &SessionId, guilty: &[u8]) -> SessionReport<TestProtocol, SP> {
+        SessionReport {
+            outcome: SessionOutcome::NotEnoughMessages,
+            provable_errors: guilty
+                .iter()
+                .map(|id| (TestSigner::new(*id).verifying_key(), make_evidence(*id, session_id)))
+                .collect(),
+            unprovable_errors: BTreeMap::new(),
+            missing_messages: BTreeMap::new(),
+            offenses: BTreeMap::new(),
+        }
+    }
+
+    fn assert_invalid(bundle: &EvidenceBundle<TestProtocol, SP>) {
+        assert!(matches!(bundle.verify(&()), Err(EvidenceError::InvalidEvidence(_))));
+    }
+
+    #[test]
+    fn valid_bundle() {
+        let session_id = SessionId::from_seed::<SP>(b"session");
+        let report = make_report(&session_id, &[0, 2]);
+        let bundle =
+            EvidenceBundle::from_report(&mut OsRng, &TestSigner::new(1), &session_id, &parties(), &report).unwrap();
+
+        assert_eq!(bundle.evidence().len(), 2);
+        assert_eq!(bundle.reporter(), &TestVerifier::new(1));
+        assert!(bundle.verify(&()).is_ok());
+    }
+
+    #[test]
+    fn evidence_from_another_session() {
+        let session_id = SessionId::from_seed::<SP>(b"session");
+        let other_session_id = SessionId::from_seed::<SP>(b"other session");
+        let signer = TestSigner::new(1);
+
+        // The reporter cannot bundle it by mistake
+        let report = make_report(&other_session_id, &[0]);
+        assert!(EvidenceBundle::from_report(&mut OsRng, &signer, &session_id, &parties(), &report).is_err());
+
+        // And a validly signed bundle with evidence moved from another session does not verify
+        let contents = BundleContents {
+            protocol_name: TestProtocol::NAME.into(),
+            protocol_version: TestProtocol::VERSION,
+            session_id,
+            parties: parties(),
+            reporter: signer.verifying_key(),
+            evidence: report.provable_errors.into(),
+        };
+        let bundle = EvidenceBundle::sign(&mut OsRng, &signer, contents).unwrap();
+        assert_invalid(&bundle);
+    }
+
+    #[test]
+    fn invalid_signature() {
+        let session_id = SessionId::from_seed::<SP>(b"session");
+        let report = make_report(&session_id, &[0]);
+        let bundle =
+            EvidenceBundle::from_report(&mut OsRng, &TestSigner::new(1), &session_id, &parties(), &report).unwrap();
+
+        // Attribute the bundle to another party
+        let mut tampered = bundle.clone();
+        tampered.contents.reporter = TestVerifier::new(2);
+        assert_invalid(&tampered);
+
+        // Move the bundle into another session
+        let mut tampered = bundle;
+        tampered.contents.session_id = SessionId::from_seed::<SP>(b"other session");
+        assert_invalid(&tampered);
+    }
+
+    #[test]
+    fn protocol_mismatch() {
+        let session_id = SessionId::from_seed::<SP>(b"session");
+        let signer = TestSigner::new(1);
+        let report = make_report(&session_id, &[0]);
+        let contents = BundleContents {
+            protocol_name: TestProtocol::NAME.into(),
+            protocol_version: TestProtocol::VERSION + 1,
+            session_id,
+            parties: parties(),
+            reporter: signer.verifying_key(),
+            evidence: report.provable_errors.into(),
+        };
+        let bundle = EvidenceBundle::sign(&mut OsRng, &signer, contents).unwrap();
+        assert_invalid(&bundle);
+    }
+
+    #[test]
+    fn reporter_or_guilty_party_outside_of_session() {
+        let session_id = SessionId::from_seed::<SP>(b"session");
+        let report = make_report(&session_id, &[0]);
+
+        // The reporter is not a party
+        let signer = TestSigner::new(5);
+        assert!(EvidenceBundle::from_report(&mut OsRng, &signer, &session_id, &parties(), &report).is_err());
+
+        // The guilty party is not a party
+        let report = make_report(&session_id, &[4]);
+        let bundle =
+            EvidenceBundle::from_report(&mut OsRng, &TestSigner::new(1), &session_id, &parties(), &report).unwrap();
+        assert_invalid(&bundle);
+
+        // The reporter blames itself
+        let report = make_report(&session_id, &[1]);
+        let bundle =
+            EvidenceBundle::from_report(&mut OsRng, &TestSigner::new(1), &session_id, &parties(), &report).unwrap();
+        assert_invalid(&bundle);
+    }
+}
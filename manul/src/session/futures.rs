@@ -0,0 +1,642 @@
+//! Runtime-agnostic API for executing sessions, communicating via [`Stream`]s and [`Sink`]s.
+
+use alloc::{boxed::Box, format, sync::Arc, vec, vec::Vec};
+use core::{fmt::Display, future::Future};
+
+use futures::{
+    channel::{mpsc, oneshot},
+    pin_mut, select_biased, stream, FutureExt, Sink, SinkExt, Stream, StreamExt,
+};
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRngCore, SeedableRng};
+use tracing::{debug, trace};
+
+use super::{
+    message::VerifiedMessage,
+    retransmission::Outbox,
+    session::{
+        AsyncSessionParameters, CanFinalize, ProcessedArtifact, ProcessedMessage, RoundOutcome, Session,
+        SessionParameters,
+    },
+    transcript::SessionReport,
+    transport::{answer_resend_request, request_missing_messages},
+    LocalError, LocalErrorKind,
+};
+use crate::protocol::{BoxFuture, Protocol};
+
+pub use super::transport::{Incoming, MessageIn, MessageOut, Outgoing};
+
+/// An interface to the async runtime used by [`par_run_session`] to execute tasks in parallel.
+pub trait Spawner: 'static + Clone + Send + Sync {
+    /// Spawns a future to be executed in the background.
+    fn spawn(&self, future: BoxFuture<'static, ()>);
+
+    /// Spawns a closure performing CPU-bound work to be executed in the background.
+    ///
+    /// The default implementation executes it in a future spawned with [`spawn`](`Self::spawn`);
+    /// runtimes with a dedicated thread pool for blocking work should use it instead.
+    fn spawn_blocking(&self, task: Box<dyn FnOnce() + Send>) {
+        self.spawn(Box::pin(async move { task() }))
+    }
+}
+
+async fn send_item<SP, Si>(tx: &mut Si, item: Outgoing<SP>) -> Result<(), LocalError>
+where
+    SP: SessionParameters,
+    Si: Sink<Outgoing<SP>> + Unpin,
+    Si::Error: Display,
+{
+    let (from, to) = match &item {
+        Outgoing::Message(message_out) => (message_out.from.clone(), message_out.to.clone()),
+        Outgoing::ResendRequest { to, request } => (request.requester().clone(), to.clone()),
+    };
+    tx.send(item).await.map_err(|err| {
+        LocalError::new_with_kind(
+            LocalErrorKind::Transport,
+            format!("Failed to send a message from {from:?} to {to:?}: {err}"),
+        )
+    })
+}
+
+/// Sends out a message created by the session, keeping a copy in the outbox if retransmission is enabled.
+async fn send_message<SP, Si>(
+    tx: &mut Si,
+    outbox: Option<&mut Outbox<SP>>,
+    message_out: MessageOut<SP>,
+) -> Result<(), LocalError>
+where
+    SP: SessionParameters,
+    Si: Sink<Outgoing<SP>> + Unpin,
+    Si::Error: Display,
+{
+    if let Some(outbox) = outbox {
+        outbox.record(&message_out.to, &message_out.message);
+    }
+    send_item(tx, Outgoing::Message(message_out)).await
+}
+
+/// Executes the session waiting for the messages from the `rx` stream
+/// and pushing outgoing messages into the `tx` sink.
+///
+/// The session is terminated when `cancellation` resolves.
+///
+/// Supports both synchronous and asynchronous rounds.
+/// The messages are signed with [`AsyncSessionParameters::sign_digests`], with all the messages of a round
+/// signed in one batch.
+pub async fn run_session<P, SP, Si, St>(
+    rng: &mut impl CryptoRngCore,
+    tx: &mut Si,
+    rx: &mut St,
+    cancellation: impl Future<Output = ()>,
+    session: Session<P, SP>,
+) -> Result<SessionReport<P, SP>, LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    Si: Sink<MessageOut<SP>> + Unpin,
+    Si::Error: Display,
+    St: Stream<Item = MessageIn<SP>> + Unpin,
+{
+    let mut tx = messages_only(tx);
+    let mut rx = rx.map(Incoming::Message);
+    run_session_inner(rng, &mut tx, &mut rx, cancellation, session, None).await
+}
+
+/// Same as [`run_session`], but with the retransmission of lost messages enabled.
+///
+/// The sent messages are kept in an [`Outbox`] to answer the [`Incoming::ResendRequest`]s from the other nodes,
+/// and the missing messages of the current round are requested with [`Outgoing::ResendRequest`]
+/// on every [`Incoming::ResendTimeout`] the caller pushes into `rx`.
+///
+/// The outbox is returned along with the report, so that the caller could keep answering the requests
+/// of the nodes that lost the messages of the last round.
+pub async fn run_session_with_retransmission<P, SP, Si, St>(
+    rng: &mut impl CryptoRngCore,
+    tx: &mut Si,
+    rx: &mut St,
+    cancellation: impl Future<Output = ()>,
+    session: Session<P, SP>,
+) -> Result<(SessionReport<P, SP>, Outbox<SP>), LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    Si: Sink<Outgoing<SP>> + Unpin,
+    Si::Error: Display,
+    St: Stream<Item = Incoming<SP>> + Unpin,
+{
+    let mut outbox = Outbox::new(session.verifier(), session.session_id().clone());
+    let report = run_session_inner(rng, tx, rx, cancellation, session, Some(&mut outbox)).await?;
+    Ok((report, outbox))
+}
+
+/// Adapts a sink of messages to the runners that can also send resend requests,
+/// which are never created if retransmission is disabled.
+fn messages_only<SP, Si>(tx: &mut Si) -> impl Sink<Outgoing<SP>, Error = Si::Error> + Unpin + '_
+where
+    SP: SessionParameters,
+    Si: Sink<MessageOut<SP>> + Unpin,
+{
+    tx.with_flat_map(|item| {
+        stream::iter(match item {
+            Outgoing::Message(message_out) => Some(Ok(message_out)),
+            Outgoing::ResendRequest { .. } => None,
+        })
+    })
+}
+
+/// Executes the session; retransmission is enabled if `outbox` is not `None`.
+async fn run_session_inner<P, SP, Si, St>(
+    rng: &mut impl CryptoRngCore,
+    tx: &mut Si,
+    rx: &mut St,
+    cancellation: impl Future<Output = ()>,
+    session: Session<P, SP>,
+    mut outbox: Option<&mut Outbox<SP>>,
+) -> Result<SessionReport<P, SP>, LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    Si: Sink<Outgoing<SP>> + Unpin,
+    Si::Error: Display,
+    St: Stream<Item = Incoming<SP>> + Unpin,
+{
+    let mut session = session;
+    // Some rounds can finalize early and put off sending messages to the next round. Such messages
+    // will be stored here and applied after the messages for this round are sent.
+    let mut cached_messages = Vec::new();
+
+    let my_id = format!("{:?}", session.verifier());
+
+    let cancellation = cancellation.fuse();
+    pin_mut!(cancellation);
+
+    // Each iteration of the loop progresses the session as follows:
+    //  - Send out messages as dictated by the session "destinations".
+    //  - Apply any cached messages.
+    //  - Enter a nested loop:
+    //      - Try to finalize the session; if we're done, exit the inner loop.
+    //      - Wait until we get an incoming message.
+    //      - Process the message we received and continue the loop.
+    //  - When all messages have been sent and received as specified by the protocol, finalize the
+    //    round.
+    //  - If the protocol outcome is a new round, go to the top of the loop and start over with a
+    //    new session.
+    loop {
+        debug!("{my_id}: *** starting round {:?} ***", session.round_id());
+
+        // This is kept in the main task since it's mutable,
+        // and we don't want to bother with synchronization.
+        let mut accum = session.make_accumulator();
+
+        // Note: generating/sending messages and verifying newly received messages
+        // can be done in parallel, with the results being assembled into `accum`
+        // sequentially in the host task.
+
+        // In production usage, this will happen in a spawned task
+        // (since it can take some time to create a message),
+        // and the artifacts will be sent back to the host task
+        // to be added to the accumulator.
+        // The RNG reference is held across the asynchronous signing, so it has to be `Send`.
+        let mut message_rng = ChaCha20Rng::from_rng(&mut *rng).map_err(|_| LocalError::new("Can't fork the RNG"))?;
+        let messages = session
+            .make_messages_async(&mut message_rng, session.message_destinations())
+            .await?;
+        for (destination, (message, artifact)) in messages {
+            debug!("{my_id}: Sending a message to {destination:?}",);
+            send_message(
+                tx,
+                outbox.as_deref_mut(),
+                MessageOut {
+                    session_id: session.session_id().clone(),
+                    from: session.verifier().clone(),
+                    to: destination,
+                    message,
+                },
+            )
+            .await?;
+
+            // This would happen in a host task
+            session.add_artifact(&mut accum, artifact)?;
+        }
+
+        for preprocessed in cached_messages {
+            // In production usage, this would happen in a spawned task and relayed back to the main task.
+            debug!("{my_id}: Applying a cached message");
+            let processed = session.process_message_async(preprocessed).await;
+
+            // This would happen in a host task.
+            session.add_processed_message(&mut accum, processed)?;
+        }
+
+        loop {
+            match session.can_finalize(&accum) {
+                CanFinalize::Yes => break,
+                CanFinalize::NotYet => {}
+                // Due to already registered invalid messages from nodes,
+                // even if the remaining nodes send correct messages, it won't be enough.
+                // Terminating.
+                CanFinalize::Never => {
+                    tracing::warn!("{my_id}: This session cannot ever be finalized. Terminating.");
+                    return session.terminate_due_to_errors(accum);
+                }
+            }
+
+            debug!("{my_id}: Waiting for a message");
+            let incoming = select_biased! {
+                _ = cancellation => {
+                    return session.terminate_due_to_errors(accum);
+                }
+                incoming = rx.next().fuse() => {
+                    incoming.ok_or_else(|| LocalError::new_with_kind(LocalErrorKind::Transport, "The incoming message stream was closed unexpectedly"))?
+                },
+            };
+
+            let message_in = match incoming {
+                Incoming::Message(message_in) => message_in,
+                Incoming::ResendRequest(request) => {
+                    if let Some(message_out) = answer_resend_request(&session, outbox.as_deref(), &request)? {
+                        send_item(tx, Outgoing::Message(message_out)).await?;
+                    }
+                    continue;
+                }
+                Incoming::ResendTimeout => {
+                    for item in request_missing_messages(rng, &session, &accum, outbox.as_deref())? {
+                        send_item(tx, item).await?;
+                    }
+                    continue;
+                }
+            };
+
+            // Perform quick checks before proceeding with the verification.
+            match session
+                .preprocess_message(&mut accum, &message_in.from, message_in.message)?
+                .ok()
+            {
+                Some(preprocessed) => {
+                    // In production usage, this would happen in a separate task.
+                    debug!("{my_id}: Applying a message from {:?}", message_in.from);
+                    let processed = session.process_message_async(preprocessed).await;
+                    // In production usage, this would be a host task.
+                    session.add_processed_message(&mut accum, processed)?;
+                }
+                None => {
+                    trace!("{my_id} Pre-processing complete. Current state: {accum:?}")
+                }
+            }
+        }
+
+        debug!("{my_id}: Finalizing the round");
+
+        // The RNG reference is held across the asynchronous finalization, so it has to be `Send`.
+        let mut finalize_rng = ChaCha20Rng::from_rng(&mut *rng).map_err(|_| LocalError::new("Can't fork the RNG"))?;
+        match session.finalize_round_async(&mut finalize_rng, accum).await? {
+            RoundOutcome::Finished(report) => break Ok(report),
+            RoundOutcome::AnotherRound {
+                session: new_session,
+                cached_messages: new_cached_messages,
+            } => {
+                session = new_session;
+                cached_messages = new_cached_messages;
+            }
+        }
+    }
+}
+
+// The spawned tasks below drop their references to the session before reporting the results,
+// so that the session could be reclaimed by the host task after all the tasks have reported.
+// The sending results are ignored, since the receivers are only dropped when the round is already over.
+
+/// The result of creating an outgoing message in a separate task.
+type CreatedMessage<SP> = Result<(MessageOut<SP>, ProcessedArtifact<SP>), LocalError>;
+
+/// Creates and signs a message in new tasks, sending the result to `outgoing_tx`.
+///
+/// Creating a message is expected to be CPU-bound, so it is done in a blocking task,
+/// while the signer may need to be awaited.
+fn spawn_message_creation<P, SP>(
+    spawner: &impl Spawner,
+    session: Arc<Session<P, SP>>,
+    rng: &mut impl CryptoRngCore,
+    destination: SP::Verifier,
+    outgoing_tx: mpsc::UnboundedSender<CreatedMessage<SP>>,
+) -> Result<(), LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    P::ProtocolError: Send + Sync,
+{
+    // Spawned tasks must not share the same RNG state; we use the provided RNG to seed new ChaCha RNGs to
+    // ensure each task has access to unique randomness.
+    let mut creation_rng = ChaCha20Rng::from_rng(&mut *rng).map_err(|_| LocalError::new("Can't fork the RNG"))?;
+    let mut signing_rng = ChaCha20Rng::from_rng(&mut *rng).map_err(|_| LocalError::new("Can't fork the RNG"))?;
+
+    let (prepared_tx, prepared_rx) = oneshot::channel();
+    let creation_session = session.clone();
+    let creation_destination = destination.clone();
+    spawner.spawn_blocking(Box::new(move || {
+        let prepared = creation_session.prepare_message(&mut creation_rng, &creation_destination);
+        drop(creation_session);
+        let _ = prepared_tx.send(prepared);
+    }));
+
+    spawner.spawn(Box::pin(async move {
+        let signed = async {
+            let (message, artifact) = prepared_rx
+                .await
+                .map_err(|_| LocalError::new("The message creation task was dropped"))??;
+            let message = session
+                .sign_messages_async(&mut signing_rng, vec![message])
+                .await?
+                .pop()
+                .ok_or_else(|| LocalError::new("The message was not signed"))?;
+            let message_out = MessageOut {
+                session_id: session.session_id().clone(),
+                from: session.verifier().clone(),
+                to: destination,
+                message,
+            };
+            Ok((message_out, artifact))
+        }
+        .await;
+        drop(session);
+        let _ = outgoing_tx.unbounded_send(signed);
+    }));
+
+    Ok(())
+}
+
+/// Processes a message in a new task, sending the result to `processed_tx`.
+///
+/// Synchronous rounds process messages in a blocking task, since it is expected to be CPU-bound.
+fn spawn_message_processing<P, SP>(
+    spawner: &impl Spawner,
+    session: Arc<Session<P, SP>>,
+    processed_tx: mpsc::UnboundedSender<ProcessedMessage<P, SP>>,
+    message: VerifiedMessage<SP::Verifier>,
+) where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    P::ProtocolError: Send + Sync,
+{
+    if session.has_async_round() {
+        spawner.spawn(Box::pin(async move {
+            let processed = session.process_message_async(message).await;
+            drop(session);
+            let _ = processed_tx.unbounded_send(processed);
+        }))
+    } else {
+        spawner.spawn_blocking(Box::new(move || {
+            let processed = session.process_message(message);
+            drop(session);
+            let _ = processed_tx.unbounded_send(processed);
+        }))
+    }
+}
+
+/// Executes the session waiting for the messages from the `rx` stream
+/// and pushing outgoing messages into the `tx` sink.
+/// The messages are created and processed in parallel, in the tasks launched by `spawner`.
+///
+/// The session is terminated when `cancellation` resolves.
+///
+/// This function should be used if message creation and verification takes a significant amount of time,
+/// to offset the parallelizing overhead.
+pub async fn par_run_session<P, SP, Si, St>(
+    rng: &mut impl CryptoRngCore,
+    spawner: &impl Spawner,
+    tx: &mut Si,
+    rx: &mut St,
+    cancellation: impl Future<Output = ()>,
+    session: Session<P, SP>,
+) -> Result<SessionReport<P, SP>, LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    P::ProtocolError: Send + Sync,
+    Si: Sink<MessageOut<SP>> + Unpin,
+    Si::Error: Display,
+    St: Stream<Item = MessageIn<SP>> + Unpin,
+{
+    let mut tx = messages_only(tx);
+    let mut rx = rx.map(Incoming::Message);
+    par_run_session_inner(rng, spawner, &mut tx, &mut rx, cancellation, session, None).await
+}
+
+/// Same as [`par_run_session`], but with the retransmission of lost messages enabled.
+///
+/// See [`run_session_with_retransmission`] for details.
+pub async fn par_run_session_with_retransmission<P, SP, Si, St>(
+    rng: &mut impl CryptoRngCore,
+    spawner: &impl Spawner,
+    tx: &mut Si,
+    rx: &mut St,
+    cancellation: impl Future<Output = ()>,
+    session: Session<P, SP>,
+) -> Result<(SessionReport<P, SP>, Outbox<SP>), LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    P::ProtocolError: Send + Sync,
+    Si: Sink<Outgoing<SP>> + Unpin,
+    Si::Error: Display,
+    St: Stream<Item = Incoming<SP>> + Unpin,
+{
+    let mut outbox = Outbox::new(session.verifier(), session.session_id().clone());
+    let report = par_run_session_inner(rng, spawner, tx, rx, cancellation, session, Some(&mut outbox)).await?;
+    Ok((report, outbox))
+}
+
+/// Executes the session processing the messages in parallel; retransmission is enabled if `outbox` is not `None`.
+async fn par_run_session_inner<P, SP, Si, St>(
+    rng: &mut impl CryptoRngCore,
+    spawner: &impl Spawner,
+    tx: &mut Si,
+    rx: &mut St,
+    cancellation: impl Future<Output = ()>,
+    session: Session<P, SP>,
+    mut outbox: Option<&mut Outbox<SP>>,
+) -> Result<SessionReport<P, SP>, LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    P::ProtocolError: Send + Sync,
+    Si: Sink<Outgoing<SP>> + Unpin,
+    Si::Error: Display,
+    St: Stream<Item = Incoming<SP>> + Unpin,
+{
+    let mut session = Arc::new(session);
+    // Some rounds can finalize early and put off sending messages to the next round. Such messages
+    // will be stored here and applied after the messages for this round are sent.
+    let mut cached_messages = Vec::new();
+
+    let my_id = format!("{:?}", session.verifier());
+
+    let cancellation = cancellation.fuse();
+    pin_mut!(cancellation);
+
+    // Each iteration of the loop progresses the session as follows:
+    //  - Send out messages as dictated by the session "destinations".
+    //  - Apply any cached messages.
+    //  - Enter a nested loop:
+    //      - Try to finalize the session; if we're done, exit the inner loop.
+    //      - Wait until we get an incoming message.
+    //      - Process the message we received and continue the loop.
+    //  - When all messages have been sent and received as specified by the protocol, finalize the
+    //    round.
+    //  - If the protocol outcome is a new round, go to the top of the loop and start over with a
+    //    new session.
+    loop {
+        debug!("{my_id}: *** starting round {:?} ***", session.round_id());
+
+        let (processed_tx, mut processed_rx) = mpsc::unbounded::<ProcessedMessage<P, SP>>();
+        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded::<CreatedMessage<SP>>();
+
+        // This is kept in the main task since it's mutable,
+        // and we don't want to bother with synchronization.
+        let mut accum = session.make_accumulator();
+
+        // Note: generating/sending messages and verifying newly received messages
+        // can be done in parallel, with the results being assembled into `accum`
+        // sequentially in the host task.
+
+        for destination in session.message_destinations() {
+            spawn_message_creation(spawner, session.clone(), rng, destination.clone(), outgoing_tx.clone())?;
+        }
+
+        for preprocessed in cached_messages {
+            debug!("{my_id}: Applying a cached message");
+            spawn_message_processing(spawner, session.clone(), processed_tx.clone(), preprocessed);
+        }
+
+        let can_finalize = loop {
+            match session.can_finalize(&accum) {
+                CanFinalize::Yes => break true,
+                CanFinalize::NotYet => {}
+                // Due to already registered invalid messages from nodes,
+                // even if the remaining nodes send correct messages, it won't be enough.
+                // Terminating.
+                CanFinalize::Never => break false,
+            }
+
+            select_biased! {
+                _ = cancellation => {
+                    break false;
+                }
+                processed = processed_rx.next() => {
+                    let processed = processed.ok_or_else(|| LocalError::new("The processed message channel was closed unexpectedly"))?;
+                    session.add_processed_message(&mut accum, processed)?;
+                }
+                outgoing = outgoing_rx.next() => {
+                    let (message_out, artifact) = outgoing.ok_or_else(|| LocalError::new("The outgoing message channel was closed unexpectedly"))??;
+                    debug!("{my_id}: Sending a message to {:?}", message_out.to);
+                    send_message(tx, outbox.as_deref_mut(), message_out).await?;
+                    session.add_artifact(&mut accum, artifact)?;
+                }
+                incoming = rx.next().fuse() => {
+                    let incoming = incoming.ok_or_else(|| LocalError::new_with_kind(LocalErrorKind::Transport, "The incoming message stream was closed unexpectedly"))?;
+                    let message_in = match incoming {
+                        Incoming::Message(message_in) => message_in,
+                        Incoming::ResendRequest(request) => {
+                            if let Some(message_out) = answer_resend_request(&session, outbox.as_deref(), &request)? {
+                                send_item(tx, Outgoing::Message(message_out)).await?;
+                            }
+                            continue;
+                        }
+                        Incoming::ResendTimeout => {
+                            for item in request_missing_messages(rng, &session, &accum, outbox.as_deref())? {
+                                send_item(tx, item).await?;
+                            }
+                            continue;
+                        }
+                    };
+                    match session
+                        .preprocess_message(&mut accum, &message_in.from, message_in.message)?
+                        .ok()
+                    {
+                        Some(preprocessed) => {
+                            debug!("{my_id}: Applying a message from {:?}", message_in.from);
+                            spawn_message_processing(spawner, session.clone(), processed_tx.clone(), preprocessed);
+                        }
+                        None => {
+                            trace!("{my_id} Pre-processing complete. Current state: {accum:?}")
+                        }
+                    }
+                },
+            }
+        };
+
+        debug!("{my_id}: Finalizing the round {}", session.round_id());
+
+        // Drop our copies of the senders to let the channels close once all the spawned tasks are finished.
+        drop(outgoing_tx);
+        drop(processed_tx);
+
+        // Send all the remaining messages
+        while let Some(outgoing) = outgoing_rx.next().await {
+            let (message_out, artifact) = outgoing?;
+            send_message(tx, outbox.as_deref_mut(), message_out).await?;
+            session.add_artifact(&mut accum, artifact)?;
+        }
+
+        debug!("{my_id}: Sent out all remaining messages");
+
+        // Wait for the message processing tasks to finish; their results are not needed anymore.
+        while processed_rx.next().await.is_some() {}
+
+        let session_inner = Arc::into_inner(session)
+            .ok_or_else(|| LocalError::new("There are still references to the session left"))?;
+
+        if !can_finalize {
+            return session_inner.terminate_due_to_errors(accum);
+        }
+
+        // The RNG reference is held across the asynchronous finalization, so it has to be `Send`.
+        let mut finalize_rng = ChaCha20Rng::from_rng(&mut *rng).map_err(|_| LocalError::new("Can't fork the RNG"))?;
+        match session_inner.finalize_round_async(&mut finalize_rng, accum).await? {
+            RoundOutcome::Finished(report) => return Ok(report),
+            RoundOutcome::AnotherRound {
+                session: new_session,
+                cached_messages: new_cached_messages,
+            } => {
+                session = Arc::new(new_session);
+                cached_messages = new_cached_messages;
+            }
+        }
+    }
+}
+
+/// Turns the session into a [`Sink`] accepting the incoming messages and a [`Stream`] of the outgoing messages,
+/// which can be composed with codecs and framed transports directly.
+///
+/// The session is executed (with the semantics of [`run_session`]) by the returned future,
+/// which resolves to the session report, and has to be polled for the session to make progress.
+/// The stream ends when the session is finished.
+///
+/// The session is terminated when `cancellation` resolves.
+#[allow(clippy::type_complexity)]
+pub fn split_session<P, SP>(
+    rng: impl 'static + CryptoRngCore + Send,
+    cancellation: impl 'static + Future<Output = ()> + Send,
+    session: Session<P, SP>,
+) -> (
+    mpsc::UnboundedSender<MessageIn<SP>>,
+    mpsc::UnboundedReceiver<MessageOut<SP>>,
+    BoxFuture<'static, Result<SessionReport<P, SP>, LocalError>>,
+)
+where
+    P: Protocol<SP::Verifier>,
+    SP: AsyncSessionParameters,
+    P::ProtocolError: Send + Sync,
+    P::Result: Send,
+{
+    let (incoming_tx, mut incoming_rx) = mpsc::unbounded();
+    let (mut outgoing_tx, outgoing_rx) = mpsc::unbounded();
+    let mut rng = rng;
+    let task = Box::pin(async move {
+        let report = run_session(&mut rng, &mut outgoing_tx, &mut incoming_rx, cancellation, session).await;
+        // Make sure the stream of the outgoing messages ends even if the sink is still held by someone.
+        outgoing_tx.close_channel();
+        report
+    });
+    (incoming_tx, outgoing_rx, task)
+}
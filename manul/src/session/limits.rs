@@ -0,0 +1,81 @@
+use alloc::{format, string::String};
+
+/// Limits on the resources a single node can make a [`Session`](`super::Session`) spend on its messages.
+///
+/// The limits are checked in [`Session::preprocess_message`](`super::Session::preprocess_message`)
+/// before the signatures are verified, and a message violating them is recorded
+/// as an unprovable error of its sender.
+/// Since honest nodes must not violate them, all the nodes in a session should use the same limits,
+/// and these should be chosen with the largest messages of the protocol in mind.
+///
+/// By default, there are no limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MessageLimits {
+    max_part_size: Option<usize>,
+    max_cached_messages: Option<usize>,
+    max_bytes_per_sender: Option<usize>,
+}
+
+impl MessageLimits {
+    /// No limits.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum size in bytes of a single signed message part
+    /// (the serialized payload along with its signature).
+    pub fn with_max_part_size(mut self, max_part_size: usize) -> Self {
+        self.max_part_size = Some(max_part_size);
+        self
+    }
+
+    /// Sets the maximum number of messages for the future rounds that can be cached for a single sender.
+    pub fn with_max_cached_messages(mut self, max_cached_messages: usize) -> Self {
+        self.max_cached_messages = Some(max_cached_messages);
+        self
+    }
+
+    /// Sets the maximum total size in bytes of the signed message parts
+    /// received from a single sender during the whole session
+    /// (including the messages cached for the future rounds, and the ones with invalid signatures).
+    ///
+    /// Redeliveries of the messages already received are not counted.
+    pub fn with_max_bytes_per_sender(mut self, max_bytes_per_sender: usize) -> Self {
+        self.max_bytes_per_sender = Some(max_bytes_per_sender);
+        self
+    }
+
+    /// Checks the sizes of the parts of a single message.
+    pub(crate) fn check_part_sizes(&self, part_sizes: impl Iterator<Item = usize>) -> Result<(), String> {
+        if let Some(max_part_size) = self.max_part_size {
+            for size in part_sizes {
+                if size > max_part_size {
+                    return Err(format!(
+                        "The message part size ({size} bytes) exceeds the limit ({max_part_size} bytes)"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the number of messages cached for the sender, including the new one.
+    pub(crate) fn check_cached_messages(&self, cached_messages: usize) -> Result<(), String> {
+        match self.max_cached_messages {
+            Some(max_cached_messages) if cached_messages > max_cached_messages => Err(format!(
+                "The number of cached messages from the sender exceeds the limit ({max_cached_messages})"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the total size of the messages received from the sender, including the new one.
+    pub(crate) fn check_bytes_per_sender(&self, bytes: usize) -> Result<(), String> {
+        match self.max_bytes_per_sender {
+            Some(max_bytes_per_sender) if bytes > max_bytes_per_sender => Err(format!(
+                "The total size of the messages from the sender ({bytes} bytes) exceeds the limit ({max_bytes_per_sender} bytes)"
+            )),
+            _ => Ok(()),
+        }
+    }
+}
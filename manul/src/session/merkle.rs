@@ -0,0 +1,297 @@
+use alloc::{boxed::Box, format, vec, vec::Vec};
+
+use digest::Digest;
+use serde::{Deserialize, Serialize};
+use serde_encoded_bytes::{Hex, SliceLike};
+
+use super::{message::SignedMessageHash, session::SessionParameters, wire_format::WireFormat, LocalError};
+
+/// A node of a Merkle tree over echoed message hashes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct MerkleHash(#[serde(with = "SliceLike::<Hex>")] Box<[u8]>);
+
+impl MerkleHash {
+    fn from_digest<D: Digest>(digest: D) -> Self {
+        Self(digest.finalize().as_ref().into())
+    }
+
+    /// Returns the leaf committing to the signed hash of a message part received from `sender`.
+    ///
+    /// The signature is not included, so that the leaf only depends on what was signed.
+    pub fn leaf<SP: SessionParameters>(sender: &SP::Verifier, hash: &SignedMessageHash) -> Result<Self, LocalError> {
+        Ok(Self::from_digest(
+            SP::Digest::new_with_prefix(b"MerkleEchoLeaf")
+                .chain_update(SP::WireFormat::serialize(sender)?)
+                .chain_update(hash.content_digest::<SP>()?),
+        ))
+    }
+
+    fn node<SP: SessionParameters>(left: &Self, right: &Self) -> Self {
+        Self::from_digest(
+            SP::Digest::new_with_prefix(b"MerkleEchoNode")
+                .chain_update(&left.0)
+                .chain_update(&right.0),
+        )
+    }
+
+    fn empty<SP: SessionParameters>() -> Self {
+        Self::from_digest(SP::Digest::new_with_prefix(b"MerkleEchoEmpty"))
+    }
+
+    /// Returns the node a [`MerkleTree`] builds from the given children,
+    /// or `None` if there is not one or two of them.
+    pub fn parent<SP: SessionParameters>(children: &[Self]) -> Option<Self> {
+        match children {
+            [left, right] => Some(Self::node::<SP>(left, right)),
+            [single] => Some(single.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// The position of a node in a [`MerkleTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MerklePosition {
+    /// The layer of the node, counting from the leaves.
+    layer: usize,
+    /// The index of the node in its layer.
+    index: usize,
+}
+
+impl MerklePosition {
+    /// Returns the index of the leaf at this position, or `None` if it is not a leaf position.
+    pub fn leaf_index(&self) -> Option<usize> {
+        (self.layer == 0).then_some(self.index)
+    }
+}
+
+/// A Merkle tree where a node without a sibling is promoted to the next layer unchanged.
+#[derive(Debug, Clone)]
+pub(crate) struct MerkleTree {
+    // The first layer contains the leaves, the last one contains the root.
+    layers: Vec<Vec<MerkleHash>>,
+}
+
+impl MerkleTree {
+    pub fn new<SP: SessionParameters>(leaves: Vec<MerkleHash>) -> Self {
+        if leaves.is_empty() {
+            return Self {
+                layers: vec![vec![MerkleHash::empty::<SP>()]],
+            };
+        }
+
+        let mut layers = vec![leaves];
+        while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+            let next = layer
+                .chunks(2)
+                .map(|pair| MerkleHash::parent::<SP>(pair).expect("chunks have one or two elements"))
+                .collect();
+            layers.push(next);
+        }
+        Self { layers }
+    }
+
+    pub fn root(&self) -> &MerkleHash {
+        self.layers
+            .last()
+            .and_then(|layer| layer.first())
+            .expect("the tree has at least one layer with one node by construction")
+    }
+
+    /// Returns the number of layers above the leaves.
+    pub fn height(&self) -> usize {
+        self.layers.len() - 1
+    }
+
+    /// Returns the position of the root.
+    pub fn root_position(&self) -> MerklePosition {
+        MerklePosition {
+            layer: self.height(),
+            index: 0,
+        }
+    }
+
+    /// Returns the positions and the values of the children of the node at the given position
+    /// (none if it is a leaf, or if the position is out of range).
+    pub fn children(&self, position: MerklePosition) -> Vec<(MerklePosition, MerkleHash)> {
+        let Some(layer) = position.layer.checked_sub(1) else {
+            return Vec::new();
+        };
+        let Some(nodes) = self.layers.get(layer) else {
+            return Vec::new();
+        };
+        let first = position.index.saturating_mul(2);
+        nodes
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(2)
+            .map(|(index, node)| (MerklePosition { layer, index }, node.clone()))
+            .collect()
+    }
+
+    /// Returns the inclusion proof for the leaf with the given index.
+    pub fn proof(&self, index: usize) -> Result<MerkleProof, LocalError> {
+        let leaf_count = self.layers.first().map_or(0, |layer| layer.len());
+        if index >= leaf_count {
+            return Err(LocalError::new(format!(
+                "Leaf index {index} is out of range (the tree has {leaf_count} leaves)"
+            )));
+        }
+
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for layer in self.layers.iter() {
+            if let Some(sibling) = layer.get(position ^ 1) {
+                siblings.push(sibling.clone());
+            }
+            position /= 2;
+        }
+        // The root layer has no siblings, so the loop above does not add anything for it.
+
+        Ok(MerkleProof {
+            index: u32::try_from(index).map_err(|_| LocalError::new("Leaf index does not fit in u32"))?,
+            leaf_count: u32::try_from(leaf_count).map_err(|_| LocalError::new("Leaf count does not fit in u32"))?,
+            siblings,
+        })
+    }
+}
+
+/// An inclusion proof for a leaf of a [`MerkleTree`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct MerkleProof {
+    index: u32,
+    leaf_count: u32,
+    siblings: Vec<MerkleHash>,
+}
+
+impl MerkleProof {
+    /// Returns the root of the tree the proof was created for, assuming it contains `leaf`,
+    /// or `None` if the proof is malformed.
+    pub fn root<SP: SessionParameters>(&self, leaf: &MerkleHash) -> Option<MerkleHash> {
+        if self.index >= self.leaf_count {
+            return None;
+        }
+
+        let mut siblings = self.siblings.iter();
+        let mut hash = leaf.clone();
+        let mut position = self.index;
+        let mut layer_len = self.leaf_count;
+        while layer_len > 1 {
+            let has_sibling = (position ^ 1) < layer_len;
+            if has_sibling {
+                let sibling = siblings.next()?;
+                if sibling.0.len() != hash.0.len() {
+                    return None;
+                }
+                hash = if position % 2 == 0 {
+                    MerkleHash::node::<SP>(&hash, sibling)
+                } else {
+                    MerkleHash::node::<SP>(sibling, &hash)
+                };
+            }
+            position /= 2;
+            layer_len = layer_len.div_ceil(2);
+        }
+
+        // All the siblings must be used
+        if siblings.next().is_some() {
+            return None;
+        }
+
+        Some(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{boxed::Box, vec::Vec};
+
+    use super::{MerkleHash, MerklePosition, MerkleTree};
+    use crate::dev::{BinaryFormat, TestSessionParams};
+
+    type SP = TestSessionParams<BinaryFormat>;
+
+    fn leaves(count: u8) -> Vec<MerkleHash> {
+        (0..count).map(|i| MerkleHash(Box::new([i; 32]))).collect()
+    }
+
+    #[test]
+    fn proofs() {
+        for count in 1..10 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new::<SP>(leaves.clone());
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert_eq!(proof.root::<SP>(leaf).as_ref(), Some(tree.root()));
+
+                // A proof does not work for a different leaf
+                let other = &leaves[(index + 1) % leaves.len()];
+                if other != leaf {
+                    assert_ne!(proof.root::<SP>(other).as_ref(), Some(tree.root()));
+                }
+            }
+            assert!(tree.proof(leaves.len()).is_err());
+        }
+    }
+
+    #[test]
+    fn malformed_proofs() {
+        let leaves = leaves(5);
+        let tree = MerkleTree::new::<SP>(leaves.clone());
+        let proof = tree.proof(2).unwrap();
+
+        let mut extra_sibling = proof.clone();
+        extra_sibling.siblings.push(leaves[0].clone());
+        assert!(extra_sibling.root::<SP>(&leaves[2]).is_none());
+
+        let mut missing_sibling = proof.clone();
+        missing_sibling.siblings.pop();
+        assert!(missing_sibling.root::<SP>(&leaves[2]).is_none());
+
+        let mut out_of_range = proof;
+        out_of_range.index = 5;
+        assert!(out_of_range.root::<SP>(&leaves[2]).is_none());
+    }
+
+    #[test]
+    fn children() {
+        fn check_subtree(
+            tree: &MerkleTree,
+            position: MerklePosition,
+            node: &MerkleHash,
+            leaf_indices: &mut Vec<usize>,
+        ) {
+            let children = tree.children(position);
+            if children.is_empty() {
+                leaf_indices.push(position.leaf_index().unwrap());
+                return;
+            }
+            let child_nodes = children
+                .iter()
+                .map(|(_position, child)| child.clone())
+                .collect::<Vec<_>>();
+            assert_eq!(MerkleHash::parent::<SP>(&child_nodes).as_ref(), Some(node));
+            for (child_position, child) in children {
+                check_subtree(tree, child_position, &child, leaf_indices);
+            }
+        }
+
+        for count in 1..10 {
+            let tree = MerkleTree::new::<SP>(leaves(count));
+            let mut leaf_indices = Vec::new();
+            check_subtree(&tree, tree.root_position(), tree.root(), &mut leaf_indices);
+            // Descending from the root reaches every leaf in order
+            assert_eq!(leaf_indices, (0..usize::from(count)).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn roots_differ() {
+        let tree1 = MerkleTree::new::<SP>(leaves(4));
+        let tree2 = MerkleTree::new::<SP>(leaves(3));
+        let tree3 = MerkleTree::new::<SP>(Vec::new());
+        assert_ne!(tree1.root(), tree2.root());
+        assert_ne!(tree2.root(), tree3.root());
+    }
+}
@@ -0,0 +1,626 @@
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    format,
+    vec::Vec,
+};
+use core::fmt::Debug;
+
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::{
+    echo::{check_echoed_hash, EchoRoundError},
+    merkle::{MerkleHash, MerklePosition, MerkleProof, MerkleTree},
+    message::{SignedMessageHash, SignedMessagePart},
+    party_encoding::{PartiesDigest, PartyEncoding, PartyRef},
+    session::{EchoRoundInfo, SessionParameters},
+    LocalError,
+};
+use crate::protocol::{
+    Artifact, BoxedFormat, BoxedRound, CommunicationInfo, DirectMessage, EchoBroadcast, EchoRoundParticipation,
+    FinalizeOutcome, NormalBroadcast, Payload, Protocol, ProtocolMessage, ProtocolMessagePart, ReceiveError,
+    RemoteErrorKind, Round, TransitionInfo,
+};
+
+/// The message sent in the Merkle echo round.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct MerkleEchoMessage {
+    /// The root of the Merkle tree built over the hashes of the echo broadcasts
+    /// received by the sender (except for its own), in the order of the sender IDs.
+    pub(super) root: MerkleHash,
+}
+
+/// An alternative to [`EchoRound`](`super::echo::EchoRound`) where, instead of the full set of echoed hashes,
+/// every node broadcasts a Merkle root of it.
+///
+/// If some of the received roots do not match the one the node built itself,
+/// the node proceeds to a series of [`MerkleResolutionRound`]s with the senders of the mismatched roots
+/// where the trees are bisected to find the culprit.
+#[derive_where::derive_where(Debug)]
+pub struct MerkleEchoRound<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    verifier: SP::Verifier,
+    echo_broadcasts: BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>>,
+    leaves: BTreeMap<SP::Verifier, MerkleHash>,
+    echo_round_info: EchoRoundInfo<SP::Verifier>,
+    party_encoding: PartyEncoding<SP::Verifier>,
+    parties_digest: Option<PartiesDigest>,
+    communication_info: CommunicationInfo<SP::Verifier>,
+    main_round: BoxedRound<SP::Verifier, P>,
+    payloads: BTreeMap<SP::Verifier, Payload>,
+    artifacts: BTreeMap<SP::Verifier, Artifact>,
+}
+
+impl<P, SP> MerkleEchoRound<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    pub fn new(
+        verifier: SP::Verifier,
+        echo_broadcasts: BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>>,
+        echo_round_info: EchoRoundInfo<SP::Verifier>,
+        party_encoding: PartyEncoding<SP::Verifier>,
+        main_round: BoxedRound<SP::Verifier, P>,
+        payloads: BTreeMap<SP::Verifier, Payload>,
+        artifacts: BTreeMap<SP::Verifier, Artifact>,
+    ) -> Result<Self, LocalError> {
+        debug!(
+            "{:?}: initialized Merkle echo round with {:?}",
+            verifier, echo_round_info
+        );
+
+        let communication_info = CommunicationInfo {
+            message_destinations: echo_round_info.message_destinations.clone(),
+            expecting_messages_from: echo_round_info.expecting_messages_from.clone(),
+            optional_messages_from: BTreeSet::new(),
+            echo_round_participation: EchoRoundParticipation::Default,
+        };
+
+        let parties_digest = party_encoding.digest::<SP>()?;
+
+        let leaves = echo_broadcasts
+            .iter()
+            .map(|(id, echo_broadcast)| {
+                Ok((
+                    id.clone(),
+                    MerkleHash::leaf::<SP>(id, &echo_broadcast.to_signed_hash::<SP>())?,
+                ))
+            })
+            .collect::<Result<_, LocalError>>()?;
+
+        Ok(Self {
+            verifier,
+            echo_broadcasts,
+            leaves,
+            echo_round_info,
+            party_encoding,
+            parties_digest,
+            communication_info,
+            main_round,
+            payloads,
+            artifacts,
+        })
+    }
+
+    /// Returns the tree built over the hashes of the broadcasts from the given senders.
+    fn tree<'a>(&self, senders: impl Iterator<Item = &'a SP::Verifier>) -> Result<MerkleTree, LocalError> {
+        let leaves = senders
+            .map(|id| {
+                self.leaves
+                    .get(id)
+                    .cloned()
+                    .ok_or_else(|| LocalError::new(format!("Missing the echo broadcast from {id:?}")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(MerkleTree::new::<SP>(leaves))
+    }
+
+    /// Returns the senders of the broadcasts this node commits to (all except its own).
+    fn committed_senders(&self) -> impl Iterator<Item = &SP::Verifier> {
+        self.echo_broadcasts.keys().filter(|id| *id != &self.verifier)
+    }
+
+    /// Returns the senders of the broadcasts `from` is expected to commit to (all except its own).
+    fn expected_senders<'a>(&'a self, from: &'a SP::Verifier) -> impl Iterator<Item = &'a SP::Verifier> {
+        self.echo_round_info.expected_echos.iter().filter(move |id| *id != from)
+    }
+}
+
+impl<P, SP> Round<SP::Verifier> for MerkleEchoRound<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    type Protocol = P;
+
+    fn transition_info(&self) -> TransitionInfo {
+        // See the comment in `EchoRound::transition_info()`
+        self.main_round
+            .transition_info()
+            .merkle_echo()
+            .expect("the main round is not an echo round")
+    }
+
+    fn communication_info(&self) -> CommunicationInfo<SP::Verifier> {
+        self.communication_info.clone()
+    }
+
+    fn make_normal_broadcast(
+        &self,
+        _rng: &mut dyn CryptoRngCore,
+        format: &BoxedFormat,
+    ) -> Result<NormalBroadcast, LocalError> {
+        debug!("{:?}: making a Merkle echo round message", self.verifier);
+
+        // Don't commit to our own message
+        if !self.echo_broadcasts.contains_key(&self.verifier) {
+            return Err(LocalError::new(format!(
+                "Expected {:?} to be in the set of all echo messages",
+                self.verifier
+            )));
+        }
+        let root = self.tree(self.committed_senders())?.root().clone();
+
+        NormalBroadcast::new(format, MerkleEchoMessage { root })
+    }
+
+    fn receive_message(
+        &self,
+        format: &BoxedFormat,
+        from: &SP::Verifier,
+        message: ProtocolMessage,
+    ) -> Result<Payload, ReceiveError<SP::Verifier, Self::Protocol>> {
+        debug!("{:?}: received a Merkle echo message from {:?}", self.verifier, from);
+
+        message.echo_broadcast.assert_is_none()?;
+        message.direct_message.assert_is_none()?;
+
+        let message = message.normal_broadcast.deserialize::<MerkleEchoMessage>(format)?;
+
+        // `from` is expected to commit to all the messages from `expected_echos` except its own.
+        let expected_tree = self.tree(self.expected_senders(from))?;
+
+        // A mismatch is not an error by itself, since we do not know yet whose fault it is.
+        // The payload records the mismatched root, so that the disputed leaf can be located
+        // together with `from` in the resolution rounds.
+        let mismatched_root = (&message.root != expected_tree.root()).then_some(message.root);
+        Ok(Payload::new(mismatched_root))
+    }
+
+    fn finalize(
+        self: Box<Self>,
+        rng: &mut dyn CryptoRngCore,
+        payloads: BTreeMap<SP::Verifier, Payload>,
+        _artifacts: BTreeMap<SP::Verifier, Artifact>,
+    ) -> Result<FinalizeOutcome<SP::Verifier, Self::Protocol>, LocalError> {
+        let mut mismatched_roots = BTreeMap::new();
+        for (id, payload) in payloads {
+            if let Some(root) = payload.downcast::<Option<MerkleHash>>()? {
+                mismatched_roots.insert(id, root);
+            }
+        }
+
+        if mismatched_roots.is_empty() {
+            return self.main_round.finalize(rng, self.payloads, self.artifacts);
+        }
+
+        debug!(
+            "{:?}: Merkle roots mismatched for {:?}, proceeding to resolution",
+            self.verifier,
+            mismatched_roots.keys()
+        );
+        Ok(FinalizeOutcome::AnotherRound(BoxedRound::new_dynamic(
+            MerkleResolutionRound::new(*self, mismatched_roots)?,
+        )))
+    }
+}
+
+/// The message sent in the Merkle echo resolution rounds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum MerkleResolutionMessage<Id> {
+    /// The children of the disputed nodes.
+    Bisection {
+        /// The children in the tree the sender committed to.
+        committed: Vec<MerkleHash>,
+        /// The children in the sender's version of the tree the recipient committed to.
+        expected: Vec<MerkleHash>,
+    },
+    /// The disputed leaf of the tree the sender committed to (`None` if the tree is empty).
+    Reveal(Option<RevealedLeaf<Id>>),
+}
+
+/// A leaf of a Merkle tree revealed along with its inclusion proof.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RevealedLeaf<Id> {
+    /// The commitment to the party list `sender` refers to (if the party indices are used).
+    pub(super) parties_digest: Option<PartiesDigest>,
+    pub(super) sender: PartyRef<Id>,
+    pub(super) hash: SignedMessageHash,
+    pub(super) proof: MerkleProof,
+}
+
+/// The search for the disputed leaves with one of the nodes whose root did not match ours.
+///
+/// Both nodes follow two paths down from the roots at the same time:
+/// one in the tree we committed to, and the other in the tree the counterpart committed to.
+/// At each step they exchange the children of the current nodes and descend to the first child they disagree on,
+/// and once both paths reach the leaves, they reveal the leaves from their committed trees.
+#[derive(Debug, Clone)]
+struct Bisection {
+    /// The position of the disputed node in the tree we committed to.
+    ours: MerklePosition,
+    /// The counterpart's version of that node, if it has already sent it.
+    their_version_of_ours: Option<MerkleHash>,
+    /// The root the counterpart committed to, and our version of its tree.
+    their_root: MerkleHash,
+    their_tree: MerkleTree,
+    /// The position of the disputed node in the tree the counterpart committed to.
+    theirs: MerklePosition,
+    /// The counterpart's committed value of that node.
+    their_node: MerkleHash,
+    /// The step where the leaves are revealed.
+    reveal_step: u8,
+}
+
+/// The positions and the counterpart's values of the nodes to descend to
+/// (`None` if the corresponding path has already reached the leaves).
+#[derive(Debug, Clone)]
+struct BisectionStep {
+    ours: Option<(MerklePosition, MerkleHash)>,
+    theirs: Option<(MerklePosition, MerkleHash)>,
+}
+
+/// Checks the children of a disputed node sent by the counterpart,
+/// and returns the first one that differs from ours (or `None` if the node is a leaf).
+fn descend<SP: SessionParameters>(
+    tree: &MerkleTree,
+    position: MerklePosition,
+    their_node: Option<&MerkleHash>,
+    their_children: Vec<MerkleHash>,
+) -> Result<Option<(MerklePosition, MerkleHash)>, &'static str> {
+    let children = tree.children(position);
+    if children.len() != their_children.len() {
+        return Err("Unexpected number of children of the disputed node");
+    }
+    if children.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(node) = their_node {
+        if MerkleHash::parent::<SP>(&their_children).as_ref() != Some(node) {
+            return Err("The children of the disputed node do not match the node itself");
+        }
+    }
+
+    children
+        .into_iter()
+        .zip(their_children)
+        .find(|((_position, ours), theirs)| ours != theirs)
+        .map(|((position, _ours), theirs)| Some((position, theirs)))
+        .ok_or("The children of the disputed node are the same as ours")
+}
+
+/// A round following [`MerkleEchoRound`] where the nodes whose Merkle roots did not match
+/// take one step down the trees the roots were built from, towards the disputed leaves.
+///
+/// Since the rounds take the logarithm of the number of nodes to reach the leaves,
+/// only a few hashes are exchanged in each of them instead of the full sets of echoed hashes.
+/// The round where the leaves are revealed always ends with a provable or an unprovable error for every participant,
+/// since the roots of honest nodes can only differ if someone sent them different echo broadcasts.
+#[derive_where::derive_where(Debug)]
+pub struct MerkleResolutionRound<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    echo_round: MerkleEchoRound<P, SP>,
+    step: u8,
+    /// The tree we committed to in the Merkle echo round, and the senders of its leaves.
+    tree: MerkleTree,
+    senders: Vec<SP::Verifier>,
+    bisections: BTreeMap<SP::Verifier, Bisection>,
+    communication_info: CommunicationInfo<SP::Verifier>,
+}
+
+impl<P, SP> MerkleResolutionRound<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    fn new(echo_round: MerkleEchoRound<P, SP>, roots: BTreeMap<SP::Verifier, MerkleHash>) -> Result<Self, LocalError> {
+        let senders = echo_round.committed_senders().cloned().collect::<Vec<_>>();
+        let tree = echo_round.tree(senders.iter())?;
+
+        let bisections = roots
+            .into_iter()
+            .map(|(id, root)| {
+                let their_tree = echo_round.tree(echo_round.expected_senders(&id))?;
+                let reveal_step = u8::try_from(tree.height().max(their_tree.height()))
+                    .map_err(|_| LocalError::new("The Merkle tree is too high"))?;
+                let bisection = Bisection {
+                    ours: tree.root_position(),
+                    their_version_of_ours: None,
+                    theirs: their_tree.root_position(),
+                    their_tree,
+                    their_node: root.clone(),
+                    their_root: root,
+                    reveal_step,
+                };
+                Ok((id, bisection))
+            })
+            .collect::<Result<_, LocalError>>()?;
+
+        Ok(Self::new_step(echo_round, 0, tree, senders, bisections))
+    }
+
+    fn new_step(
+        echo_round: MerkleEchoRound<P, SP>,
+        step: u8,
+        tree: MerkleTree,
+        senders: Vec<SP::Verifier>,
+        bisections: BTreeMap<SP::Verifier, Bisection>,
+    ) -> Self {
+        let parties = bisections.keys().cloned().collect::<BTreeSet<_>>();
+        let communication_info = CommunicationInfo {
+            message_destinations: parties.clone(),
+            expecting_messages_from: parties,
+            optional_messages_from: BTreeSet::new(),
+            echo_round_participation: EchoRoundParticipation::Default,
+        };
+        Self {
+            echo_round,
+            step,
+            tree,
+            senders,
+            bisections,
+            communication_info,
+        }
+    }
+
+    fn bisection(&self, id: &SP::Verifier) -> Result<&Bisection, LocalError> {
+        self.bisections
+            .get(id)
+            .ok_or_else(|| LocalError::new(format!("No Merkle echo resolution with {id:?} is in progress")))
+    }
+
+    /// Returns the leaf at the given position of the tree we committed to.
+    fn reveal(&self, position: MerklePosition) -> Result<Option<RevealedLeaf<SP::Verifier>>, LocalError> {
+        // The tree over no leaves only has an auxiliary one, with nothing to reveal.
+        if self.senders.is_empty() {
+            return Ok(None);
+        }
+
+        let index = position
+            .leaf_index()
+            .ok_or_else(|| LocalError::new("The disputed node has not reached the leaves"))?;
+        let sender = self
+            .senders
+            .get(index)
+            .ok_or_else(|| LocalError::new(format!("Leaf index {index} is out of range")))?;
+        let echo_broadcast = self
+            .echo_round
+            .echo_broadcasts
+            .get(sender)
+            .ok_or_else(|| LocalError::new(format!("Missing the echo broadcast from {sender:?}")))?;
+
+        Ok(Some(RevealedLeaf {
+            parties_digest: self.echo_round.parties_digest.clone(),
+            sender: self.echo_round.party_encoding.encode(sender),
+            hash: echo_broadcast.to_signed_hash::<SP>(),
+            proof: self.tree.proof(index)?,
+        }))
+    }
+
+    fn receive_bisection(
+        &self,
+        bisection: &Bisection,
+        committed: Vec<MerkleHash>,
+        expected: Vec<MerkleHash>,
+    ) -> Result<Payload, ReceiveError<SP::Verifier, P>> {
+        let to_error = |message| ReceiveError::unprovable_with_kind(RemoteErrorKind::InvalidEchoRoundMessage, message);
+
+        let ours = descend::<SP>(
+            &self.tree,
+            bisection.ours,
+            bisection.their_version_of_ours.as_ref(),
+            expected,
+        )
+        .map_err(to_error)?;
+        let theirs = descend::<SP>(
+            &bisection.their_tree,
+            bisection.theirs,
+            Some(&bisection.their_node),
+            committed,
+        )
+        .map_err(to_error)?;
+
+        Ok(Payload::new(BisectionStep { ours, theirs }))
+    }
+
+    fn receive_reveal(
+        &self,
+        bisection: &Bisection,
+        revealed: Option<RevealedLeaf<SP::Verifier>>,
+    ) -> Result<Payload, ReceiveError<SP::Verifier, P>> {
+        let echo_round = &self.echo_round;
+
+        let revealed = revealed.ok_or_else(|| {
+            ReceiveError::unprovable_with_kind(
+                RemoteErrorKind::InvalidEchoRoundMessage,
+                "The sender did not reveal the disputed leaf",
+            )
+        })?;
+
+        let sender = (revealed.parties_digest == echo_round.parties_digest)
+            .then(|| echo_round.party_encoding.decode(&revealed.sender))
+            .flatten()
+            .ok_or_else(|| {
+                ReceiveError::unprovable_with_kind(
+                    RemoteErrorKind::InvalidEchoRoundMessage,
+                    "The revealed leaf refers to a party of a different session",
+                )
+            })?;
+
+        // Check that the leaf is a part of the tree `from` committed to in the Merkle echo round.
+        // If it is not, it is a provable fault, since both the root and the leaf are signed by `from`.
+
+        let leaf = MerkleHash::leaf::<SP>(sender, &revealed.hash)?;
+        if revealed.proof.root::<SP>(&leaf).as_ref() != Some(&bisection.their_root) {
+            return Err(EchoRoundError::InconsistentMerkleRoot.into());
+        }
+
+        let previously_received_echo = echo_round.echo_broadcasts.get(sender).ok_or_else(|| {
+            ReceiveError::unprovable_with_kind(
+                RemoteErrorKind::InvalidEchoRoundMessage,
+                format!("The revealed leaf is for an unexpected sender {sender:?}"),
+            )
+        })?;
+
+        check_echoed_hash::<P, SP>(sender, &revealed.hash, previously_received_echo, || {
+            Ok(EchoRoundError::InvalidMerkleLeaf {
+                sender: sender.clone(),
+                leaf: revealed.hash.clone(),
+                proof: revealed.proof.clone(),
+            })
+        })?;
+
+        // The revealed leaf is the same as ours, so `from` must have sent inconsistent nodes during the search.
+        Err(ReceiveError::unprovable_with_kind(
+            RemoteErrorKind::InvalidEchoRoundMessage,
+            "The revealed leaf is the same as ours",
+        ))
+    }
+}
+
+impl<P, SP> Round<SP::Verifier> for MerkleResolutionRound<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    type Protocol = P;
+
+    fn transition_info(&self) -> TransitionInfo {
+        // See the comment in `EchoRound::transition_info()`
+        self.echo_round
+            .main_round
+            .transition_info()
+            .merkle_resolution(self.step)
+            .expect("the main round is not an echo round, and the number of steps is limited by the tree height")
+    }
+
+    fn communication_info(&self) -> CommunicationInfo<SP::Verifier> {
+        self.communication_info.clone()
+    }
+
+    fn make_direct_message(
+        &self,
+        _rng: &mut dyn CryptoRngCore,
+        format: &BoxedFormat,
+        destination: &SP::Verifier,
+    ) -> Result<(DirectMessage, Option<Artifact>), LocalError> {
+        debug!(
+            "{:?}: making a Merkle echo resolution message (step {}) for {:?}",
+            self.echo_round.verifier, self.step, destination
+        );
+
+        let bisection = self.bisection(destination)?;
+        let message = if self.step < bisection.reveal_step {
+            MerkleResolutionMessage::Bisection {
+                committed: self
+                    .tree
+                    .children(bisection.ours)
+                    .into_iter()
+                    .map(|(_position, node)| node)
+                    .collect(),
+                expected: bisection
+                    .their_tree
+                    .children(bisection.theirs)
+                    .into_iter()
+                    .map(|(_position, node)| node)
+                    .collect(),
+            }
+        } else {
+            MerkleResolutionMessage::Reveal(self.reveal(bisection.ours)?)
+        };
+
+        Ok((DirectMessage::new(format, message)?, None))
+    }
+
+    fn receive_message(
+        &self,
+        format: &BoxedFormat,
+        from: &SP::Verifier,
+        message: ProtocolMessage,
+    ) -> Result<Payload, ReceiveError<SP::Verifier, Self::Protocol>> {
+        debug!(
+            "{:?}: received a Merkle echo resolution message (step {}) from {:?}",
+            self.echo_round.verifier, self.step, from
+        );
+
+        message.echo_broadcast.assert_is_none()?;
+        message.normal_broadcast.assert_is_none()?;
+
+        let message = message
+            .direct_message
+            .deserialize::<MerkleResolutionMessage<SP::Verifier>>(format)?;
+        let bisection = self.bisection(from)?;
+
+        match message {
+            MerkleResolutionMessage::Bisection { committed, expected } if self.step < bisection.reveal_step => {
+                self.receive_bisection(bisection, committed, expected)
+            }
+            MerkleResolutionMessage::Reveal(revealed) if self.step == bisection.reveal_step => {
+                self.receive_reveal(bisection, revealed)
+            }
+            _ => Err(ReceiveError::unprovable_with_kind(
+                RemoteErrorKind::InvalidEchoRoundMessage,
+                "Unexpected kind of the Merkle echo resolution message for this step",
+            )),
+        }
+    }
+
+    fn finalize(
+        self: Box<Self>,
+        rng: &mut dyn CryptoRngCore,
+        payloads: BTreeMap<SP::Verifier, Payload>,
+        _artifacts: BTreeMap<SP::Verifier, Artifact>,
+    ) -> Result<FinalizeOutcome<SP::Verifier, Self::Protocol>, LocalError> {
+        let Self {
+            echo_round,
+            step,
+            tree,
+            senders,
+            mut bisections,
+            ..
+        } = *self;
+
+        for (id, payload) in payloads {
+            let BisectionStep { ours, theirs } = payload.downcast::<BisectionStep>()?;
+            let bisection = bisections
+                .get_mut(&id)
+                .ok_or_else(|| LocalError::new(format!("No Merkle echo resolution with {id:?} is in progress")))?;
+            if let Some((position, node)) = ours {
+                bisection.ours = position;
+                bisection.their_version_of_ours = Some(node);
+            }
+            if let Some((position, node)) = theirs {
+                bisection.theirs = position;
+                bisection.their_node = node;
+            }
+        }
+
+        // Revealing the leaves always results in an error, so the search can only end here
+        // if there was no one left to search with, which should not happen.
+        let next_step = step
+            .checked_add(1)
+            .ok_or_else(|| LocalError::new("Too many Merkle echo resolution steps"))?;
+        bisections.retain(|_id, bisection| bisection.reveal_step >= next_step);
+        if bisections.is_empty() {
+            return echo_round
+                .main_round
+                .finalize(rng, echo_round.payloads, echo_round.artifacts);
+        }
+
+        Ok(FinalizeOutcome::AnotherRound(BoxedRound::new_dynamic(Self::new_step(
+            echo_round, next_step, tree, senders, bisections,
+        ))))
+    }
+}
@@ -7,14 +7,15 @@ use serde_encoded_bytes::{Hex, SliceLike};
 use signature::{DigestVerifier, RandomizedDigestSigner};
 
 use super::{
+    party_encoding::PartyRef,
     session::{SessionId, SessionParameters},
     wire_format::WireFormat,
-    LocalError,
+    LocalError, LocalErrorKind,
 };
 use crate::protocol::{DirectMessage, EchoBroadcast, NormalBroadcast, ProtocolMessagePartHashable, RoundId};
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-struct SerializedSignature(#[serde(with = "SliceLike::<Hex>")] Box<[u8]>);
+pub(crate) struct SerializedSignature(#[serde(with = "SliceLike::<Hex>")] Box<[u8]>);
 
 impl SerializedSignature {
     pub fn new<SP>(signature: SP::Signature) -> Result<Self, LocalError>
@@ -111,10 +112,110 @@ impl<M: ProtocolMessagePartHashable> MessageWithMetadata<M> {
     }
 }
 
+/// A compact record of a received message, identifying it by the signed contents of its parts
+/// (that is, regardless of the particular signatures).
+///
+/// Used to tell redeliveries of a message from conflicting messages for the same round.
+#[derive(Debug, Clone)]
+pub(crate) struct MessageFingerprint {
+    contents_digest: Box<[u8]>,
+    // Kept to prove the equivocation if the echo broadcast of a conflicting message is different.
+    echo_broadcast: SignedMessageHash,
+}
+
+impl MessageFingerprint {
+    fn new<SP>(
+        direct_message: &MessageWithMetadata<DirectMessage>,
+        echo_broadcast: &MessageWithMetadata<EchoBroadcast>,
+        echo_broadcast_signature: &SerializedSignature,
+        normal_broadcast: &MessageWithMetadata<NormalBroadcast>,
+        echo_round_message: Option<&MessageWithMetadata<NormalBroadcast>>,
+    ) -> Result<Self, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        let echo_broadcast_hash = echo_broadcast.message.hash::<SP::Digest>();
+
+        // All the part digests have the same length, so the optional one at the end cannot cause ambiguity.
+        let mut digest = SP::Digest::new_with_prefix(b"MessageFingerprint")
+            .chain_update(direct_message.digest::<SP>()?.finalize())
+            .chain_update(message_digest::<SP>(&echo_broadcast.metadata, &echo_broadcast_hash)?.finalize())
+            .chain_update(normal_broadcast.digest::<SP>()?.finalize());
+        if let Some(echo_round_message) = echo_round_message {
+            digest = digest.chain_update(echo_round_message.digest::<SP>()?.finalize());
+        }
+
+        Ok(Self {
+            contents_digest: digest.finalize().as_slice().into(),
+            echo_broadcast: SignedMessageHash {
+                signature: echo_broadcast_signature.clone(),
+                metadata: echo_broadcast.metadata.clone(),
+                message_part_hash: echo_broadcast_hash.as_ref().into(),
+            },
+        })
+    }
+
+    /// Returns `true` if both fingerprints belong to messages with the same signed contents.
+    pub fn is_same_message(&self, other: &Self) -> bool {
+        self.contents_digest == other.contents_digest
+    }
+
+    /// Returns the signed hash of the echo broadcast of the message.
+    pub fn echo_broadcast(&self) -> &SignedMessageHash {
+        &self.echo_broadcast
+    }
+}
+
+/// A message part with its metadata, yet to be signed.
+#[derive(Debug, Clone)]
+pub(crate) struct UnsignedMessagePart<M>(MessageWithMetadata<M>);
+
+impl<M> UnsignedMessagePart<M>
+where
+    M: ProtocolMessagePartHashable,
+{
+    pub fn new(session_id: &SessionId, round_id: &RoundId, message: M) -> Self {
+        let metadata = MessageMetadata::new(session_id, round_id);
+        Self(MessageWithMetadata { metadata, message })
+    }
+
+    pub fn digest<SP>(&self) -> Result<SP::Digest, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        self.0.digest::<SP>()
+    }
+
+    pub fn sign<SP>(
+        self,
+        mut rng: &mut dyn CryptoRngCore,
+        signer: &SP::Signer,
+    ) -> Result<SignedMessagePart<M>, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        let signature = signer
+            .try_sign_digest_with_rng(&mut rng, self.digest::<SP>()?)
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Signing, format!("Failed to sign: {:?}", err)))?;
+        self.with_signature::<SP>(signature)
+    }
+
+    pub fn with_signature<SP>(self, signature: SP::Signature) -> Result<SignedMessagePart<M>, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        Ok(SignedMessagePart {
+            signature: SerializedSignature::new::<SP>(signature)?,
+            message_with_metadata: self.0,
+        })
+    }
+}
+
 impl<M> SignedMessagePart<M>
 where
     M: ProtocolMessagePartHashable,
 {
+    #[cfg(any(test, feature = "dev"))]
     pub fn new<SP>(
         rng: &mut impl CryptoRngCore,
         signer: &SP::Signer,
@@ -125,16 +226,7 @@ where
     where
         SP: SessionParameters,
     {
-        let metadata = MessageMetadata::new(session_id, round_id);
-        let message_with_metadata = MessageWithMetadata { metadata, message };
-        let digest = message_with_metadata.digest::<SP>()?;
-        let signature = signer
-            .try_sign_digest_with_rng(rng, digest)
-            .map_err(|err| LocalError::new(format!("Failed to sign: {:?}", err)))?;
-        Ok(Self {
-            signature: SerializedSignature::new::<SP>(signature)?,
-            message_with_metadata,
-        })
+        UnsignedMessagePart::new(session_id, round_id, message).sign::<SP>(rng, signer)
     }
 
     pub(crate) fn to_signed_hash<SP>(&self) -> SignedMessageHash
@@ -157,6 +249,30 @@ where
         &self.message_with_metadata.message
     }
 
+    /// Returns the size of the serialized payload and the signature in bytes.
+    pub(crate) fn size(&self) -> usize {
+        self.signature.0.len() + self.message_with_metadata.message.payload_size()
+    }
+
+    /// Signs the same payload and metadata again with the given signer.
+    #[cfg(any(test, feature = "dev"))]
+    pub(crate) fn resign<SP>(self, rng: &mut impl CryptoRngCore, signer: &SP::Signer) -> Result<Self, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        let MessageWithMetadata { metadata, message } = self.message_with_metadata;
+        Self::new::<SP>(rng, signer, &metadata.session_id, &metadata.round_id, message)
+    }
+
+    /// Replaces the signature with bytes that cannot be deserialized.
+    #[cfg(any(test, feature = "dev"))]
+    pub(crate) fn with_invalid_signature(self) -> Self {
+        Self {
+            signature: SerializedSignature(Box::new([])),
+            message_with_metadata: self.message_with_metadata,
+        }
+    }
+
     pub(crate) fn verify<SP>(self, verifier: &SP::Verifier) -> Result<VerifiedMessagePart<M>, MessageVerificationError>
     where
         SP: SessionParameters,
@@ -201,44 +317,108 @@ impl<M> VerifiedMessagePart<M> {
 /// A signed message destined for another node.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Message<Verifier> {
-    destination: Verifier,
+    destination: PartyRef<Verifier>,
     direct_message: SignedMessagePart<DirectMessage>,
     echo_broadcast: SignedMessagePart<EchoBroadcast>,
     normal_broadcast: SignedMessagePart<NormalBroadcast>,
+    // The message of the previous round's echo round, if it is carried out along with this round.
+    echo_round_message: Option<SignedMessagePart<NormalBroadcast>>,
 }
 
-impl<Verifier> Message<Verifier>
-where
-    Verifier: Clone,
-{
-    #[allow(clippy::too_many_arguments)]
-    pub(crate) fn new<SP>(
-        rng: &mut impl CryptoRngCore,
-        signer: &SP::Signer,
+/// A [`Message`] with the direct message part yet to be signed.
+#[derive(Debug, Clone)]
+pub(crate) struct UnsignedMessage<Verifier> {
+    destination: PartyRef<Verifier>,
+    direct_message: UnsignedMessagePart<DirectMessage>,
+    echo_broadcast: SignedMessagePart<EchoBroadcast>,
+    normal_broadcast: SignedMessagePart<NormalBroadcast>,
+    echo_round_message: Option<SignedMessagePart<NormalBroadcast>>,
+}
+
+impl<Verifier> UnsignedMessage<Verifier> {
+    pub fn new(
         session_id: &SessionId,
         round_id: &RoundId,
-        destination: &Verifier,
+        destination: PartyRef<Verifier>,
         direct_message: DirectMessage,
         echo_broadcast: SignedMessagePart<EchoBroadcast>,
         normal_broadcast: SignedMessagePart<NormalBroadcast>,
-    ) -> Result<Self, LocalError>
+        echo_round_message: Option<SignedMessagePart<NormalBroadcast>>,
+    ) -> Self {
+        Self {
+            destination,
+            direct_message: UnsignedMessagePart::new(session_id, round_id, direct_message),
+            echo_broadcast,
+            normal_broadcast,
+            echo_round_message,
+        }
+    }
+
+    pub fn digest<SP>(&self) -> Result<SP::Digest, LocalError>
     where
         SP: SessionParameters,
     {
-        let direct_message = SignedMessagePart::new::<SP>(rng, signer, session_id, round_id, direct_message)?;
-        Ok(Self {
-            destination: destination.clone(),
+        self.direct_message.digest::<SP>()
+    }
+
+    pub fn sign<SP>(self, rng: &mut dyn CryptoRngCore, signer: &SP::Signer) -> Result<Message<Verifier>, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        let direct_message = self.direct_message.sign::<SP>(rng, signer)?;
+        Ok(Message {
+            destination: self.destination,
             direct_message,
-            echo_broadcast,
-            normal_broadcast,
+            echo_broadcast: self.echo_broadcast,
+            normal_broadcast: self.normal_broadcast,
+            echo_round_message: self.echo_round_message,
         })
     }
 
-    /// The verifier of the party this message is intended for.
-    pub fn destination(&self) -> &Verifier {
+    pub fn with_signature<SP>(self, signature: SP::Signature) -> Result<Message<Verifier>, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        let direct_message = self.direct_message.with_signature::<SP>(signature)?;
+        Ok(Message {
+            destination: self.destination,
+            direct_message,
+            echo_broadcast: self.echo_broadcast,
+            normal_broadcast: self.normal_broadcast,
+            echo_round_message: self.echo_round_message,
+        })
+    }
+}
+
+impl<Verifier> Message<Verifier>
+where
+    Verifier: Clone,
+{
+    /// The reference to the party this message is intended for,
+    /// encoded according to the [`PartyEncoding`](`super::PartyEncoding`) of the session.
+    pub fn destination(&self) -> &PartyRef<Verifier> {
         &self.destination
     }
 
+    /// The ID of the round this message claims to belong to.
+    ///
+    /// Note that the parts of a message received from the network can have different metadata;
+    /// the round ID here is the one attached to the direct message part.
+    pub fn round_id(&self) -> &RoundId {
+        self.direct_message.metadata().round_id()
+    }
+
+    /// Returns the sizes of the signed parts of the message in bytes.
+    pub(crate) fn part_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        [
+            self.direct_message.size(),
+            self.echo_broadcast.size(),
+            self.normal_broadcast.size(),
+        ]
+        .into_iter()
+        .chain(self.echo_round_message.as_ref().map(|message| message.size()))
+    }
+
     pub(crate) fn unify_metadata(self) -> Option<CheckedMessage> {
         if self.echo_broadcast.metadata() != self.direct_message.metadata() {
             return None;
@@ -248,16 +428,110 @@ where
             return None;
         }
 
+        // The attached echo round message belongs to another round, but it must be from the same session.
+        if let Some(echo_round_message) = &self.echo_round_message {
+            if echo_round_message.metadata().session_id() != self.direct_message.metadata().session_id() {
+                return None;
+            }
+        }
+
         let metadata = self.direct_message.message_with_metadata.metadata.clone();
         Some(CheckedMessage {
             metadata,
             direct_message: self.direct_message,
             echo_broadcast: self.echo_broadcast,
             normal_broadcast: self.normal_broadcast,
+            echo_round_message: self.echo_round_message,
         })
     }
 }
 
+/// Tampering with messages, for testing how sessions handle misbehaving transports and senders.
+#[cfg(any(test, feature = "dev"))]
+impl<Verifier> Message<Verifier> {
+    /// Replaces the signed echo broadcast part with the one from `other`.
+    pub fn with_echo_broadcast_of(self, other: &Self) -> Self {
+        Self {
+            echo_broadcast: other.echo_broadcast.clone(),
+            ..self
+        }
+    }
+
+    /// Replaces the signed normal broadcast part with the one from `other`.
+    pub fn with_normal_broadcast_of(self, other: &Self) -> Self {
+        Self {
+            normal_broadcast: other.normal_broadcast.clone(),
+            ..self
+        }
+    }
+
+    /// Replaces the signed direct message part with the one from `other`.
+    pub fn with_direct_message_of(self, other: &Self) -> Self {
+        Self {
+            direct_message: other.direct_message.clone(),
+            ..self
+        }
+    }
+
+    /// Signs all the parts of the message again with the given signer,
+    /// keeping their payloads and metadata intact.
+    pub fn resigned<SP>(self, rng: &mut impl CryptoRngCore, signer: &SP::Signer) -> Result<Self, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        Ok(Self {
+            destination: self.destination,
+            direct_message: self.direct_message.resign::<SP>(rng, signer)?,
+            echo_broadcast: self.echo_broadcast.resign::<SP>(rng, signer)?,
+            normal_broadcast: self.normal_broadcast.resign::<SP>(rng, signer)?,
+            echo_round_message: self
+                .echo_round_message
+                .map(|message| message.resign::<SP>(rng, signer))
+                .transpose()?,
+        })
+    }
+
+    /// Replaces the echo broadcast with the one containing `payload` (serialized with `SP::WireFormat`),
+    /// signed by `signer` with the same metadata.
+    ///
+    /// Allows one to simulate a sender sending different echo broadcasts to different nodes.
+    pub fn with_echo_broadcast_payload<SP, T>(
+        self,
+        rng: &mut impl CryptoRngCore,
+        signer: &SP::Signer,
+        payload: T,
+    ) -> Result<Self, LocalError>
+    where
+        SP: SessionParameters,
+        T: 'static + Serialize,
+    {
+        let metadata = self.echo_broadcast.metadata().clone();
+        let format = crate::protocol::BoxedFormat::new::<SP::WireFormat>();
+        let echo_broadcast = <EchoBroadcast as crate::protocol::ProtocolMessagePart>::new(&format, payload)?;
+        Ok(Self {
+            echo_broadcast: SignedMessagePart::new::<SP>(
+                rng,
+                signer,
+                metadata.session_id(),
+                metadata.round_id(),
+                echo_broadcast,
+            )?,
+            ..self
+        })
+    }
+
+    /// Replaces the signatures of all the parts of the message with ones that cannot be deserialized.
+    pub fn with_invalid_signatures(self) -> Self {
+        Self {
+            destination: self.destination,
+            direct_message: self.direct_message.with_invalid_signature(),
+            echo_broadcast: self.echo_broadcast.with_invalid_signature(),
+            normal_broadcast: self.normal_broadcast.with_invalid_signature(),
+            echo_round_message: self.echo_round_message.map(|message| message.with_invalid_signature()),
+        }
+    }
+}
+
 /// A `CheckedMessage` is like a [`Message`] but where we have checked that the metadata
 /// (i.e. SessionId and RoundId) from the Echo message (if any) matches with that of the
 /// [`DirectMessage`].
@@ -268,6 +542,7 @@ pub(crate) struct CheckedMessage {
     direct_message: SignedMessagePart<DirectMessage>,
     echo_broadcast: SignedMessagePart<EchoBroadcast>,
     normal_broadcast: SignedMessagePart<NormalBroadcast>,
+    echo_round_message: Option<SignedMessagePart<NormalBroadcast>>,
 }
 
 impl CheckedMessage {
@@ -275,6 +550,21 @@ impl CheckedMessage {
         &self.metadata
     }
 
+    pub fn fingerprint<SP>(&self) -> Result<MessageFingerprint, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        MessageFingerprint::new::<SP>(
+            &self.direct_message.message_with_metadata,
+            &self.echo_broadcast.message_with_metadata,
+            &self.echo_broadcast.signature,
+            &self.normal_broadcast.message_with_metadata,
+            self.echo_round_message
+                .as_ref()
+                .map(|message| &message.message_with_metadata),
+        )
+    }
+
     pub fn verify<SP>(self, verifier: &SP::Verifier) -> Result<VerifiedMessage<SP::Verifier>, MessageVerificationError>
     where
         SP: SessionParameters,
@@ -282,6 +572,10 @@ impl CheckedMessage {
         let direct_message = self.direct_message.verify::<SP>(verifier)?;
         let echo_broadcast = self.echo_broadcast.verify::<SP>(verifier)?;
         let normal_broadcast = self.normal_broadcast.verify::<SP>(verifier)?;
+        let echo_round_message = self
+            .echo_round_message
+            .map(|message| message.verify::<SP>(verifier))
+            .transpose()?;
 
         Ok(VerifiedMessage {
             from: verifier.clone(),
@@ -289,6 +583,7 @@ impl CheckedMessage {
             direct_message,
             echo_broadcast,
             normal_broadcast,
+            echo_round_message,
         })
     }
 }
@@ -305,6 +600,7 @@ pub struct VerifiedMessage<Verifier> {
     direct_message: VerifiedMessagePart<DirectMessage>,
     echo_broadcast: VerifiedMessagePart<EchoBroadcast>,
     normal_broadcast: VerifiedMessagePart<NormalBroadcast>,
+    echo_round_message: Option<VerifiedMessagePart<NormalBroadcast>>,
 }
 
 impl<Verifier> VerifiedMessage<Verifier> {
@@ -328,6 +624,30 @@ impl<Verifier> VerifiedMessage<Verifier> {
         self.normal_broadcast.payload()
     }
 
+    pub(crate) fn echo_round_message(&self) -> Option<&VerifiedMessagePart<NormalBroadcast>> {
+        self.echo_round_message.as_ref()
+    }
+
+    pub(crate) fn fingerprint<SP>(&self) -> Result<MessageFingerprint, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        MessageFingerprint::new::<SP>(
+            &self.direct_message.message_with_metadata,
+            &self.echo_broadcast.message_with_metadata,
+            &self.echo_broadcast.signature,
+            &self.normal_broadcast.message_with_metadata,
+            self.echo_round_message
+                .as_ref()
+                .map(|message| &message.message_with_metadata),
+        )
+    }
+
+    /// Removes the attached echo round message, returning it.
+    pub(crate) fn take_echo_round_message(&mut self) -> Option<SignedMessagePart<NormalBroadcast>> {
+        self.echo_round_message.take().map(|message| message.into_unverified())
+    }
+
     /// Split the `VerifiedMessage` into its signed constituent parts:
     /// the echo broadcast and the direct message.
     pub(crate) fn into_parts(
@@ -357,6 +677,14 @@ impl SignedMessageHash {
         &self.metadata
     }
 
+    /// Returns the digest of the signed contents (that is, not including the signature).
+    pub(crate) fn content_digest<SP>(&self) -> Result<digest::Output<SP::Digest>, LocalError>
+    where
+        SP: SessionParameters,
+    {
+        Ok(message_digest::<SP>(&self.metadata, &self.message_part_hash)?.finalize())
+    }
+
     pub(crate) fn verify<SP>(self, verifier: &SP::Verifier) -> Result<VerifiedMessageHash, MessageVerificationError>
     where
         SP: SessionParameters,
@@ -0,0 +1,170 @@
+use alloc::{boxed::Box, collections::BTreeSet, format, string::String, vec::Vec};
+
+use digest::Digest;
+use serde::{Deserialize, Serialize};
+use serde_encoded_bytes::{Hex, SliceLike};
+
+use super::{session::SessionParameters, wire_format::WireFormat, LocalError};
+use crate::protocol::PartyId;
+
+/// A reference to a party in serialized messages and evidence.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum PartyRef<Id> {
+    /// The full party ID.
+    Id(Id),
+    /// The position of the party in the sorted list of the session parties.
+    Index(u16),
+}
+
+impl<Id> PartyRef<Id> {
+    /// Returns the full party ID if the reference contains it.
+    pub fn as_id(&self) -> Option<&Id> {
+        match self {
+            Self::Id(id) => Some(id),
+            Self::Index(_) => None,
+        }
+    }
+}
+
+/// The way the parties are referred to in serialized messages and evidence.
+///
+/// By default the full IDs are serialized. If the party IDs are large (e.g. public keys),
+/// and there are many parties, it may be more efficient to fix the set of parties for the session
+/// (it must be the same for all the nodes) and refer to them by their index in it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Option<Vec<Id>>", bound(deserialize = "Id: PartyId"))]
+pub struct PartyEncoding<Id: PartyId>(Option<Vec<Id>>);
+
+impl<Id: PartyId> TryFrom<Option<Vec<Id>>> for PartyEncoding<Id> {
+    type Error = String;
+
+    fn try_from(parties: Option<Vec<Id>>) -> Result<Self, Self::Error> {
+        if let Some(parties) = &parties {
+            if parties.len() > usize::from(u16::MAX) + 1 {
+                return Err(format!("Too many parties: {}", parties.len()));
+            }
+            if parties
+                .iter()
+                .zip(parties.iter().skip(1))
+                .any(|(prev, next)| prev >= next)
+            {
+                return Err("The party list must be sorted and contain no duplicates".into());
+            }
+        }
+        Ok(Self(parties))
+    }
+}
+
+impl<Id: PartyId> Default for PartyEncoding<Id> {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+impl<Id: PartyId> PartyEncoding<Id> {
+    /// Parties are referred to by their full IDs.
+    pub fn full() -> Self {
+        Self(None)
+    }
+
+    /// Parties are referred to by their index in the sorted list of `parties`.
+    ///
+    /// Any party not in the list is referred to by its full ID.
+    pub fn indexed(parties: BTreeSet<Id>) -> Result<Self, LocalError> {
+        Self::try_from(Some(parties.into_iter().collect::<Vec<_>>())).map_err(LocalError::new)
+    }
+
+    /// Returns the list of parties the indices refer to, or `None` if the full IDs are used.
+    pub fn parties(&self) -> Option<&[Id]> {
+        self.0.as_deref()
+    }
+
+    /// Returns the reference to `id` under this encoding.
+    pub fn encode(&self, id: &Id) -> PartyRef<Id> {
+        self.0
+            .as_ref()
+            .and_then(|parties| parties.binary_search(id).ok())
+            .and_then(|index| u16::try_from(index).ok())
+            .map_or_else(|| PartyRef::Id(id.clone()), PartyRef::Index)
+    }
+
+    /// Returns the party ID the reference points to.
+    ///
+    /// Returns `None` if the reference could not have been produced by [`encode`](`Self::encode`)
+    /// (that is, it is an out of range index, or a full ID of a party that has an index).
+    pub fn decode<'a>(&'a self, party: &'a PartyRef<Id>) -> Option<&'a Id> {
+        match (party, &self.0) {
+            (PartyRef::Id(id), None) => Some(id),
+            (PartyRef::Id(id), Some(parties)) => parties.binary_search(id).is_err().then_some(id),
+            (PartyRef::Index(index), Some(parties)) => parties.get(usize::from(*index)),
+            (PartyRef::Index(_), None) => None,
+        }
+    }
+
+    /// Returns the digest committing to the party list, if the encoding uses one.
+    pub(crate) fn digest<SP>(&self) -> Result<Option<PartiesDigest>, LocalError>
+    where
+        SP: SessionParameters<Verifier = Id>,
+    {
+        self.0
+            .as_ref()
+            .map(|parties| {
+                Ok(PartiesDigest(
+                    SP::Digest::new_with_prefix(b"PartyEncoding")
+                        .chain_update(SP::WireFormat::serialize(parties)?)
+                        .finalize()
+                        .as_ref()
+                        .into(),
+                ))
+            })
+            .transpose()
+    }
+}
+
+/// A commitment to the party list used to encode the party references in a signed payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct PartiesDigest(#[serde(with = "SliceLike::<Hex>")] Box<[u8]>);
+
+#[cfg(test)]
+mod tests {
+    use alloc::{collections::BTreeSet, vec};
+
+    use super::{PartyEncoding, PartyRef};
+    use crate::{
+        dev::{BinaryFormat, TestVerifier},
+        session::WireFormat,
+    };
+
+    #[test]
+    fn round_trip() {
+        let parties = [1, 5, 9].map(TestVerifier::new).into_iter().collect::<BTreeSet<_>>();
+        let encoding = PartyEncoding::indexed(parties.clone()).unwrap();
+
+        for party in parties.iter() {
+            let party_ref = encoding.encode(party);
+            assert!(matches!(party_ref, PartyRef::Index(_)));
+            assert_eq!(encoding.decode(&party_ref), Some(party));
+        }
+
+        // Outsiders are referred to by the full ID
+        let outsider = TestVerifier::new(3);
+        assert_eq!(encoding.encode(&outsider), PartyRef::Id(outsider));
+        assert_eq!(encoding.decode(&PartyRef::Id(outsider)), Some(&outsider));
+
+        // Non-canonical references are rejected
+        assert_eq!(encoding.decode(&PartyRef::Id(TestVerifier::new(5))), None);
+        assert_eq!(encoding.decode(&PartyRef::Index(3)), None);
+        assert_eq!(PartyEncoding::full().decode(&PartyRef::<TestVerifier>::Index(0)), None);
+    }
+
+    #[test]
+    fn deserialization_checks_order() {
+        let unsorted = Some(vec![TestVerifier::new(2), TestVerifier::new(1)]);
+        let serialized = BinaryFormat::serialize(unsorted).unwrap();
+        assert!(BinaryFormat::deserialize::<PartyEncoding<TestVerifier>>(&serialized).is_err());
+
+        let sorted = Some(vec![TestVerifier::new(1), TestVerifier::new(2)]);
+        let serialized = BinaryFormat::serialize(sorted).unwrap();
+        assert!(BinaryFormat::deserialize::<PartyEncoding<TestVerifier>>(&serialized).is_ok());
+    }
+}
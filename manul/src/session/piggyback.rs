@@ -0,0 +1,156 @@
+use alloc::{boxed::Box, collections::BTreeMap, format};
+
+use rand_core::CryptoRngCore;
+
+use super::{
+    echo::EchoState,
+    message::{SignedMessagePart, VerifiedMessagePart},
+    session::SessionParameters,
+    LocalError,
+};
+use crate::protocol::{
+    Artifact, BoxedFormat, BoxedRound, CommunicationInfo, FinalizeOutcome, NormalBroadcast, PartyId, Payload, Protocol,
+    ProtocolMessage, ReceiveError, RemoteErrorKind, Round, RoundId, TransitionInfo,
+};
+
+/// The echo round of the previous round, carried out along with the current one
+/// by attaching the echo round messages to the messages of the current round.
+#[derive_where::derive_where(Debug)]
+pub(crate) struct PiggybackedEcho<SP: SessionParameters> {
+    echo: EchoState<SP>,
+    message: SignedMessagePart<NormalBroadcast>,
+}
+
+impl<SP> PiggybackedEcho<SP>
+where
+    SP: SessionParameters,
+{
+    /// Returns `true` if the echo round messages can be attached to the messages of a round
+    /// with the given communication info.
+    pub fn fits(echo: &EchoState<SP>, communication_info: &CommunicationInfo<SP::Verifier>) -> bool {
+        let echo_round_info = echo.echo_round_info();
+        echo_round_info
+            .message_destinations
+            .is_subset(&communication_info.message_destinations)
+            && echo_round_info
+                .expecting_messages_from
+                .is_subset(&communication_info.expecting_messages_from)
+    }
+
+    /// `message` is our own signed echo round message.
+    pub fn new(echo: EchoState<SP>, message: SignedMessagePart<NormalBroadcast>) -> Self {
+        Self { echo, message }
+    }
+
+    /// The ID of the echo round.
+    pub fn round_id(&self) -> &RoundId {
+        self.message.metadata().round_id()
+    }
+
+    /// Returns the echo round message to attach to the message for `destination`, if any.
+    pub fn message_for(&self, destination: &SP::Verifier) -> Option<SignedMessagePart<NormalBroadcast>> {
+        self.echo
+            .echo_round_info()
+            .message_destinations
+            .contains(destination)
+            .then(|| self.message.clone())
+    }
+
+    /// Checks the echo round message attached to the message from `from`.
+    pub fn receive<P: Protocol<SP::Verifier>>(
+        &self,
+        format: &BoxedFormat,
+        from: &SP::Verifier,
+        message: Option<&VerifiedMessagePart<NormalBroadcast>>,
+    ) -> Result<(), ReceiveError<SP::Verifier, P>> {
+        let expecting = self.echo.echo_round_info().expecting_messages_from.contains(from);
+        let message = match (message, expecting) {
+            (Some(message), true) => message,
+            (None, false) => return Ok(()),
+            (None, true) => {
+                return Err(ReceiveError::unprovable_with_kind(
+                    RemoteErrorKind::InvalidAttachedEchoRoundMessage,
+                    "Missing the attached echo round message",
+                ))
+            }
+            (Some(_), false) => {
+                return Err(ReceiveError::unprovable_with_kind(
+                    RemoteErrorKind::InvalidAttachedEchoRoundMessage,
+                    "Unexpected attached echo round message",
+                ))
+            }
+        };
+        if message.metadata().round_id() != self.round_id() {
+            return Err(ReceiveError::unprovable_with_kind(
+                RemoteErrorKind::InvalidAttachedEchoRoundMessage,
+                format!(
+                    "The attached echo round message has an incorrect round ID: {}",
+                    message.metadata().round_id()
+                ),
+            ));
+        }
+        self.echo.receive_normal_broadcast(format, from, message.payload())
+    }
+}
+
+/// A stand-in for an already finalized round, used to run a regular echo round
+/// when the echo round messages could not be attached to the messages of the next round.
+#[derive_where::derive_where(Debug)]
+pub(crate) struct FinalizedRound<Id: PartyId, P: Protocol<Id>> {
+    transition_info: TransitionInfo,
+    communication_info: CommunicationInfo<Id>,
+    next_round: BoxedRound<Id, P>,
+}
+
+impl<Id, P> FinalizedRound<Id, P>
+where
+    Id: PartyId,
+    P: Protocol<Id>,
+{
+    /// `transition_info` and `communication_info` are those of the finalized round.
+    pub fn new(
+        transition_info: TransitionInfo,
+        communication_info: CommunicationInfo<Id>,
+        next_round: BoxedRound<Id, P>,
+    ) -> Self {
+        Self {
+            transition_info,
+            communication_info,
+            next_round,
+        }
+    }
+}
+
+impl<Id, P> Round<Id> for FinalizedRound<Id, P>
+where
+    Id: PartyId,
+    P: Protocol<Id>,
+{
+    type Protocol = P;
+
+    fn transition_info(&self) -> TransitionInfo {
+        self.transition_info.clone()
+    }
+
+    fn communication_info(&self) -> CommunicationInfo<Id> {
+        self.communication_info.clone()
+    }
+
+    fn receive_message(
+        &self,
+        _format: &BoxedFormat,
+        _from: &Id,
+        _message: ProtocolMessage,
+    ) -> Result<Payload, ReceiveError<Id, Self::Protocol>> {
+        Err(LocalError::new("The round has already been finalized").into())
+    }
+
+    fn finalize(
+        self: Box<Self>,
+        _rng: &mut dyn CryptoRngCore,
+        _payloads: BTreeMap<Id, Payload>,
+        _artifacts: BTreeMap<Id, Artifact>,
+    ) -> Result<FinalizeOutcome<Id, Self::Protocol>, LocalError> {
+        Ok(FinalizeOutcome::AnotherRound(self.next_round))
+    }
+}
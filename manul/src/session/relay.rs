@@ -0,0 +1,391 @@
+//! Support for a store-and-forward topology, where the nodes exchange messages through an untrusted coordinator.
+
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec,
+    vec::Vec,
+};
+
+use digest::Digest;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use signature::{DigestVerifier, Keypair, RandomizedDigestSigner};
+
+use super::{
+    message::{Message, MessageVerificationError, SerializedSignature},
+    session::{AsyncSessionParameters, SessionId, SessionParameters},
+    wire_format::WireFormat,
+    LocalError, LocalErrorKind,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EnvelopeContents<Verifier> {
+    session_id: SessionId,
+    from: Verifier,
+    to: Verifier,
+    message: Message<Verifier>,
+}
+
+impl<Verifier> EnvelopeContents<Verifier>
+where
+    Verifier: Serialize,
+{
+    fn digest<SP>(&self) -> Result<SP::Digest, LocalError>
+    where
+        SP: SessionParameters<Verifier = Verifier>,
+    {
+        Ok(SP::Digest::new_with_prefix(b"RelayEnvelopeDigest").chain_update(SP::WireFormat::serialize(self)?))
+    }
+}
+
+/// A [`Message`] wrapped for delivery through a [`Relay`].
+///
+/// The envelope is signed by the sender and binds the message to the session, the sender, and the recipient,
+/// so the relay cannot forge messages, redirect them to another node, or mix parts of different messages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelayEnvelope<Verifier> {
+    contents: EnvelopeContents<Verifier>,
+    signature: SerializedSignature,
+}
+
+impl<Verifier> RelayEnvelope<Verifier> {
+    /// Returns the ID of the session the message belongs to.
+    pub fn session_id(&self) -> &SessionId {
+        &self.contents.session_id
+    }
+
+    /// Returns the verifying key of the party that sent the message.
+    ///
+    /// Note that this is only authenticated after [`RelayClient::open`] succeeds.
+    pub fn from(&self) -> &Verifier {
+        &self.contents.from
+    }
+
+    /// Returns the verifying key of the party the message is intended for.
+    pub fn to(&self) -> &Verifier {
+        &self.contents.to
+    }
+}
+
+/// An error that can occur when opening a [`RelayEnvelope`].
+#[derive(Debug, Clone)]
+pub enum RelayError {
+    /// Indicates a runtime problem or a bug in the code.
+    Local(LocalError),
+    /// The envelope was not sealed by its declared sender for this node,
+    /// or it was already delivered before.
+    ///
+    /// Since the envelope is signed by the sender, this is the fault of the relay,
+    /// and the envelope must be discarded without involving the session.
+    InvalidEnvelope(String),
+}
+
+impl From<LocalError> for RelayError {
+    fn from(error: LocalError) -> Self {
+        Self::Local(error)
+    }
+}
+
+/// A store-and-forward coordinator, buffering the envelopes for each recipient until they are fetched.
+///
+/// The relay does not need to be trusted: it cannot forge or alter the messages (see [`RelayEnvelope`]).
+/// If it withholds some of them, the recipient's session will not be able to finalize the round;
+/// when the session is then terminated, the senders of the withheld messages end up
+/// in [`SessionReport::missing_messages`](`super::SessionReport::missing_messages`),
+/// and not among the misbehaving parties.
+#[derive(Debug, Clone)]
+pub struct Relay<Verifier> {
+    mailboxes: BTreeMap<Verifier, Vec<RelayEnvelope<Verifier>>>,
+}
+
+impl<Verifier> Default for Relay<Verifier> {
+    fn default() -> Self {
+        Self {
+            mailboxes: BTreeMap::new(),
+        }
+    }
+}
+
+impl<Verifier> Relay<Verifier>
+where
+    Verifier: Clone + Ord,
+{
+    /// Creates a relay with no envelopes stored.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the envelope until its recipient requests it.
+    pub fn deposit(&mut self, envelope: RelayEnvelope<Verifier>) {
+        match self.mailboxes.get_mut(envelope.to()) {
+            Some(mailbox) => mailbox.push(envelope),
+            None => {
+                self.mailboxes.insert(envelope.to().clone(), vec![envelope]);
+            }
+        }
+    }
+
+    /// Returns the number of envelopes stored for the given recipient.
+    pub fn pending(&self, recipient: &Verifier) -> usize {
+        self.mailboxes.get(recipient).map_or(0, |mailbox| mailbox.len())
+    }
+
+    /// Removes and returns all the envelopes stored for the given recipient, in the order they were deposited.
+    pub fn fetch(&mut self, recipient: &Verifier) -> Vec<RelayEnvelope<Verifier>> {
+        self.mailboxes.remove(recipient).unwrap_or_default()
+    }
+}
+
+/// The node side of the communication through a [`Relay`].
+///
+/// Seals the outgoing messages into [`RelayEnvelope`]s, and authenticates the incoming ones,
+/// rejecting any envelope delivered more than once.
+#[derive_where::derive_where(Debug)]
+pub struct RelayClient<SP: SessionParameters> {
+    verifier: SP::Verifier,
+    session_id: SessionId,
+    // The digests of all the envelopes opened so far.
+    opened: BTreeSet<Box<[u8]>>,
+}
+
+impl<SP> RelayClient<SP>
+where
+    SP: SessionParameters,
+{
+    /// Creates a client for the node with the given verifying key, taking part in the session `session_id`.
+    pub fn new(verifier: SP::Verifier, session_id: SessionId) -> Self {
+        Self {
+            verifier,
+            session_id,
+            opened: BTreeSet::new(),
+        }
+    }
+
+    fn contents(
+        &self,
+        signer: &SP::Signer,
+        to: SP::Verifier,
+        message: Message<SP::Verifier>,
+    ) -> Result<EnvelopeContents<SP::Verifier>, LocalError> {
+        if signer.verifying_key() != self.verifier {
+            return Err(LocalError::new(
+                "The signer does not correspond to the verifier of the relay client",
+            ));
+        }
+        Ok(EnvelopeContents {
+            session_id: self.session_id.clone(),
+            from: self.verifier.clone(),
+            to,
+            message,
+        })
+    }
+
+    /// Seals a message intended for `to` with the given signer.
+    pub fn seal(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        signer: &SP::Signer,
+        to: SP::Verifier,
+        message: Message<SP::Verifier>,
+    ) -> Result<RelayEnvelope<SP::Verifier>, LocalError> {
+        let contents = self.contents(signer, to, message)?;
+        let signature = signer
+            .try_sign_digest_with_rng(rng, contents.digest::<SP>()?)
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Signing, format!("Failed to sign: {:?}", err)))?;
+        Ok(RelayEnvelope {
+            contents,
+            signature: SerializedSignature::new::<SP>(signature)?,
+        })
+    }
+
+    /// Seals a message intended for `to` with the given signer,
+    /// signing it with [`AsyncSessionParameters::sign_digests`].
+    pub async fn seal_async(
+        &self,
+        rng: &mut (dyn CryptoRngCore + Send),
+        signer: &SP::Signer,
+        to: SP::Verifier,
+        message: Message<SP::Verifier>,
+    ) -> Result<RelayEnvelope<SP::Verifier>, LocalError>
+    where
+        SP: AsyncSessionParameters,
+    {
+        let contents = self.contents(signer, to, message)?;
+        let signature = SP::sign_digests(signer, rng, vec![contents.digest::<SP>()?])
+            .await
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Signing, format!("Failed to sign: {:?}", err)))?
+            .pop()
+            .ok_or_else(|| LocalError::new("No signature was returned"))?;
+        Ok(RelayEnvelope {
+            contents,
+            signature: SerializedSignature::new::<SP>(signature)?,
+        })
+    }
+
+    /// Authenticates an envelope received from the relay,
+    /// returning the sender and the message to be passed to the session.
+    pub fn open(
+        &mut self,
+        envelope: RelayEnvelope<SP::Verifier>,
+    ) -> Result<(SP::Verifier, Message<SP::Verifier>), RelayError> {
+        let RelayEnvelope { contents, signature } = envelope;
+
+        if contents.session_id != self.session_id {
+            return Err(RelayError::InvalidEnvelope(
+                "The envelope belongs to another session".into(),
+            ));
+        }
+        if contents.to != self.verifier {
+            return Err(RelayError::InvalidEnvelope(format!(
+                "The envelope is intended for {:?}",
+                contents.to
+            )));
+        }
+
+        // Signatures can be malleable, so the envelopes are identified by their contents.
+        let digest_bytes: Box<[u8]> = contents.digest::<SP>()?.finalize().as_slice().into();
+        if self.opened.contains(&digest_bytes) {
+            return Err(RelayError::InvalidEnvelope("The envelope was already delivered".into()));
+        }
+
+        let signature = signature.deserialize::<SP>().map_err(|err| match err {
+            MessageVerificationError::Local(err) => RelayError::Local(err),
+            _ => RelayError::InvalidEnvelope("Failed to deserialize the envelope signature".into()),
+        })?;
+        contents
+            .from
+            .verify_digest(contents.digest::<SP>()?, &signature)
+            .map_err(|_| RelayError::InvalidEnvelope("Invalid envelope signature".into()))?;
+
+        self.opened.insert(digest_bytes);
+        Ok((contents.from, contents.message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+    use signature::Keypair;
+
+    use super::{Relay, RelayClient, RelayEnvelope, RelayError};
+    use crate::{
+        dev::{BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
+        protocol::{BoxedFormat, DirectMessage, EchoBroadcast, NormalBroadcast, ProtocolMessagePart, RoundId},
+        session::{
+            message::{Message, SignedMessagePart, UnsignedMessage},
+            party_encoding::PartyEncoding,
+            SessionId,
+        },
+    };
+
+    type SP = TestSessionParams<BinaryFormat>;
+
+    fn make_message(signer: &TestSigner, session_id: &SessionId, to: &TestVerifier) -> Message<TestVerifier> {
+        let format = BoxedFormat::new::<BinaryFormat>();
+        let round_id = RoundId::new(1);
+        let echo_broadcast =
+            SignedMessagePart::new::<SP>(&mut OsRng, signer, session_id, &round_id, EchoBroadcast::none()).unwrap();
+        let normal_broadcast =
+            SignedMessagePart::new::<SP>(&mut OsRng, signer, session_id, &round_id, NormalBroadcast::none()).unwrap();
+        UnsignedMessage::new(
+            session_id,
+            &round_id,
+            PartyEncoding::full().encode(to),
+            DirectMessage::new(&format, [1u8]).unwrap(),
+            echo_broadcast,
+            normal_broadcast,
+            None,
+        )
+        .sign::<SP>(&mut OsRng, signer)
+        .unwrap()
+    }
+
+    fn seal(
+        signer: &TestSigner,
+        session_id: &SessionId,
+        to: &TestVerifier,
+    ) -> (RelayClient<SP>, RelayEnvelope<TestVerifier>) {
+        let client = RelayClient::<SP>::new(signer.verifying_key(), session_id.clone());
+        let message = make_message(signer, session_id, to);
+        let envelope = client.seal(&mut OsRng, signer, *to, message).unwrap();
+        (client, envelope)
+    }
+
+    #[test]
+    fn delivery() {
+        let session_id = SessionId::random::<SP>(&mut OsRng);
+        let alice = TestSigner::new(0);
+        let bob = TestSigner::new(1);
+
+        let (_client, envelope) = seal(&alice, &session_id, &bob.verifying_key());
+
+        let mut relay = Relay::new();
+        relay.deposit(envelope);
+        assert_eq!(relay.pending(&bob.verifying_key()), 1);
+        assert_eq!(relay.pending(&alice.verifying_key()), 0);
+
+        let mut bob_client = RelayClient::<SP>::new(bob.verifying_key(), session_id);
+        let envelopes = relay.fetch(&bob.verifying_key());
+        assert_eq!(envelopes.len(), 1);
+        assert_eq!(relay.pending(&bob.verifying_key()), 0);
+
+        let (from, _message) = bob_client.open(envelopes[0].clone()).unwrap();
+        assert_eq!(from, alice.verifying_key());
+
+        // A replayed envelope is rejected
+        assert!(matches!(
+            bob_client.open(envelopes[0].clone()),
+            Err(RelayError::InvalidEnvelope(_))
+        ));
+    }
+
+    #[test]
+    fn misrouted_envelope() {
+        let session_id = SessionId::random::<SP>(&mut OsRng);
+        let alice = TestSigner::new(0);
+        let bob = TestSigner::new(1);
+        let carol = TestSigner::new(2);
+
+        let (_client, envelope) = seal(&alice, &session_id, &bob.verifying_key());
+
+        // Delivered to the wrong node
+        let mut carol_client = RelayClient::<SP>::new(carol.verifying_key(), session_id.clone());
+        assert!(matches!(
+            carol_client.open(envelope.clone()),
+            Err(RelayError::InvalidEnvelope(_))
+        ));
+
+        // Delivered in another session
+        let mut bob_client = RelayClient::<SP>::new(bob.verifying_key(), SessionId::random::<SP>(&mut OsRng));
+        assert!(matches!(bob_client.open(envelope), Err(RelayError::InvalidEnvelope(_))));
+    }
+
+    #[test]
+    fn forged_envelope() {
+        let session_id = SessionId::random::<SP>(&mut OsRng);
+        let alice = TestSigner::new(0);
+        let bob = TestSigner::new(1);
+        let carol = TestSigner::new(2);
+
+        let mut bob_client = RelayClient::<SP>::new(bob.verifying_key(), session_id.clone());
+
+        // The relay claims that Carol's message came from Alice
+        let (_client, mut envelope) = seal(&carol, &session_id, &bob.verifying_key());
+        envelope.contents.from = alice.verifying_key();
+        assert!(matches!(bob_client.open(envelope), Err(RelayError::InvalidEnvelope(_))));
+    }
+
+    #[test]
+    fn mismatched_signer() {
+        let session_id = SessionId::random::<SP>(&mut OsRng);
+        let alice = TestSigner::new(0);
+        let bob = TestSigner::new(1);
+
+        let client = RelayClient::<SP>::new(alice.verifying_key(), session_id.clone());
+        let message = make_message(&bob, &session_id, &alice.verifying_key());
+        assert!(client.seal(&mut OsRng, &bob, alice.verifying_key(), message).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! An optional sub-protocol for requesting the retransmission of lost messages.
+//!
+//! It is supported by [`SessionDriver::new_with_retransmission`](`super::SessionDriver::new_with_retransmission`)
+//! and the `*_with_retransmission` variants of the session runners.
+//! Custom runners can use [`Outbox`] and [`ResendRequest`] directly.
+
+use alloc::{collections::BTreeMap, format, string::String};
+
+use digest::Digest;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use signature::{DigestVerifier, Keypair, RandomizedDigestSigner};
+use tracing::{debug, trace, warn};
+
+use super::{
+    message::{Message, MessageVerificationError, SerializedSignature},
+    session::{SessionId, SessionParameters},
+    wire_format::WireFormat,
+    LocalError, LocalErrorKind,
+};
+use crate::protocol::RoundId;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RequestContents<Verifier> {
+    session_id: SessionId,
+    round_id: RoundId,
+    requester: Verifier,
+    responder: Verifier,
+}
+
+impl<Verifier> RequestContents<Verifier>
+where
+    Verifier: Serialize,
+{
+    fn digest<SP>(&self) -> Result<SP::Digest, LocalError>
+    where
+        SP: SessionParameters<Verifier = Verifier>,
+    {
+        Ok(SP::Digest::new_with_prefix(b"ResendRequestDigest").chain_update(SP::WireFormat::serialize(self)?))
+    }
+}
+
+/// A request to resend the message of a specific round, signed by the party that did not receive it.
+///
+/// The request is answered by the [`Outbox`] of the party the message was expected from.
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
+pub struct ResendRequest<SP: SessionParameters> {
+    contents: RequestContents<SP::Verifier>,
+    signature: SerializedSignature,
+}
+
+impl<SP> ResendRequest<SP>
+where
+    SP: SessionParameters,
+{
+    /// Creates a request to `responder` for the message of the round `round_id` in the session `session_id`,
+    /// signed by `signer`.
+    pub fn new(
+        rng: &mut impl CryptoRngCore,
+        signer: &SP::Signer,
+        session_id: &SessionId,
+        round_id: &RoundId,
+        responder: &SP::Verifier,
+    ) -> Result<Self, LocalError> {
+        let contents = RequestContents {
+            session_id: session_id.clone(),
+            round_id: round_id.clone(),
+            requester: signer.verifying_key(),
+            responder: responder.clone(),
+        };
+        let signature = signer
+            .try_sign_digest_with_rng(rng, contents.digest::<SP>()?)
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Signing, format!("Failed to sign: {:?}", err)))?;
+        Ok(Self {
+            contents,
+            signature: SerializedSignature::new::<SP>(signature)?,
+        })
+    }
+
+    /// Returns the ID of the round the message is requested for.
+    pub fn round_id(&self) -> &RoundId {
+        &self.contents.round_id
+    }
+
+    /// Returns the verifying key of the party that requested the message.
+    ///
+    /// Note that this is only authenticated after [`Outbox::respond`] succeeds.
+    pub fn requester(&self) -> &SP::Verifier {
+        &self.contents.requester
+    }
+
+    /// Returns the verifying key of the party the message is requested from.
+    pub fn responder(&self) -> &SP::Verifier {
+        &self.contents.responder
+    }
+}
+
+/// An error that can occur when answering a [`ResendRequest`].
+#[derive(Debug, Clone)]
+pub enum ResendRequestError {
+    /// Indicates a runtime problem or a bug in the code.
+    Local(LocalError),
+    /// The request was not signed by its declared requester, or it was not intended for this node.
+    InvalidRequest(String),
+}
+
+impl From<LocalError> for ResendRequestError {
+    fn from(error: LocalError) -> Self {
+        Self::Local(error)
+    }
+}
+
+/// The messages sent by a node during a session, kept to answer [`ResendRequest`]s.
+#[derive_where::derive_where(Debug)]
+pub struct Outbox<SP: SessionParameters> {
+    verifier: SP::Verifier,
+    session_id: SessionId,
+    messages: BTreeMap<(RoundId, SP::Verifier), Message<SP::Verifier>>,
+}
+
+impl<SP> Outbox<SP>
+where
+    SP: SessionParameters,
+{
+    /// Creates an empty outbox for the node with the given verifying key, taking part in the session `session_id`.
+    pub fn new(verifier: SP::Verifier, session_id: SessionId) -> Self {
+        Self {
+            verifier,
+            session_id,
+            messages: BTreeMap::new(),
+        }
+    }
+
+    /// Keeps a copy of the message sent to `to`.
+    ///
+    /// If there already is a message for the same round and destination, it is replaced.
+    pub fn record(&mut self, to: &SP::Verifier, message: &Message<SP::Verifier>) {
+        self.messages
+            .insert((message.round_id().clone(), to.clone()), message.clone());
+    }
+
+    /// Authenticates the request, and returns the message to be sent to the requester
+    /// if one was sent to it in the requested round.
+    ///
+    /// The message is the one originally sent, so it will be accepted by the requester's session as is.
+    pub fn respond(&self, request: &ResendRequest<SP>) -> Result<Option<Message<SP::Verifier>>, ResendRequestError> {
+        let contents = &request.contents;
+
+        if contents.session_id != self.session_id {
+            return Err(ResendRequestError::InvalidRequest(
+                "The request belongs to another session".into(),
+            ));
+        }
+        if contents.responder != self.verifier {
+            return Err(ResendRequestError::InvalidRequest(format!(
+                "The request is intended for {:?}",
+                contents.responder
+            )));
+        }
+
+        let signature = request.signature.deserialize::<SP>().map_err(|err| match err {
+            MessageVerificationError::Local(err) => ResendRequestError::Local(err),
+            _ => ResendRequestError::InvalidRequest("Failed to deserialize the request signature".into()),
+        })?;
+        contents
+            .requester
+            .verify_digest(contents.digest::<SP>()?, &signature)
+            .map_err(|_| ResendRequestError::InvalidRequest("Invalid request signature".into()))?;
+
+        Ok(self
+            .messages
+            .get(&(contents.round_id.clone(), contents.requester.clone()))
+            .cloned())
+    }
+
+    /// Same as [`respond`](`Self::respond`), but logs and ignores the invalid requests.
+    pub(crate) fn answer(&self, request: &ResendRequest<SP>) -> Result<Option<Message<SP::Verifier>>, LocalError> {
+        match self.respond(request) {
+            Ok(Some(message)) => {
+                debug!(
+                    "Resending the message for {:?} to {:?}",
+                    request.round_id(),
+                    request.requester()
+                );
+                Ok(Some(message))
+            }
+            Ok(None) => {
+                trace!(
+                    "No message for {:?} was sent to {:?}",
+                    request.round_id(),
+                    request.requester()
+                );
+                Ok(None)
+            }
+            Err(ResendRequestError::InvalidRequest(err)) => {
+                warn!("Ignoring an invalid resend request: {err}");
+                Ok(None)
+            }
+            Err(ResendRequestError::Local(err)) => Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+    use signature::Keypair;
+
+    use super::{Outbox, ResendRequest, ResendRequestError};
+    use crate::{
+        dev::{BinaryFormat, TestSessionParams, TestSigner},
+        protocol::RoundId,
+        session::SessionId,
+    };
+
+    type SP = TestSessionParams<BinaryFormat>;
+
+    #[test]
+    fn invalid_requests() {
+        let session_id = SessionId::random::<SP>(&mut OsRng);
+        let round_id = RoundId::new(1);
+        let alice = TestSigner::new(0);
+        let bob = TestSigner::new(1);
+        let carol = TestSigner::new(2);
+
+        let outbox = Outbox::<SP>::new(bob.verifying_key(), session_id.clone());
+
+        // A valid request, but nothing was sent to Alice
+        let request =
+            ResendRequest::<SP>::new(&mut OsRng, &alice, &session_id, &round_id, &bob.verifying_key()).unwrap();
+        assert!(outbox.respond(&request).unwrap().is_none());
+
+        // Intended for another node
+        let request =
+            ResendRequest::<SP>::new(&mut OsRng, &alice, &session_id, &round_id, &carol.verifying_key()).unwrap();
+        assert!(matches!(
+            outbox.respond(&request),
+            Err(ResendRequestError::InvalidRequest(_))
+        ));
+
+        // From another session
+        let other_session_id = SessionId::random::<SP>(&mut OsRng);
+        let request =
+            ResendRequest::<SP>::new(&mut OsRng, &alice, &other_session_id, &round_id, &bob.verifying_key()).unwrap();
+        assert!(matches!(
+            outbox.respond(&request),
+            Err(ResendRequestError::InvalidRequest(_))
+        ));
+
+        // Carol pretends to be Alice to get the message sent to her
+        let mut request =
+            ResendRequest::<SP>::new(&mut OsRng, &carol, &session_id, &round_id, &bob.verifying_key()).unwrap();
+        request.contents.requester = alice.verifying_key();
+        assert!(matches!(
+            outbox.respond(&request),
+            Err(ResendRequestError::InvalidRequest(_))
+        ));
+    }
+}
@@ -3,6 +3,7 @@ use alloc::{
     collections::{BTreeMap, BTreeSet},
     format,
     string::String,
+    sync::Arc,
     vec::Vec,
 };
 use core::fmt::Debug;
@@ -15,17 +16,27 @@ use signature::{DigestVerifier, Keypair, RandomizedDigestSigner};
 use tracing::{debug, trace};
 
 use super::{
-    echo::EchoRound,
+    ban_policy::{BanDecision, BanPolicy, StrictBanPolicy},
+    bracha,
+    echo::{EchoRound, EchoState},
     evidence::Evidence,
-    message::{Message, MessageVerificationError, SignedMessagePart, VerifiedMessage},
-    transcript::{SessionOutcome, SessionReport, Transcript},
+    limits::MessageLimits,
+    merkle_echo::MerkleEchoRound,
+    message::{
+        Message, MessageFingerprint, MessageVerificationError, SignedMessagePart, UnsignedMessage, UnsignedMessagePart,
+        VerifiedMessage,
+    },
+    party_encoding::PartyEncoding,
+    piggyback::{FinalizedRound, PiggybackedEcho},
+    retransmission::ResendRequest,
+    transcript::{Offense, SessionOutcome, SessionReport, Transcript},
     wire_format::WireFormat,
-    LocalError, RemoteError,
+    LocalError, LocalErrorKind, RemoteError, RemoteErrorKind,
 };
 use crate::protocol::{
-    Artifact, BoxedFormat, BoxedRound, CommunicationInfo, DirectMessage, EchoBroadcast, EchoRoundParticipation,
-    EntryPoint, FinalizeOutcome, NormalBroadcast, PartyId, Payload, Protocol, ProtocolMessage, ProtocolMessagePart,
-    ReceiveError, ReceiveErrorType, RoundId, TransitionInfo,
+    Artifact, AsyncEntryPoint, BoxFuture, BoxedFormat, BoxedRound, CommunicationInfo, DirectMessage, EchoBroadcast,
+    EchoRoundParticipation, EntryPoint, FinalizeOutcome, NormalBroadcast, PartyId, Payload, Protocol, ProtocolError,
+    ProtocolMessage, ProtocolMessagePart, ReceiveError, ReceiveErrorType, RoundId, RoundKind, TransitionInfo,
 };
 
 /// A set of types needed to execute a session.
@@ -49,6 +60,35 @@ pub trait SessionParameters: 'static {
     type WireFormat: WireFormat;
 }
 
+/// Additional requirements for executing a session asynchronously.
+///
+/// These are needed by the asynchronous methods of [`Session`]
+/// and the runners in [`session::futures`](`crate::session::futures`) and [`session::tokio`](`crate::session::tokio`).
+pub trait AsyncSessionParameters: SessionParameters<Signer: Send + Sync, Digest: Send, Signature: Send> {
+    /// Signs the given digests with `signer`, returning the signatures in the same order.
+    ///
+    /// The asynchronous methods of [`Session`] pass all the message parts that need signing
+    /// at a given point in one batch.
+    /// The default implementation signs the digests one by one synchronously.
+    ///
+    /// Signers backed by an asynchronous API (for example, with the keys held in an HSM or a KMS)
+    /// can override this method. Their [`RandomizedDigestSigner`] implementation
+    /// (still required by [`SessionParameters`]) is then never called by the asynchronous methods,
+    /// and can just return an error, making the synchronous methods of [`Session`] unavailable.
+    fn sign_digests<'a>(
+        signer: &'a Self::Signer,
+        mut rng: &'a mut (dyn CryptoRngCore + Send),
+        digests: Vec<Self::Digest>,
+    ) -> BoxFuture<'a, Result<Vec<Self::Signature>, signature::Error>> {
+        Box::pin(async move {
+            digests
+                .into_iter()
+                .map(|digest| signer.try_sign_digest_with_rng(&mut rng, digest))
+                .collect()
+        })
+    }
+}
+
 /// A session identifier shared between the parties.
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct SessionId(#[serde(with = "SliceLike::<Hex>")] Box<[u8]>);
@@ -97,6 +137,163 @@ impl AsRef<[u8]> for SessionId {
     }
 }
 
+/// The way the session ensures that all the nodes received the same echo broadcasts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BroadcastConsistency {
+    /// After each round with echo broadcasts, every node sends to every other node
+    /// the signed hashes of all the echo broadcasts it received.
+    ///
+    /// The total amount of data sent by each node is quadratic in the number of nodes.
+    #[default]
+    Echo,
+    /// After each round with echo broadcasts, every node sends to every other node
+    /// a Merkle root of the hashes of all the echo broadcasts it received.
+    ///
+    /// The nodes whose roots did not match bisect their trees to find the culprit:
+    /// in each of the additional rounds (logarithmic in the number of nodes) they exchange
+    /// the children of the disputed nodes, and then reveal the disputed hashes along with their inclusion proofs.
+    /// The total amount of data sent by each node is linear in the number of nodes
+    /// if nobody equivocates, and stays linear if someone does.
+    MerkleEcho,
+    /// A Bracha-style reliable broadcast: after each round with echo broadcasts, every node
+    /// sends to every other node the signed hashes of all the echo broadcasts it received (the echo phase),
+    /// and then a digest of the set of echo broadcasts it is ready to deliver (the ready phase).
+    ///
+    /// Assuming there are `n` nodes of which fewer than `n / 3` are faulty,
+    /// the echo phase is finalized after receiving the messages from `n - f - 1` other nodes,
+    /// and the ready phase after receiving matching messages from `2 * f` other nodes
+    /// (where `f = (n - 1) / 3`), so that the missing messages from faulty nodes do not stall the session.
+    /// Equivocations detected in the echo phase are still reported as provable errors.
+    ///
+    /// Note that a ready message with a view different from ours is recorded as an unprovable error
+    /// of its sender, although it may be caused by an equivocation of another node
+    /// that we have not received the echo for.
+    /// Also, the provable errors in the following rounds that require the echo phase message of the sender
+    /// will be reported as unprovable ones if that message was not received before the echo phase was finalized.
+    Bracha,
+    /// The transport already guarantees that every node receives the same echo broadcasts
+    /// (e.g. they are posted to an append-only log), so no rounds are inserted to ensure it.
+    ///
+    /// The echo broadcasts are still signed and stored in the transcript, and can be used as evidence.
+    /// Note that since there is no record of the echo broadcasts a node received from other nodes,
+    /// the provable errors that require them
+    /// (see [`RequiredMessages`](`crate::protocol::RequiredMessages`))
+    /// will be reported as unprovable ones.
+    Trusted,
+    /// Same as [`Echo`](`Self::Echo`), but the echo round messages are attached
+    /// to the messages of the next round instead of being sent in a separate round,
+    /// saving a round trip for every round with echo broadcasts.
+    ///
+    /// This means that the messages of the next round are created and sent before
+    /// the consistency of the echo broadcasts is confirmed, so this mode should only be used
+    /// if it is safe for the protocol in question.
+    /// An inconsistency is still detected (and reported with the same evidence), but only when the next round is finalized.
+    ///
+    /// A separate echo round is used if the round may produce a result,
+    /// or if the next round does not have the same or wider communication pattern as the echo round.
+    /// The protocol must ensure that all the nodes make the same choice
+    /// (which is the case if the communication patterns of the rounds are the same for all the nodes).
+    PiggybackedEcho,
+}
+
+/// Optional settings of a [`Session`].
+///
+/// All the nodes in a session must use the same configuration.
+#[derive(Debug, Clone)]
+pub struct SessionConfig<Id: PartyId> {
+    party_encoding: PartyEncoding<Id>,
+    broadcast_consistency: BroadcastConsistency,
+    message_limits: MessageLimits,
+    ban_policy: Arc<dyn BanPolicy<Id>>,
+}
+
+impl<Id: PartyId> Default for SessionConfig<Id> {
+    fn default() -> Self {
+        Self {
+            party_encoding: PartyEncoding::full(),
+            broadcast_consistency: BroadcastConsistency::default(),
+            message_limits: MessageLimits::default(),
+            ban_policy: Arc::new(StrictBanPolicy),
+        }
+    }
+}
+
+impl<Id: PartyId> SessionConfig<Id> {
+    /// Sets the way the parties are referred to in the serialized messages and evidence
+    /// (by default, the full IDs are used).
+    pub fn with_party_encoding(mut self, party_encoding: PartyEncoding<Id>) -> Self {
+        self.party_encoding = party_encoding;
+        self
+    }
+
+    /// Returns the way the parties are referred to in the serialized messages and evidence.
+    pub fn party_encoding(&self) -> &PartyEncoding<Id> {
+        &self.party_encoding
+    }
+
+    /// Sets the way the session ensures the consistency of echo broadcasts
+    /// (by default, [`BroadcastConsistency::Echo`]).
+    pub fn with_broadcast_consistency(mut self, broadcast_consistency: BroadcastConsistency) -> Self {
+        self.broadcast_consistency = broadcast_consistency;
+        self
+    }
+
+    /// Returns the way the session ensures the consistency of echo broadcasts.
+    pub fn broadcast_consistency(&self) -> BroadcastConsistency {
+        self.broadcast_consistency
+    }
+
+    /// Sets the limits on the size and the number of messages accepted from other nodes
+    /// (by default, [`MessageLimits::unlimited`]).
+    pub fn with_message_limits(mut self, message_limits: MessageLimits) -> Self {
+        self.message_limits = message_limits;
+        self
+    }
+
+    /// Returns the limits on the size and the number of messages accepted from other nodes.
+    pub fn message_limits(&self) -> &MessageLimits {
+        &self.message_limits
+    }
+
+    /// Sets the policy deciding which unprovable errors get their sender banned
+    /// (by default, [`StrictBanPolicy`](`super::StrictBanPolicy`)).
+    ///
+    /// Unlike the other settings, this one only affects the local node.
+    pub fn with_ban_policy(mut self, ban_policy: impl BanPolicy<Id> + 'static) -> Self {
+        self.ban_policy = Arc::new(ban_policy);
+        self
+    }
+
+    /// Returns the policy deciding which unprovable errors get their sender banned.
+    pub fn ban_policy(&self) -> &dyn BanPolicy<Id> {
+        self.ban_policy.as_ref()
+    }
+
+    /// Returns the ID of the round following `round_id` that ensures the consistency of its echo broadcasts,
+    /// or `None` if there is no such round.
+    pub(crate) fn echo_round_id(&self, round_id: &RoundId) -> Result<Option<RoundId>, LocalError> {
+        Ok(match self.broadcast_consistency {
+            BroadcastConsistency::Echo | BroadcastConsistency::PiggybackedEcho => Some(round_id.echo()?),
+            BroadcastConsistency::MerkleEcho => Some(round_id.merkle_echo()?),
+            BroadcastConsistency::Bracha => Some(round_id.bracha_echo()?),
+            BroadcastConsistency::Trusted => None,
+        })
+    }
+
+    /// Returns the IDs of all the rounds the session inserts after `round_id`
+    /// to ensure the consistency of its echo broadcasts.
+    ///
+    /// These do not include the rounds that are only created if an inconsistency is found.
+    pub(crate) fn echo_round_ids(&self, round_id: &RoundId) -> Result<BTreeSet<RoundId>, LocalError> {
+        Ok(match self.broadcast_consistency {
+            BroadcastConsistency::Echo | BroadcastConsistency::PiggybackedEcho => [round_id.echo()?].into(),
+            BroadcastConsistency::MerkleEcho => [round_id.merkle_echo()?].into(),
+            BroadcastConsistency::Bracha => [round_id.bracha_echo()?, round_id.bracha_ready()?].into(),
+            BroadcastConsistency::Trusted => BTreeSet::new(),
+        })
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct EchoRoundInfo<Verifier> {
     pub(crate) message_destinations: BTreeSet<Verifier>,
@@ -112,6 +309,7 @@ pub struct Session<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     signer: SP::Signer,
     verifier: SP::Verifier,
     format: BoxedFormat,
+    config: SessionConfig<SP::Verifier>,
     round: BoxedRound<SP::Verifier, P>,
     communication_info: CommunicationInfo<SP::Verifier>,
     echo_round_info: Option<EchoRoundInfo<SP::Verifier>>,
@@ -119,9 +317,17 @@ pub struct Session<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     normal_broadcast: SignedMessagePart<NormalBroadcast>,
     transition_info: TransitionInfo,
     transcript: Transcript<P, SP>,
+    // Messages cached during the previous rounds for the rounds after the current one.
+    cached: BTreeMap<SP::Verifier, BTreeMap<RoundId, VerifiedMessage<SP::Verifier>>>,
+    // The fingerprints of the cached messages for the current round, handed out to be processed.
+    current_cached: BTreeMap<SP::Verifier, MessageFingerprint>,
+    // The echo round of the previous round, carried out along with this one.
+    piggybacked_echo: Option<PiggybackedEcho<SP>>,
 }
 
 /// Possible non-erroneous results of finalizing a round.
+// There is only one of these in existence at a time, so the size difference is not a concern.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum RoundOutcome<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     /// The execution is finished.
@@ -135,6 +341,298 @@ pub enum RoundOutcome<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     },
 }
 
+#[allow(clippy::large_enum_variant)]
+enum FinalizationStep<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    /// The finalization is complete, save for signing the messages of the next round.
+    Done(UnsignedRoundOutcome<P, SP>),
+    /// The round has to be finalized, with the outcome passed to [`RoundFinalization::complete`].
+    FinalizeRound {
+        finalization: RoundFinalization<P, SP>,
+        round: BoxedRound<SP::Verifier, P>,
+        payloads: BTreeMap<SP::Verifier, Payload>,
+        artifacts: BTreeMap<SP::Verifier, Artifact>,
+    },
+}
+
+/// The state of a session whose round is being finalized.
+struct RoundFinalization<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    session_id: SessionId,
+    signer: SP::Signer,
+    format: BoxedFormat,
+    config: SessionConfig<SP::Verifier>,
+    transition_info: TransitionInfo,
+    communication_info: CommunicationInfo<SP::Verifier>,
+    transcript: Transcript<P, SP>,
+    cached: BTreeMap<SP::Verifier, BTreeMap<RoundId, VerifiedMessage<SP::Verifier>>>,
+    // The echo round state, if the echo round messages are to be attached to the messages of the next round.
+    piggybacked_echo: Option<EchoState<SP>>,
+}
+
+impl<P, SP> RoundFinalization<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    /// Completes the finalization given the outcome of the round's `finalize()`.
+    fn complete(
+        mut self,
+        rng: &mut impl CryptoRngCore,
+        outcome: FinalizeOutcome<SP::Verifier, P>,
+    ) -> Result<UnsignedRoundOutcome<P, SP>, LocalError> {
+        let round_id = self.transition_info.id();
+        let round = match outcome {
+            FinalizeOutcome::Result(_) if self.piggybacked_echo.is_some() => {
+                return Err(LocalError::new(format!(
+                    "{round_id} produced a result, but its transition info says it cannot"
+                )))
+            }
+            FinalizeOutcome::Result(result) => {
+                return Ok(UnsignedRoundOutcome::Finished(SessionReport::new(
+                    SessionOutcome::Result(result),
+                    self.transcript,
+                )))
+            }
+            FinalizeOutcome::AnotherRound(round) => round,
+        };
+
+        if round.is_deferred() {
+            return Err(LocalError::new(
+                "Asynchronous rounds must be finalized with `Session::finalize_round_async()`",
+            ));
+        }
+
+        let next_round_id = round.transition_info().id();
+        // Protecting against common bugs
+        if !self.transition_info.children.contains(&next_round_id) {
+            return Err(LocalError::new(format!(
+                "Unexpected next round id: {:?}",
+                next_round_id
+            )));
+        }
+
+        let echo = match self.piggybacked_echo.take() {
+            Some(echo) => echo,
+            None => {
+                let session = Session::new_for_next_round(
+                    rng,
+                    self.session_id,
+                    self.signer,
+                    self.format,
+                    self.config,
+                    round,
+                    self.transcript,
+                )?;
+                return Ok(UnsignedRoundOutcome::AnotherRound {
+                    session,
+                    cached: self.cached,
+                    is_echo_round: false,
+                });
+            }
+        };
+
+        if PiggybackedEcho::fits(&echo, &round.communication_info()) {
+            let echo_round_message = UnsignedMessagePart::new(
+                &self.session_id,
+                &round_id.echo()?,
+                echo.make_normal_broadcast(&self.format)?,
+            );
+            let mut session = Session::new_for_next_round(
+                rng,
+                self.session_id,
+                self.signer,
+                self.format,
+                self.config,
+                round,
+                self.transcript,
+            )?;
+            session.piggybacked_echo = Some((echo, echo_round_message));
+            return Ok(UnsignedRoundOutcome::AnotherRound {
+                session,
+                cached: self.cached,
+                is_echo_round: false,
+            });
+        }
+
+        // Some of the echo round messages would have nothing to be attached to,
+        // so we fall back to a separate echo round.
+        let finalized_round = FinalizedRound::new(self.transition_info.clone(), self.communication_info.clone(), round);
+        let echo_round = BoxedRound::new_dynamic(EchoRound::<P, SP>::new(
+            BroadcastConsistency::Echo,
+            echo,
+            BoxedRound::new_dynamic(finalized_round),
+            BTreeMap::new(),
+            BTreeMap::new(),
+        ));
+        self.into_echo_round_outcome(rng, echo_round)
+    }
+
+    /// Returns the outcome of transitioning into the given echo round.
+    fn into_echo_round_outcome(
+        self,
+        rng: &mut impl CryptoRngCore,
+        round: BoxedRound<SP::Verifier, P>,
+    ) -> Result<UnsignedRoundOutcome<P, SP>, LocalError> {
+        let session = Session::new_for_next_round(
+            rng,
+            self.session_id,
+            self.signer,
+            self.format,
+            self.config,
+            round,
+            self.transcript,
+        )?;
+        Ok(UnsignedRoundOutcome::AnotherRound {
+            session,
+            cached: self.cached,
+            is_echo_round: true,
+        })
+    }
+}
+
+/// A [`RoundOutcome`] with the broadcasts of the next round yet to be signed.
+#[allow(clippy::large_enum_variant)]
+enum UnsignedRoundOutcome<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    Finished(SessionReport<P, SP>),
+    AnotherRound {
+        session: UnsignedSession<P, SP>,
+        cached: BTreeMap<SP::Verifier, BTreeMap<RoundId, VerifiedMessage<SP::Verifier>>>,
+        is_echo_round: bool,
+    },
+}
+
+impl<P, SP> UnsignedRoundOutcome<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    fn sign(self, rng: &mut dyn CryptoRngCore) -> Result<RoundOutcome<P, SP>, LocalError> {
+        match self {
+            Self::Finished(report) => Ok(RoundOutcome::Finished(report)),
+            Self::AnotherRound {
+                session,
+                cached,
+                is_echo_round,
+            } => session.sign(rng)?.into_next_round_outcome(cached, is_echo_round),
+        }
+    }
+
+    async fn sign_async(self, rng: &mut (dyn CryptoRngCore + Send)) -> Result<RoundOutcome<P, SP>, LocalError>
+    where
+        SP: AsyncSessionParameters,
+    {
+        match self {
+            Self::Finished(report) => Ok(RoundOutcome::Finished(report)),
+            Self::AnotherRound {
+                session,
+                cached,
+                is_echo_round,
+            } => session
+                .sign_async(rng)
+                .await?
+                .into_next_round_outcome(cached, is_echo_round),
+        }
+    }
+}
+
+/// A [`Session`] with the broadcasts of its round yet to be signed.
+struct UnsignedSession<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    session_id: SessionId,
+    signer: SP::Signer,
+    verifier: SP::Verifier,
+    format: BoxedFormat,
+    config: SessionConfig<SP::Verifier>,
+    round: BoxedRound<SP::Verifier, P>,
+    communication_info: CommunicationInfo<SP::Verifier>,
+    echo_round_info: Option<EchoRoundInfo<SP::Verifier>>,
+    echo_broadcast: UnsignedMessagePart<EchoBroadcast>,
+    normal_broadcast: UnsignedMessagePart<NormalBroadcast>,
+    transition_info: TransitionInfo,
+    transcript: Transcript<P, SP>,
+    piggybacked_echo: Option<(EchoState<SP>, UnsignedMessagePart<NormalBroadcast>)>,
+}
+
+impl<P, SP> UnsignedSession<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    fn digests(&self) -> Result<Vec<SP::Digest>, LocalError> {
+        let mut digests = Vec::from([
+            self.echo_broadcast.digest::<SP>()?,
+            self.normal_broadcast.digest::<SP>()?,
+        ]);
+        if let Some((_echo, echo_round_message)) = &self.piggybacked_echo {
+            digests.push(echo_round_message.digest::<SP>()?);
+        }
+        Ok(digests)
+    }
+
+    fn sign(self, mut rng: &mut dyn CryptoRngCore) -> Result<Session<P, SP>, LocalError> {
+        let signatures = self
+            .digests()?
+            .into_iter()
+            .map(|digest| self.signer.try_sign_digest_with_rng(&mut rng, digest))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Signing, format!("Failed to sign: {:?}", err)))?;
+        self.with_signatures(signatures)
+    }
+
+    async fn sign_async(self, rng: &mut (dyn CryptoRngCore + Send)) -> Result<Session<P, SP>, LocalError>
+    where
+        SP: AsyncSessionParameters,
+    {
+        let digests = self.digests()?;
+        let signatures = SP::sign_digests(&self.signer, rng, digests)
+            .await
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Signing, format!("Failed to sign: {:?}", err)))?;
+        self.with_signatures(signatures)
+    }
+
+    fn with_signatures(self, signatures: Vec<SP::Signature>) -> Result<Session<P, SP>, LocalError> {
+        let expected_len = 2 + usize::from(self.piggybacked_echo.is_some());
+        if signatures.len() != expected_len {
+            return Err(LocalError::new(format!(
+                "Expected {expected_len} signatures from the signer, got {}",
+                signatures.len()
+            )));
+        }
+        let mut signatures = signatures.into_iter();
+        let mut next_signature = || {
+            signatures
+                .next()
+                .ok_or_else(|| LocalError::new("Not enough signatures"))
+        };
+
+        let echo_broadcast = self.echo_broadcast.with_signature::<SP>(next_signature()?)?;
+        let normal_broadcast = self.normal_broadcast.with_signature::<SP>(next_signature()?)?;
+        let piggybacked_echo = match self.piggybacked_echo {
+            Some((echo, echo_round_message)) => Some(PiggybackedEcho::new(
+                echo,
+                echo_round_message.with_signature::<SP>(next_signature()?)?,
+            )),
+            None => None,
+        };
+
+        Ok(Session {
+            session_id: self.session_id,
+            signer: self.signer,
+            verifier: self.verifier,
+            format: self.format,
+            config: self.config,
+            round: self.round,
+            communication_info: self.communication_info,
+            echo_round_info: self.echo_round_info,
+            echo_broadcast,
+            normal_broadcast,
+            transition_info: self.transition_info,
+            transcript: self.transcript,
+            cached: BTreeMap::new(),
+            current_cached: BTreeMap::new(),
+            piggybacked_echo,
+        })
+    }
+}
+
 impl<P, SP> Session<P, SP>
 where
     P: Protocol<SP::Verifier>,
@@ -147,12 +645,66 @@ where
         signer: SP::Signer,
         entry_point: EP,
     ) -> Result<Self, LocalError>
+    where
+        EP: EntryPoint<SP::Verifier, Protocol = P>,
+    {
+        Self::new_with_config(rng, session_id, signer, entry_point, SessionConfig::default())
+    }
+
+    /// Initializes a new session with the given configuration.
+    pub fn new_with_config<EP>(
+        rng: &mut impl CryptoRngCore,
+        session_id: SessionId,
+        signer: SP::Signer,
+        entry_point: EP,
+        config: SessionConfig<SP::Verifier>,
+    ) -> Result<Self, LocalError>
     where
         EP: EntryPoint<SP::Verifier, Protocol = P>,
     {
         let first_round = entry_point.make_round(rng, session_id.as_ref(), &signer.verifying_key())?;
         let format = BoxedFormat::new::<SP::WireFormat>();
-        Self::new_for_next_round(rng, session_id, signer, format, first_round, Transcript::new())
+        Self::new_for_next_round(rng, session_id, signer, format, config, first_round, Transcript::new())?.sign(rng)
+    }
+
+    /// Initializes a new session with an entry point that may be asynchronous.
+    ///
+    /// The resulting session must be executed with the asynchronous methods
+    /// ([`process_message_async`](`Self::process_message_async`)
+    /// and [`finalize_round_async`](`Self::finalize_round_async`)) if any of the protocol's rounds are asynchronous.
+    pub async fn new_async<EP>(
+        rng: &mut (impl CryptoRngCore + Send),
+        session_id: SessionId,
+        signer: SP::Signer,
+        entry_point: EP,
+    ) -> Result<Self, LocalError>
+    where
+        SP: AsyncSessionParameters,
+        EP: AsyncEntryPoint<SP::Verifier, Protocol = P>,
+    {
+        Self::new_async_with_config(rng, session_id, signer, entry_point, SessionConfig::default()).await
+    }
+
+    /// Initializes a new session with an entry point that may be asynchronous, and the given configuration.
+    ///
+    /// See [`new_async`](`Self::new_async`) for details.
+    pub async fn new_async_with_config<EP>(
+        rng: &mut (impl CryptoRngCore + Send),
+        session_id: SessionId,
+        signer: SP::Signer,
+        entry_point: EP,
+        config: SessionConfig<SP::Verifier>,
+    ) -> Result<Self, LocalError>
+    where
+        SP: AsyncSessionParameters,
+        EP: AsyncEntryPoint<SP::Verifier, Protocol = P>,
+    {
+        let verifier = signer.verifying_key();
+        let first_round = entry_point.make_round(rng, session_id.as_ref(), &verifier).await?;
+        let format = BoxedFormat::new::<SP::WireFormat>();
+        Self::new_for_next_round(rng, session_id, signer, format, config, first_round, Transcript::new())?
+            .sign_async(rng)
+            .await
     }
 
     fn new_for_next_round(
@@ -160,23 +712,26 @@ where
         session_id: SessionId,
         signer: SP::Signer,
         format: BoxedFormat,
+        config: SessionConfig<SP::Verifier>,
         round: BoxedRound<SP::Verifier, P>,
         transcript: Transcript<P, SP>,
-    ) -> Result<Self, LocalError> {
+    ) -> Result<UnsignedSession<P, SP>, LocalError> {
         let verifier = signer.verifying_key();
 
-        let transition_info = round.as_ref().transition_info();
+        let transition_info = round.transition_info();
 
-        let echo = round.as_ref().make_echo_broadcast(rng, &format)?;
-        let echo_broadcast = SignedMessagePart::new::<SP>(rng, &signer, &session_id, &transition_info.id(), echo)?;
+        let echo = round.make_echo_broadcast(rng, &format)?;
+        let round_sends_echo_broadcast = !echo.is_none();
+        let echo_broadcast = UnsignedMessagePart::new(&session_id, &transition_info.id(), echo);
 
-        let normal = round.as_ref().make_normal_broadcast(rng, &format)?;
-        let normal_broadcast = SignedMessagePart::new::<SP>(rng, &signer, &session_id, &transition_info.id(), normal)?;
+        let normal = round.make_normal_broadcast(rng, &format)?;
+        let normal_broadcast = UnsignedMessagePart::new(&session_id, &transition_info.id(), normal);
 
-        let communication_info = round.as_ref().communication_info();
+        let communication_info = round.communication_info();
 
-        let round_sends_echo_broadcast = !echo_broadcast.payload().is_none();
         let echo_round_info = match &communication_info.echo_round_participation {
+            // The transport guarantees the consistency of echo broadcasts by itself
+            _ if config.broadcast_consistency == BroadcastConsistency::Trusted => None,
             EchoRoundParticipation::Default => {
                 if round_sends_echo_broadcast {
                     // Add our own echo message to the expected list because we expect it to be sent back from other nodes.
@@ -199,11 +754,12 @@ where
             }),
         };
 
-        Ok(Self {
+        Ok(UnsignedSession {
             session_id,
             signer,
             verifier,
             format,
+            config,
             round,
             echo_broadcast,
             normal_broadcast,
@@ -211,6 +767,7 @@ where
             communication_info,
             echo_round_info,
             transcript,
+            piggybacked_echo: None,
         })
     }
 
@@ -224,11 +781,22 @@ where
         &self.session_id
     }
 
+    /// Returns `true` if the current round is asynchronous.
+    #[cfg(feature = "std")]
+    pub(crate) fn has_async_round(&self) -> bool {
+        self.round.is_async()
+    }
+
     /// Returns the set of message destinations for the current round.
     pub fn message_destinations(&self) -> &BTreeSet<SP::Verifier> {
         &self.communication_info.message_destinations
     }
 
+    fn is_expecting_message_from(&self, from: &SP::Verifier) -> bool {
+        self.communication_info.expecting_messages_from.contains(from)
+            || self.communication_info.optional_messages_from.contains(from)
+    }
+
     /// Creates the message to be sent to the given destination.
     ///
     /// The destination must be one of those returned by [`message_destinations`](`Self::message_destinations`).
@@ -237,21 +805,57 @@ where
         rng: &mut impl CryptoRngCore,
         destination: &SP::Verifier,
     ) -> Result<(Message<SP::Verifier>, ProcessedArtifact<SP>), LocalError> {
-        let (direct_message, artifact) = self
-            .round
-            .as_ref()
-            .make_direct_message(rng, &self.format, destination)?;
+        let (message, processed_artifact) = self.prepare_message(rng, destination)?;
+        let message = message.sign::<SP>(rng, &self.signer)?;
+        Ok((message, processed_artifact))
+    }
 
-        let message = Message::new::<SP>(
-            rng,
-            &self.signer,
+    /// Creates the messages to be sent to the given destinations,
+    /// signing them with one call to [`AsyncSessionParameters::sign_digests`].
+    ///
+    /// The destinations must be among those returned by [`message_destinations`](`Self::message_destinations`).
+    pub async fn make_messages_async(
+        &self,
+        rng: &mut (impl CryptoRngCore + Send),
+        destinations: &BTreeSet<SP::Verifier>,
+    ) -> Result<BTreeMap<SP::Verifier, (Message<SP::Verifier>, ProcessedArtifact<SP>)>, LocalError>
+    where
+        SP: AsyncSessionParameters,
+    {
+        let mut messages = Vec::with_capacity(destinations.len());
+        let mut processed_artifacts = Vec::with_capacity(destinations.len());
+        for destination in destinations {
+            let (message, processed_artifact) = self.prepare_message(rng, destination)?;
+            messages.push(message);
+            processed_artifacts.push(processed_artifact);
+        }
+        let messages = self.sign_messages_async(rng, messages).await?;
+        Ok(destinations
+            .iter()
+            .cloned()
+            .zip(messages.into_iter().zip(processed_artifacts))
+            .collect())
+    }
+
+    /// Creates the message to be sent to the given destination, without signing it.
+    pub(crate) fn prepare_message(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        destination: &SP::Verifier,
+    ) -> Result<(UnsignedMessage<SP::Verifier>, ProcessedArtifact<SP>), LocalError> {
+        let (direct_message, artifact) = self.round.make_direct_message(rng, &self.format, destination)?;
+
+        let message = UnsignedMessage::new(
             &self.session_id,
             &self.transition_info.id(),
-            destination,
+            self.config.party_encoding.encode(destination),
             direct_message,
             self.echo_broadcast.clone(),
             self.normal_broadcast.clone(),
-        )?;
+            self.piggybacked_echo
+                .as_ref()
+                .and_then(|piggybacked| piggybacked.message_for(destination)),
+        );
 
         let processed_artifact = ProcessedArtifact {
             destination: destination.clone(),
@@ -261,6 +865,36 @@ where
         Ok((message, processed_artifact))
     }
 
+    /// Signs the messages created by [`prepare_message`](`Self::prepare_message`) in one batch.
+    pub(crate) async fn sign_messages_async(
+        &self,
+        rng: &mut (impl CryptoRngCore + Send),
+        messages: Vec<UnsignedMessage<SP::Verifier>>,
+    ) -> Result<Vec<Message<SP::Verifier>>, LocalError>
+    where
+        SP: AsyncSessionParameters,
+    {
+        let digests = messages
+            .iter()
+            .map(|message| message.digest::<SP>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let signatures = SP::sign_digests(&self.signer, rng, digests)
+            .await
+            .map_err(|err| LocalError::new_with_kind(LocalErrorKind::Signing, format!("Failed to sign: {:?}", err)))?;
+        if signatures.len() != messages.len() {
+            return Err(LocalError::new(format!(
+                "Expected {} signatures from the signer, got {}",
+                messages.len(),
+                signatures.len()
+            )));
+        }
+        messages
+            .into_iter()
+            .zip(signatures)
+            .map(|(message, signature)| message.with_signature::<SP>(signature))
+            .collect()
+    }
+
     /// Adds the artifact from [`make_message`](`Self::make_message`) to the accumulator.
     pub fn add_artifact(
         &self,
@@ -298,80 +932,176 @@ where
         let key = self.verifier();
         if self.transcript.is_banned(from) || accum.is_banned(from) {
             trace!("[{key:?}] Banned.");
-            return Ok(PreprocessOutcome::remote_error("The sender is banned"));
+            return Ok(PreprocessOutcome::remote_error(
+                RemoteErrorKind::BannedSender,
+                "The sender is banned",
+            ));
+        }
+
+        // This is the round ID of the direct message part, which is checked to be the same for all the parts below
+        let message_round_id = message.round_id().clone();
+
+        // Check the limits before doing anything expensive with the message
+        let limits = &self.config.message_limits;
+        let message_size = message.part_sizes().fold(0usize, usize::saturating_add);
+        if let Err(err) = limits.check_part_sizes(message.part_sizes()) {
+            let err = RemoteError::new_with_kind(RemoteErrorKind::LimitExceeded, err);
+            accum.register_unprovable_error(from, &message_round_id, err.clone());
+            trace!("[{key:?}] {err}");
+            return Ok(PreprocessOutcome::Error(err));
         }
 
         let checked_message = match message.unify_metadata() {
             Some(checked_message) => checked_message,
             None => {
-                let err = "Mismatched metadata in bundled messages.";
-                accum.register_unprovable_error(from, RemoteError::new(err))?;
+                let err = RemoteError::new_with_kind(
+                    RemoteErrorKind::MismatchedMetadata,
+                    "Mismatched metadata in bundled messages.",
+                );
+                accum.register_unprovable_error(from, &message_round_id, err.clone());
                 trace!("[{key:?}] {err}");
-                return Ok(PreprocessOutcome::remote_error(err));
+                return Ok(PreprocessOutcome::Error(err));
             }
         };
-        let message_round_id = checked_message.metadata().round_id().clone();
 
         if checked_message.metadata().session_id() != &self.session_id {
-            let err = "The received message has an incorrect session ID";
-            accum.register_unprovable_error(from, RemoteError::new(err))?;
+            let err = RemoteError::new_with_kind(
+                RemoteErrorKind::WrongSession,
+                "The received message has an incorrect session ID",
+            );
+            accum.register_unprovable_error(from, &message_round_id, err.clone());
             trace!("[{key:?}] {err}");
-            return Ok(PreprocessOutcome::remote_error(err));
+            return Ok(PreprocessOutcome::Error(err));
         }
 
         enum MessageFor {
             ThisRound,
             SimultaneousRound,
+            // A message for the same round was already accepted from this sender.
+            AlreadyAccepted(MessageFingerprint),
         }
 
-        let acceptable_round_ids = self
-            .transition_info
-            .simultaneous_rounds(self.echo_round_info.is_some())?;
+        let acceptable_round_ids = self.acceptable_round_ids()?;
 
         let message_for = if message_round_id == self.round_id() {
-            if accum.message_is_being_processed(from) {
-                let err = "Message from this party is already being processed";
-                accum.register_unprovable_error(from, RemoteError::new(err))?;
+            if !accum.is_expecting_message_from(from) {
+                let err = "The sender is not expected to send messages in this round";
                 trace!("[{key:?}] {err}");
-                return Ok(PreprocessOutcome::remote_error(err));
+                return Ok(PreprocessOutcome::remote_error(RemoteErrorKind::UnexpectedSender, err));
             }
-            MessageFor::ThisRound
+            match accum.message_being_processed(from) {
+                Some(fingerprint) => MessageFor::AlreadyAccepted(fingerprint.clone()),
+                None => MessageFor::ThisRound,
+            }
+        } else if let Some(fingerprint) = self.transcript.get_accepted_message(&message_round_id, from) {
+            // Checked before the simultaneous rounds, since they include the parent round during an echo round.
+            MessageFor::AlreadyAccepted(fingerprint.clone())
         } else if acceptable_round_ids.contains(&message_round_id) {
-            if accum.message_is_cached(from, &message_round_id) {
-                let err = format!("Message for {message_round_id:?} is already cached");
-                accum.register_unprovable_error(from, RemoteError::new(&err))?;
-                trace!("[{key:?}] {err}");
-                return Ok(PreprocessOutcome::remote_error(err));
+            match accum
+                .cached_message(from, &message_round_id)
+                .or_else(|| self.cached_message(from, &message_round_id))
+            {
+                Some(message) => MessageFor::AlreadyAccepted(message.fingerprint::<SP>()?),
+                None => {
+                    let cached_messages = accum.cached_messages_num(from) + self.cached_messages_num(from) + 1;
+                    if let Err(err) = limits.check_cached_messages(cached_messages) {
+                        let err = RemoteError::new_with_kind(RemoteErrorKind::LimitExceeded, err);
+                        accum.register_unprovable_error(from, &message_round_id, err.clone());
+                        trace!("[{key:?}] {err}");
+                        return Ok(PreprocessOutcome::Error(err));
+                    }
+                    MessageFor::SimultaneousRound
+                }
             }
-            MessageFor::SimultaneousRound
+        } else if self.transcript.is_silent_optional_sender(&message_round_id, from) {
+            // The round was finalized without waiting for the optional message.
+            let err = format!("Late optional message for {message_round_id:?}");
+            trace!("[{key:?}] {err}");
+            return Ok(PreprocessOutcome::remote_error(RemoteErrorKind::LateMessage, err));
+        } else if matches!(message_round_id.kind(), RoundKind::BrachaEcho | RoundKind::BrachaReady) {
+            // The Bracha rounds are finalized without waiting for all the messages,
+            // so the remaining ones may arrive later. This is not the sender's fault.
+            let err = format!("Late message for {message_round_id:?}");
+            trace!("[{key:?}] {err}");
+            return Ok(PreprocessOutcome::remote_error(RemoteErrorKind::LateMessage, err));
         } else {
-            let err = format!("Unexpected message round ID: {message_round_id:?}");
-            accum.register_unprovable_error(from, RemoteError::new(&err))?;
+            let err = RemoteError::new_with_kind(
+                RemoteErrorKind::UnexpectedRound,
+                format!("Unexpected message round ID: {message_round_id:?}"),
+            );
+            accum.register_unprovable_error(from, &message_round_id, err.clone());
             trace!("[{key:?}] {err}");
-            return Ok(PreprocessOutcome::remote_error(err));
+            return Ok(PreprocessOutcome::Error(err));
         };
 
+        if let MessageFor::AlreadyAccepted(earlier) = &message_for {
+            if checked_message.fingerprint::<SP>()?.is_same_message(earlier) {
+                // A redelivery of the same message, which is normal for at-least-once transports.
+                trace!("[{key:?}] Ignoring a duplicate {message_round_id} message from {from:?}");
+                return Ok(PreprocessOutcome::Duplicate);
+            }
+        }
+
+        // Only count the message towards the sender's quota once we know it is not a redelivery,
+        // so that honest senders on at-least-once transports are not penalized.
+        let bytes_received = accum.add_bytes_received(from, message_size);
+        if let Err(err) = limits.check_bytes_per_sender(bytes_received) {
+            let err = RemoteError::new_with_kind(RemoteErrorKind::LimitExceeded, err);
+            accum.register_unprovable_error(from, &message_round_id, err.clone());
+            trace!("[{key:?}] {err}");
+            return Ok(PreprocessOutcome::Error(err));
+        }
+
         // Verify the signature now
 
         let verified_message = match checked_message.verify::<SP>(from) {
             Ok(verified_message) => verified_message,
             Err(MessageVerificationError::InvalidSignature) => {
-                let err = "The signature could not be deserialized.";
-                accum.register_unprovable_error(from, RemoteError::new(err))?;
+                let err = RemoteError::new_with_kind(
+                    RemoteErrorKind::InvalidSignature,
+                    "The signature could not be deserialized.",
+                );
+                accum.register_unprovable_error(from, &message_round_id, err.clone());
                 trace!("{key:?} {err}");
-                return Ok(PreprocessOutcome::remote_error(err));
+                return Ok(PreprocessOutcome::Error(err));
             }
             Err(MessageVerificationError::SignatureMismatch) => {
-                let err = "Message verification failed.";
-                accum.register_unprovable_error(from, RemoteError::new(err))?;
+                let err =
+                    RemoteError::new_with_kind(RemoteErrorKind::SignatureMismatch, "Message verification failed.");
+                accum.register_unprovable_error(from, &message_round_id, err.clone());
                 trace!("[{key:?}] {err}");
-                return Ok(PreprocessOutcome::remote_error(err));
+                return Ok(PreprocessOutcome::Error(err));
             }
             Err(MessageVerificationError::Local(error)) => return Err(error),
         };
         debug!("[{key:?}] Received {message_round_id} message from {from:?}");
 
         match message_for {
+            MessageFor::AlreadyAccepted(earlier) => {
+                // Different signed contents for the same round.
+                // If the echo broadcasts differ, which are supposed to be the same for all the nodes,
+                // the equivocation can be proven to others.
+                let (echo_broadcast, _normal_broadcast, _direct_message) = verified_message.into_parts();
+                let earlier_echo_broadcast = earlier.echo_broadcast();
+                if echo_broadcast.to_signed_hash::<SP>().content_digest::<SP>()?
+                    != earlier_echo_broadcast.content_digest::<SP>()?
+                {
+                    let evidence =
+                        Evidence::new_conflicting_echo_broadcasts(from, echo_broadcast, earlier_echo_broadcast.clone());
+                    let err = RemoteError::new_with_kind(RemoteErrorKind::ConflictingMessages, evidence.description());
+                    accum.register_provable_error(&message_round_id, evidence);
+                    trace!("[{key:?}] {err}");
+                    Ok(PreprocessOutcome::Error(err))
+                } else {
+                    let err = RemoteError::new_with_kind(
+                        RemoteErrorKind::ConflictingMessages,
+                        format!("Received conflicting messages for {message_round_id:?}"),
+                    );
+                    accum.register_unprovable_error(from, &message_round_id, err.clone());
+                    trace!("[{key:?}] {err}");
+                    Ok(PreprocessOutcome::Error(err))
+                }
+            }
             MessageFor::ThisRound => {
                 accum.mark_processing(&verified_message)?;
                 Ok(PreprocessOutcome::ToProcess(Box::new(verified_message)))
@@ -387,33 +1117,104 @@ where
     /// Processes a verified message.
     ///
     /// This can be called in a spawned task if it is known to take a long time.
+    ///
+    /// Fails (with the error recorded in the returned `ProcessedMessage`) if the round is asynchronous;
+    /// use [`process_message_async`](`Self::process_message_async`) for such rounds.
     pub fn process_message(&self, message: VerifiedMessage<SP::Verifier>) -> ProcessedMessage<P, SP> {
-        let protocol_message = ProtocolMessage {
-            echo_broadcast: message.echo_broadcast().clone(),
-            normal_broadcast: message.normal_broadcast().clone(),
-            direct_message: message.direct_message().clone(),
-        };
-        let processed = self
-            .round
-            .as_ref()
-            .receive_message(&self.format, message.from(), protocol_message);
+        let processed = self.protocol_message(&message).and_then(|protocol_message| {
+            self.round
+                .receive_message(&self.format, message.from(), protocol_message)
+        });
         // We could filter out and return a possible `LocalError` at this stage,
         // but it's no harm in delaying it until `ProcessedMessage` is added to the accumulator.
         ProcessedMessage { message, processed }
     }
 
+    /// Processes a verified message with a round that may be asynchronous.
+    ///
+    /// Synchronous rounds process the message the same way as in [`process_message`](`Self::process_message`).
+    pub async fn process_message_async(&self, message: VerifiedMessage<SP::Verifier>) -> ProcessedMessage<P, SP> {
+        let processed = match self.protocol_message(&message) {
+            Ok(protocol_message) => {
+                self.round
+                    .receive_message_async(&self.format, message.from(), protocol_message)
+                    .await
+            }
+            Err(error) => Err(error),
+        };
+        ProcessedMessage { message, processed }
+    }
+
+    /// Checks the attached echo round message, if any, and extracts the part of the message
+    /// to be processed by the round.
+    fn protocol_message(
+        &self,
+        message: &VerifiedMessage<SP::Verifier>,
+    ) -> Result<ProtocolMessage, ReceiveError<SP::Verifier, P>> {
+        match &self.piggybacked_echo {
+            Some(piggybacked) => piggybacked.receive(&self.format, message.from(), message.echo_round_message())?,
+            None if message.echo_round_message().is_some() => {
+                return Err(ReceiveError::unprovable_with_kind(
+                    RemoteErrorKind::InvalidAttachedEchoRoundMessage,
+                    "Unexpected attached echo round message",
+                ))
+            }
+            None => {}
+        };
+
+        Ok(ProtocolMessage {
+            echo_broadcast: message.echo_broadcast().clone(),
+            normal_broadcast: message.normal_broadcast().clone(),
+            direct_message: message.direct_message().clone(),
+        })
+    }
+
     /// Adds a result of [`process_message`](`Self::process_message`) to the accumulator.
     pub fn add_processed_message(
         &self,
         accum: &mut RoundAccumulator<P, SP>,
         processed: ProcessedMessage<P, SP>,
     ) -> Result<(), LocalError> {
-        accum.add_processed_message(&self.transcript, processed)
+        accum.add_processed_message(&self.transcript, &self.config, processed)
     }
 
     /// Makes an accumulator for a new round.
     pub fn make_accumulator(&self) -> RoundAccumulator<P, SP> {
-        RoundAccumulator::new(&self.communication_info.expecting_messages_from)
+        let expecting_messages_from = &self.communication_info.expecting_messages_from;
+        let optional_messages_from = self
+            .communication_info
+            .optional_messages_from
+            .difference(expecting_messages_from)
+            .cloned()
+            .collect();
+        let min_messages = bracha::min_messages(&self.round_id(), expecting_messages_from.len());
+        RoundAccumulator::new(
+            expecting_messages_from,
+            optional_messages_from,
+            min_messages,
+            self.current_cached.clone(),
+            self.config.ban_policy.clone(),
+            self.transcript.error_counts().clone(),
+            self.transcript.bytes_received().clone(),
+        )
+    }
+
+    /// Returns the IDs of the rounds other than the current one the messages can be received for.
+    fn acceptable_round_ids(&self) -> Result<BTreeSet<RoundId>, LocalError> {
+        let echo_round_ids = if self.echo_round_info.is_some() {
+            self.config.echo_round_ids(&self.round_id())?
+        } else {
+            BTreeSet::new()
+        };
+        Ok(self.transition_info.simultaneous_rounds(echo_round_ids))
+    }
+
+    fn cached_message(&self, from: &SP::Verifier, round_id: &RoundId) -> Option<&VerifiedMessage<SP::Verifier>> {
+        self.cached.get(from).and_then(|messages| messages.get(round_id))
+    }
+
+    fn cached_messages_num(&self, from: &SP::Verifier) -> usize {
+        self.cached.get(from).map_or(0, |messages| messages.len())
     }
 
     fn terminate_inner(
@@ -432,7 +1233,12 @@ where
             accum.provable_errors,
             accum.unprovable_errors,
             accum.still_have_not_sent_messages,
+            accum.offenses,
         )?;
+        let transcript = match &self.piggybacked_echo {
+            Some(piggybacked) => transcript.add_normal_broadcasts(piggybacked.round_id(), accum.echo_round_messages)?,
+            None => transcript,
+        };
         let outcome = if not_enough_messages {
             SessionOutcome::NotEnoughMessages
         } else {
@@ -456,11 +1262,67 @@ where
     }
 
     /// Attempts to finalize the current round.
+    ///
+    /// Fails if the round is asynchronous; such rounds must be finalized
+    /// with [`finalize_round_async`](`Self::finalize_round_async`).
     pub fn finalize_round(
         self,
         rng: &mut impl CryptoRngCore,
         accum: RoundAccumulator<P, SP>,
     ) -> Result<RoundOutcome<P, SP>, LocalError> {
+        match self.start_finalization(rng, accum)? {
+            FinalizationStep::Done(outcome) => outcome,
+            FinalizationStep::FinalizeRound {
+                finalization,
+                round,
+                payloads,
+                artifacts,
+            } => {
+                let outcome = round.finalize(rng, payloads, artifacts)?;
+                finalization.complete(rng, outcome)?
+            }
+        }
+        .sign(rng)
+    }
+
+    /// Attempts to finalize the current round, which may be asynchronous.
+    ///
+    /// Synchronous rounds are finalized the same way as in [`finalize_round`](`Self::finalize_round`).
+    pub async fn finalize_round_async(
+        self,
+        rng: &mut (impl CryptoRngCore + Send),
+        accum: RoundAccumulator<P, SP>,
+    ) -> Result<RoundOutcome<P, SP>, LocalError>
+    where
+        SP: AsyncSessionParameters,
+    {
+        match self.start_finalization(rng, accum)? {
+            FinalizationStep::Done(outcome) => outcome,
+            FinalizationStep::FinalizeRound {
+                finalization,
+                round,
+                payloads,
+                artifacts,
+            } => {
+                // An echo round finalizes the main round synchronously,
+                // so if the main round is asynchronous, its finalization is deferred until this point.
+                let outcome = match round.finalize_async(rng, payloads, artifacts).await? {
+                    FinalizeOutcome::AnotherRound(round) if round.is_deferred() => round.finalize_deferred(rng).await?,
+                    outcome => outcome,
+                };
+                finalization.complete(rng, outcome)?
+            }
+        }
+        .sign_async(rng)
+        .await
+    }
+
+    /// Performs the part of the round finalization preceding the call to the round's `finalize()`.
+    fn start_finalization(
+        self,
+        rng: &mut impl CryptoRngCore,
+        accum: RoundAccumulator<P, SP>,
+    ) -> Result<FinalizationStep<P, SP>, LocalError> {
         let verifier = self.verifier().clone();
         let round_id = self.round_id();
 
@@ -473,61 +1335,186 @@ where
             accum.provable_errors,
             accum.unprovable_errors,
             accum.still_have_not_sent_messages,
+            accum.offenses,
         )?;
+        let transcript = match &self.piggybacked_echo {
+            Some(piggybacked) => transcript.add_normal_broadcasts(piggybacked.round_id(), accum.echo_round_messages)?,
+            None => transcript,
+        };
+        let transcript =
+            transcript.add_silent_optional_senders(&round_id, accum.still_have_not_sent_optional_messages)?;
+        let transcript = transcript.add_accepted_messages(&round_id, accum.processing)?;
+        let transcript = transcript
+            .with_error_counts(accum.error_counts)
+            .with_bytes_received(accum.bytes_received);
+
+        let mut cached = self.cached;
+        for (from, messages) in accum.cached {
+            cached.entry(from).or_default().extend(messages);
+        }
+
+        let mut finalization = RoundFinalization {
+            session_id: self.session_id,
+            signer: self.signer,
+            format: self.format,
+            config: self.config,
+            transition_info: self.transition_info,
+            communication_info: self.communication_info,
+            transcript,
+            cached,
+            piggybacked_echo: None,
+        };
 
-        if let Some(echo_round_info) = self.echo_round_info {
-            let round = BoxedRound::new_dynamic(EchoRound::<P, SP>::new(
+        let echo_round_info = match self.echo_round_info {
+            Some(echo_round_info) => echo_round_info,
+            None => {
+                return Ok(FinalizationStep::FinalizeRound {
+                    finalization,
+                    round: self.round,
+                    payloads: accum.payloads,
+                    artifacts: accum.artifacts,
+                })
+            }
+        };
+
+        // Other nodes may have finalized the round without the optional messages we received,
+        // so the echo broadcasts from optional senders are not echoed.
+        let mut echo_broadcasts = finalization.transcript.echo_broadcasts(&round_id)?;
+        echo_broadcasts.retain(|id, _echo_broadcast| !accum.optional_messages_from.contains(id));
+        let party_encoding = finalization.config.party_encoding.clone();
+        let round = match finalization.config.broadcast_consistency {
+            BroadcastConsistency::Echo | BroadcastConsistency::Bracha => {
+                let echo = EchoState::new(verifier, echo_broadcasts, echo_round_info, party_encoding)?;
+                BoxedRound::new_dynamic(EchoRound::<P, SP>::new(
+                    finalization.config.broadcast_consistency,
+                    echo,
+                    self.round,
+                    accum.payloads,
+                    accum.artifacts,
+                ))
+            }
+            // There is no next round to attach the echo round messages to
+            BroadcastConsistency::PiggybackedEcho if finalization.transition_info.may_produce_result => {
+                let echo = EchoState::new(verifier, echo_broadcasts, echo_round_info, party_encoding)?;
+                BoxedRound::new_dynamic(EchoRound::<P, SP>::new(
+                    BroadcastConsistency::Echo,
+                    echo,
+                    self.round,
+                    accum.payloads,
+                    accum.artifacts,
+                ))
+            }
+            BroadcastConsistency::PiggybackedEcho => {
+                let echo = EchoState::new(verifier, echo_broadcasts, echo_round_info, party_encoding)?;
+                finalization.piggybacked_echo = Some(echo);
+                return Ok(FinalizationStep::FinalizeRound {
+                    finalization,
+                    round: self.round,
+                    payloads: accum.payloads,
+                    artifacts: accum.artifacts,
+                });
+            }
+            BroadcastConsistency::MerkleEcho => BoxedRound::new_dynamic(MerkleEchoRound::<P, SP>::new(
                 verifier,
-                transcript.echo_broadcasts(&round_id)?,
+                echo_broadcasts,
                 echo_round_info,
+                party_encoding,
                 self.round,
                 accum.payloads,
                 accum.artifacts,
-            ));
-            let cached_messages = filter_messages(accum.cached, &round.id());
-            let session =
-                Session::new_for_next_round(rng, self.session_id, self.signer, self.format, round, transcript)?;
-            return Ok(RoundOutcome::AnotherRound {
-                session,
-                cached_messages,
-            });
-        }
+            )?),
+            BroadcastConsistency::Trusted => {
+                return Err(LocalError::new(
+                    "Echo rounds are not used with a trusted broadcast channel",
+                ))
+            }
+        };
+        finalization
+            .into_echo_round_outcome(rng, round)
+            .map(FinalizationStep::Done)
+    }
 
-        match self.round.into_boxed().finalize(rng, accum.payloads, accum.artifacts)? {
-            FinalizeOutcome::Result(result) => Ok(RoundOutcome::Finished(SessionReport::new(
-                SessionOutcome::Result(result),
-                transcript,
-            ))),
-            FinalizeOutcome::AnotherRound(round) => {
-                let round_id = round.as_ref().transition_info().id();
-                // Protecting against common bugs
-                if !self.transition_info.children.contains(&round_id) {
-                    return Err(LocalError::new(format!("Unexpected next round id: {:?}", round_id)));
-                }
+    /// Returns the outcome of transitioning into this session's round,
+    /// along with the given cached messages intended for it.
+    fn into_next_round_outcome(
+        mut self,
+        cached: BTreeMap<SP::Verifier, BTreeMap<RoundId, VerifiedMessage<SP::Verifier>>>,
+        is_echo_round: bool,
+    ) -> Result<RoundOutcome<P, SP>, LocalError> {
+        // These messages could have been cached before
+        // processing messages from the same node for the current round.
+        // So there might have been some new errors, and we need to check again
+        // if the sender is already banned (unless this is an echo round).
+        // The cached messages could also come from nodes that the new round does not expect messages from.
+        let cached_messages = self
+            .take_cached(cached)?
+            .into_iter()
+            .filter(|message| {
+                (is_echo_round || !self.transcript.is_banned(message.from()))
+                    && self.is_expecting_message_from(message.from())
+            })
+            .collect::<Vec<_>>();
+        // The cached messages will be processed in this round, so their redeliveries must be recognized.
+        self.current_cached = cached_messages
+            .iter()
+            .map(|message| Ok((message.from().clone(), message.fingerprint::<SP>()?)))
+            .collect::<Result<_, LocalError>>()?;
+        Ok(RoundOutcome::AnotherRound {
+            cached_messages,
+            session: self,
+        })
+    }
 
-                // These messages could have been cached before
-                // processing messages from the same node for the current round.
-                // So there might have been some new errors, and we need to check again
-                // if the sender is already banned.
-                let cached_messages = filter_messages(accum.cached, &round_id)
-                    .into_iter()
-                    .filter(|message| !transcript.is_banned(message.from()))
-                    .collect::<Vec<_>>();
-
-                let session =
-                    Session::new_for_next_round(rng, self.session_id, self.signer, self.format, round, transcript)?;
-                Ok(RoundOutcome::AnotherRound {
-                    cached_messages,
-                    session,
-                })
+    /// Keeps the given cached messages that may be needed in the rounds after the current one,
+    /// and returns the ones intended for the current round.
+    fn take_cached(
+        &mut self,
+        cached: BTreeMap<SP::Verifier, BTreeMap<RoundId, VerifiedMessage<SP::Verifier>>>,
+    ) -> Result<Vec<VerifiedMessage<SP::Verifier>>, LocalError> {
+        let round_id = self.round_id();
+        let acceptable_round_ids = self.acceptable_round_ids()?;
+        let mut current = Vec::new();
+        for (from, messages) in cached {
+            for (message_round_id, message) in messages {
+                if message_round_id == round_id {
+                    current.push(message);
+                } else if acceptable_round_ids.contains(&message_round_id) {
+                    self.cached
+                        .entry(from.clone())
+                        .or_default()
+                        .insert(message_round_id, message);
+                }
             }
         }
+        Ok(current)
     }
 
     /// Checks if the round can be finalized.
     pub fn can_finalize(&self, accum: &RoundAccumulator<P, SP>) -> CanFinalize {
         accum.can_finalize()
     }
+
+    /// Returns the parties whose messages are required to finalize the current round,
+    /// but have not been received yet.
+    ///
+    /// If these messages seem to be lost, the parties can be sent a [`ResendRequest`]
+    /// created with [`make_resend_request`](`Self::make_resend_request`).
+    pub fn missing_messages(&self, accum: &RoundAccumulator<P, SP>) -> BTreeSet<SP::Verifier> {
+        accum
+            .missing_messages()
+            .filter(|id| !self.transcript.is_banned(id))
+            .cloned()
+            .collect()
+    }
+
+    /// Creates a request to `from` to resend its message for the current round.
+    pub fn make_resend_request(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        from: &SP::Verifier,
+    ) -> Result<ResendRequest<SP>, LocalError> {
+        ResendRequest::new(rng, &self.signer, &self.session_id, &self.round_id(), from)
+    }
 }
 
 /// Possible answers to whether the round can be finalized.
@@ -547,15 +1534,26 @@ pub enum CanFinalize {
 pub struct RoundAccumulator<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     still_have_not_sent_messages: BTreeSet<SP::Verifier>,
     expecting_messages_from: BTreeSet<SP::Verifier>,
-    processing: BTreeSet<SP::Verifier>,
+    still_have_not_sent_optional_messages: BTreeSet<SP::Verifier>,
+    optional_messages_from: BTreeSet<SP::Verifier>,
+    min_messages: Option<usize>,
+    // The messages for this round accepted for processing.
+    processing: BTreeMap<SP::Verifier, MessageFingerprint>,
     payloads: BTreeMap<SP::Verifier, Payload>,
     artifacts: BTreeMap<SP::Verifier, Artifact>,
     cached: BTreeMap<SP::Verifier, BTreeMap<RoundId, VerifiedMessage<SP::Verifier>>>,
+    // The total size of the messages received from each sender during the session so far.
+    bytes_received: BTreeMap<SP::Verifier, usize>,
     echo_broadcasts: BTreeMap<SP::Verifier, SignedMessagePart<EchoBroadcast>>,
     normal_broadcasts: BTreeMap<SP::Verifier, SignedMessagePart<NormalBroadcast>>,
     direct_messages: BTreeMap<SP::Verifier, SignedMessagePart<DirectMessage>>,
+    echo_round_messages: BTreeMap<SP::Verifier, SignedMessagePart<NormalBroadcast>>,
     provable_errors: BTreeMap<SP::Verifier, Evidence<P, SP>>,
     unprovable_errors: BTreeMap<SP::Verifier, RemoteError>,
+    offenses: BTreeMap<SP::Verifier, Vec<Offense<P, SP>>>,
+    ban_policy: Arc<dyn BanPolicy<SP::Verifier>>,
+    // The number of errors counted against each sender's budget during the session so far.
+    error_counts: BTreeMap<SP::Verifier, usize>,
 }
 
 impl<P, SP> RoundAccumulator<P, SP>
@@ -563,29 +1561,65 @@ where
     P: Protocol<SP::Verifier>,
     SP: SessionParameters,
 {
-    fn new(expecting_messages_from: &BTreeSet<SP::Verifier>) -> Self {
+    fn new(
+        expecting_messages_from: &BTreeSet<SP::Verifier>,
+        optional_messages_from: BTreeSet<SP::Verifier>,
+        min_messages: Option<usize>,
+        // The cached messages handed out to be processed in this round.
+        processing: BTreeMap<SP::Verifier, MessageFingerprint>,
+        ban_policy: Arc<dyn BanPolicy<SP::Verifier>>,
+        error_counts: BTreeMap<SP::Verifier, usize>,
+        bytes_received: BTreeMap<SP::Verifier, usize>,
+    ) -> Self {
         Self {
             still_have_not_sent_messages: expecting_messages_from.clone(),
             expecting_messages_from: expecting_messages_from.clone(),
-            processing: BTreeSet::new(),
+            still_have_not_sent_optional_messages: optional_messages_from.clone(),
+            optional_messages_from,
+            min_messages,
+            processing,
             payloads: BTreeMap::new(),
             artifacts: BTreeMap::new(),
             cached: BTreeMap::new(),
+            bytes_received,
             echo_broadcasts: BTreeMap::new(),
             normal_broadcasts: BTreeMap::new(),
             direct_messages: BTreeMap::new(),
+            echo_round_messages: BTreeMap::new(),
             provable_errors: BTreeMap::new(),
             unprovable_errors: BTreeMap::new(),
+            offenses: BTreeMap::new(),
+            ban_policy,
+            error_counts,
         }
     }
 
     fn can_finalize(&self) -> CanFinalize {
+        if let Some(min_messages) = self.min_messages {
+            return if self.payloads.len() >= min_messages {
+                CanFinalize::Yes
+            } else if self.payloads.len() + self.still_have_not_sent_messages.len() >= min_messages {
+                CanFinalize::NotYet
+            } else {
+                CanFinalize::Never
+            };
+        }
+
         if self
             .expecting_messages_from
             .iter()
             .all(|key| self.payloads.contains_key(key))
         {
-            CanFinalize::Yes
+            // Optional messages are not waited for, unless they are already being processed
+            if self
+                .still_have_not_sent_optional_messages
+                .iter()
+                .any(|key| self.processing.contains_key(key))
+            {
+                CanFinalize::NotYet
+            } else {
+                CanFinalize::Yes
+            }
         } else if !self.still_have_not_sent_messages.is_empty() {
             CanFinalize::NotYet
         } else {
@@ -597,42 +1631,88 @@ where
         self.provable_errors.contains_key(from) || self.unprovable_errors.contains_key(from)
     }
 
-    fn message_is_being_processed(&self, from: &SP::Verifier) -> bool {
-        self.processing.contains(from)
+    fn missing_messages(&self) -> impl Iterator<Item = &SP::Verifier> {
+        self.still_have_not_sent_messages
+            .iter()
+            .filter(|id| !self.processing.contains_key(*id) && !self.is_banned(id))
     }
 
-    fn message_is_cached(&self, from: &SP::Verifier, round_id: &RoundId) -> bool {
-        if let Some(entry) = self.cached.get(from) {
-            entry.contains_key(round_id)
-        } else {
-            false
-        }
+    fn is_expecting_message_from(&self, from: &SP::Verifier) -> bool {
+        self.expecting_messages_from.contains(from) || self.optional_messages_from.contains(from)
     }
 
-    fn register_unprovable_error(&mut self, from: &SP::Verifier, error: RemoteError) -> Result<(), LocalError> {
-        if self.unprovable_errors.insert(from.clone(), error).is_some() {
-            Err(LocalError::new(format!(
-                "An unprovable error for {:?} is already registered",
-                from
-            )))
-        } else {
-            Ok(())
+    fn message_being_processed(&self, from: &SP::Verifier) -> Option<&MessageFingerprint> {
+        self.processing.get(from)
+    }
+
+    fn cached_message(&self, from: &SP::Verifier, round_id: &RoundId) -> Option<&VerifiedMessage<SP::Verifier>> {
+        self.cached.get(from).and_then(|messages| messages.get(round_id))
+    }
+
+    fn cached_messages_num(&self, from: &SP::Verifier) -> usize {
+        self.cached.get(from).map_or(0, |messages| messages.len())
+    }
+
+    // Records the size of a message received from `from`,
+    // returning the total size of the messages received from it during the session so far.
+    fn add_bytes_received(&mut self, from: &SP::Verifier, size: usize) -> usize {
+        let bytes_received = self.bytes_received.entry(from.clone()).or_default();
+        *bytes_received = bytes_received.saturating_add(size);
+        *bytes_received
+    }
+
+    // Applies the ban policy to an unprovable error caused by `from`,
+    // returning the error if `from` is to be banned.
+    fn apply_ban_policy(&mut self, from: &SP::Verifier, error: RemoteError) -> Option<RemoteError> {
+        match self.ban_policy.classify(from, &error) {
+            BanDecision::Ignore => {
+                debug!("Ignoring an error caused by {from:?}: {error}");
+                None
+            }
+            BanDecision::Count => {
+                let count = self.error_counts.entry(from.clone()).or_default();
+                *count += 1;
+                if *count > self.ban_policy.error_budget() {
+                    Some(error)
+                } else {
+                    debug!("Counting an error caused by {from:?} ({count} so far): {error}");
+                    None
+                }
+            }
+            BanDecision::Ban => Some(error),
         }
     }
 
-    fn register_provable_error(&mut self, from: &SP::Verifier, evidence: Evidence<P, SP>) -> Result<(), LocalError> {
-        if self.provable_errors.insert(from.clone(), evidence).is_some() {
-            Err(LocalError::new(format!(
-                "A provable error for {:?} is already registered",
-                from
-            )))
-        } else {
-            Ok(())
+    // Every offense is recorded, but only the first error of a party (the one that got it banned) is kept
+    // in `provable_errors` or `unprovable_errors`.
+    // There may be more than one if a message from the party was still being processed when it got banned.
+    fn register_unprovable_error(&mut self, from: &SP::Verifier, round_id: &RoundId, error: RemoteError) {
+        self.offenses
+            .entry(from.clone())
+            .or_default()
+            .push(Offense::unprovable(round_id, &error));
+        if let Some(error) = self.apply_ban_policy(from, error) {
+            self.unprovable_errors.entry(from.clone()).or_insert(error);
         }
     }
 
+    // The evidence is registered against its guilty party, which is not necessarily the sender of the message
+    // (e.g. an echo round message may prove that someone else sent different echo broadcasts).
+    fn register_provable_error(&mut self, round_id: &RoundId, evidence: Evidence<P, SP>) {
+        let guilty_party = evidence.guilty_party().clone();
+        self.offenses
+            .entry(guilty_party.clone())
+            .or_default()
+            .push(Offense::provable(round_id, &evidence));
+        self.provable_errors.entry(guilty_party).or_insert(evidence);
+    }
+
     fn mark_processing(&mut self, message: &VerifiedMessage<SP::Verifier>) -> Result<(), LocalError> {
-        if !self.processing.insert(message.from().clone()) {
+        if self
+            .processing
+            .insert(message.from().clone(), message.fingerprint::<SP>()?)
+            .is_some()
+        {
             Err(LocalError::new(format!(
                 "A message from {:?} is already marked as being processed",
                 message.from()
@@ -662,6 +1742,7 @@ where
     fn add_processed_message(
         &mut self,
         transcript: &Transcript<P, SP>,
+        config: &SessionConfig<SP::Verifier>,
         processed: ProcessedMessage<P, SP>,
     ) -> Result<(), LocalError> {
         if self.payloads.contains_key(processed.message.from()) {
@@ -672,8 +1753,12 @@ where
         }
 
         let from = processed.message.from().clone();
+        let message_round_id = processed.message.metadata().round_id().clone();
+        let mut message = processed.message;
+        let echo_round_message = message.take_echo_round_message();
 
-        if !self.still_have_not_sent_messages.remove(&from) {
+        if !self.still_have_not_sent_messages.remove(&from) && !self.still_have_not_sent_optional_messages.remove(&from)
+        {
             return Err(LocalError::new(format!(
                 "Expected {:?} to not be in the list of expected messages",
                 from
@@ -682,7 +1767,7 @@ where
         let error = match processed.processed {
             Ok(payload) => {
                 // Note: only inserting the messages if they actually have a payload
-                let (echo_broadcast, normal_broadcast, direct_message) = processed.message.into_parts();
+                let (echo_broadcast, normal_broadcast, direct_message) = message.into_parts();
                 if !echo_broadcast.payload().is_none() {
                     self.echo_broadcasts.insert(from.clone(), echo_broadcast);
                 }
@@ -692,6 +1777,9 @@ where
                 if !direct_message.payload().is_none() {
                     self.direct_messages.insert(from.clone(), direct_message);
                 }
+                if let Some(echo_round_message) = echo_round_message {
+                    self.echo_round_messages.insert(from.clone(), echo_round_message);
+                }
                 self.payloads.insert(from.clone(), payload);
                 return Ok(());
             }
@@ -700,41 +1788,85 @@ where
 
         match error.0 {
             ReceiveErrorType::InvalidDirectMessage(error) => {
-                let (_echo_broadcast, _normal_broadcast, direct_message) = processed.message.into_parts();
+                let (_echo_broadcast, _normal_broadcast, direct_message) = message.into_parts();
                 let evidence = Evidence::new_invalid_direct_message(&from, direct_message, error);
-                self.register_provable_error(&from, evidence)
+                self.register_provable_error(&message_round_id, evidence);
+                Ok(())
             }
             ReceiveErrorType::InvalidEchoBroadcast(error) => {
-                let (echo_broadcast, _normal_broadcast, _direct_message) = processed.message.into_parts();
+                let (echo_broadcast, _normal_broadcast, _direct_message) = message.into_parts();
                 let evidence = Evidence::new_invalid_echo_broadcast(&from, echo_broadcast, error);
-                self.register_provable_error(&from, evidence)
+                self.register_provable_error(&message_round_id, evidence);
+                Ok(())
             }
             ReceiveErrorType::InvalidNormalBroadcast(error) => {
-                let (_echo_broadcast, normal_broadcast, _direct_message) = processed.message.into_parts();
+                let (_echo_broadcast, normal_broadcast, _direct_message) = message.into_parts();
                 let evidence = Evidence::new_invalid_normal_broadcast(&from, normal_broadcast, error);
-                self.register_provable_error(&from, evidence)
+                self.register_provable_error(&message_round_id, evidence);
+                Ok(())
             }
             ReceiveErrorType::Protocol(error) => {
-                let (echo_broadcast, normal_broadcast, direct_message) = processed.message.into_parts();
+                // The Bracha echo rounds can be finalized without receiving a message from `from`,
+                // and with a trusted broadcast channel there are no echo rounds at all.
+                // In both cases the evidence cannot include the echo broadcasts `from` received.
+                if let Some(combined_echos) = &error.required_messages().combined_echos {
+                    for round_id in combined_echos {
+                        let echo_round_id = config.echo_round_id(round_id)?;
+                        let has_echo_round_message = echo_round_id.is_some_and(|echo_round_id| {
+                            transcript.has_normal_broadcast(&echo_round_id, &from)
+                                || echo_round_message
+                                    .as_ref()
+                                    .is_some_and(|message| message.metadata().round_id() == &echo_round_id)
+                        });
+                        if !has_echo_round_message {
+                            let error = RemoteError::new_with_kind(
+                                RemoteErrorKind::ProtocolErrorWithoutEvidence,
+                                format!(
+                                    "Protocol error: {error} (cannot be proven without the echo round message for {round_id})"
+                                ),
+                            );
+                            self.register_unprovable_error(&from, &message_round_id, error);
+                            return Ok(());
+                        }
+                    }
+                }
+
+                let (echo_broadcast, normal_broadcast, direct_message) = message.into_parts();
                 let evidence = Evidence::new_protocol_error(
                     &from,
                     echo_broadcast,
                     normal_broadcast,
                     direct_message,
+                    echo_round_message.as_ref(),
                     error,
                     transcript,
+                    config,
                 )?;
-                self.register_provable_error(&from, evidence)
+                self.register_provable_error(&message_round_id, evidence);
+                Ok(())
             }
             ReceiveErrorType::Unprovable(error) => {
-                self.unprovable_errors.insert(from.clone(), error);
+                self.register_unprovable_error(&from, &message_round_id, error);
                 Ok(())
             }
             ReceiveErrorType::Echo(error) => {
-                let (_echo_broadcast, normal_broadcast, _direct_message) = processed.message.into_parts();
-                let evidence = Evidence::new_echo_round_error(&from, normal_broadcast, *error)?;
-                self.register_provable_error(&from, evidence)
+                let (_echo_broadcast, normal_broadcast, direct_message) = message.into_parts();
+                // If the echo round was carried out along with this round, the error is in the attached message
+                let normal_broadcast = echo_round_message.unwrap_or(normal_broadcast);
+                let evidence = Evidence::new_echo_round_error(
+                    &from,
+                    normal_broadcast,
+                    direct_message,
+                    *error,
+                    transcript,
+                    &config.party_encoding,
+                )?;
+                self.register_provable_error(&message_round_id, evidence);
+                Ok(())
             }
+            // The sender has already been removed from the expected ones,
+            // so the message is simply not counted towards finalizing the round.
+            ReceiveErrorType::Disregarded => Ok(()),
             ReceiveErrorType::Local(error) => Err(error),
         }
     }
@@ -774,6 +1906,9 @@ pub enum PreprocessOutcome<Verifier> {
     ///
     /// No action required now, cached messages will be returned on successful [`Session::finalize_round`].
     Cached,
+    /// The message has the same signed contents as the one already received from the same sender for the same round,
+    /// and was ignored.
+    Duplicate,
     /// There was an error verifying the message.
     ///
     /// The error has been recorded in the accumulator, and will be included in the [`SessionReport`].
@@ -782,8 +1917,8 @@ pub enum PreprocessOutcome<Verifier> {
 }
 
 impl<Verifier> PreprocessOutcome<Verifier> {
-    pub(crate) fn remote_error(message: impl Into<String>) -> Self {
-        Self::Error(RemoteError::new(message))
+    pub(crate) fn remote_error(kind: RemoteErrorKind, message: impl Into<String>) -> Self {
+        Self::Error(RemoteError::new_with_kind(kind, message))
     }
 
     /// Returns the verified message for further processing, if any, otherwise returns `None`.
@@ -799,16 +1934,6 @@ impl<Verifier> PreprocessOutcome<Verifier> {
     }
 }
 
-fn filter_messages<Verifier>(
-    messages: BTreeMap<Verifier, BTreeMap<RoundId, VerifiedMessage<Verifier>>>,
-    round_id: &RoundId,
-) -> Vec<VerifiedMessage<Verifier>> {
-    messages
-        .into_values()
-        .filter_map(|mut messages| messages.remove(round_id))
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
     use impls::impls;
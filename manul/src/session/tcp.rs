@@ -0,0 +1,362 @@
+//! A reference transport executing sessions over TCP connections in `tokio` tasks.
+//!
+//! Messages are serialized with the session's [`WireFormat`](`super::WireFormat`) and sent in frames prefixed
+//! by their length as a big-endian `u32`.
+//! When a connection is established, the peers authenticate each other by signing the other peer's random challenge,
+//! bound to the session ID and the identities of both peers.
+//!
+//! Note that the connections are not encrypted, so this transport is mostly useful for testing,
+//! and as a starting point for production transports.
+
+use alloc::{collections::BTreeMap, format, vec, vec::Vec};
+use core::time::Duration;
+use std::{io, net::SocketAddr};
+
+use digest::Digest;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRngCore, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use signature::{DigestVerifier, Keypair};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpListener, TcpStream,
+    },
+    sync::mpsc,
+};
+
+use super::{
+    message::Message,
+    session::{AsyncSessionParameters, SessionId, SessionParameters},
+    tokio::{MessageIn, MessageOut},
+    wire_format::WireFormat,
+    LocalError, LocalErrorKind,
+};
+
+/// The maximum size of a frame accepted from a peer.
+pub const MAX_FRAME_SIZE: usize = 1 << 24;
+
+/// The number of attempts to connect to a peer before giving up.
+const CONNECTION_ATTEMPTS: usize = 10;
+
+/// The delay between the attempts to connect to a peer.
+const CONNECTION_DELAY: Duration = Duration::from_millis(100);
+
+/// The time a peer has to complete the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The capacity of the channels returned by [`connect`].
+const CHANNEL_CAPACITY: usize = 100;
+
+const HANDSHAKE_DOMAIN: &[u8] = b"manul/tcp-handshake";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Hello<Verifier> {
+    verifier: Verifier,
+    challenge: [u8; 32],
+}
+
+async fn write_frame(stream: &mut (impl AsyncWrite + Unpin), bytes: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "The frame is too large"))?;
+    stream.write_all(&length.to_be_bytes()).await?;
+    stream.write_all(bytes).await?;
+    stream.flush().await
+}
+
+/// Reads a frame, returning `None` if the stream was closed before it started.
+async fn read_frame(stream: &mut (impl AsyncRead + Unpin)) -> io::Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match stream.read_exact(&mut length).await {
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+
+    let length = usize::try_from(u32::from_be_bytes(length))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "The frame is too large"))?;
+    if length > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("The frame size ({length}) exceeds the limit ({MAX_FRAME_SIZE})"),
+        ));
+    }
+
+    let mut bytes = vec![0u8; length];
+    stream.read_exact(&mut bytes).await?;
+    Ok(Some(bytes))
+}
+
+/// The digest signed by `signer_id` to answer the challenge sent by `challenger_id`.
+fn handshake_digest<SP: SessionParameters>(
+    session_id: &SessionId,
+    challenge: &[u8; 32],
+    signer_id: &SP::Verifier,
+    challenger_id: &SP::Verifier,
+) -> Result<SP::Digest, LocalError> {
+    Ok(SP::Digest::new_with_prefix(HANDSHAKE_DOMAIN)
+        .chain_update(session_id)
+        .chain_update(challenge)
+        .chain_update(SP::WireFormat::serialize(signer_id)?)
+        .chain_update(SP::WireFormat::serialize(challenger_id)?))
+}
+
+/// Authenticates both sides of a connection, returning the identity of the peer.
+async fn handshake<SP: AsyncSessionParameters>(
+    stream: &mut TcpStream,
+    rng: &mut ChaCha20Rng,
+    signer: &SP::Signer,
+    session_id: &SessionId,
+    is_expected: impl Fn(&SP::Verifier) -> bool,
+) -> Result<SP::Verifier, LocalError> {
+    let io_error =
+        |err: io::Error| LocalError::new_with_kind(LocalErrorKind::Transport, format!("Handshake failed: {err}"));
+    let my_id = signer.verifying_key();
+
+    let mut challenge = [0u8; 32];
+    rng.fill_bytes(&mut challenge);
+    let hello = Hello {
+        verifier: my_id.clone(),
+        challenge,
+    };
+    write_frame(stream, &SP::WireFormat::serialize(&hello)?)
+        .await
+        .map_err(io_error)?;
+
+    let peer_hello = read_frame(stream).await.map_err(io_error)?.ok_or_else(|| {
+        LocalError::new_with_kind(
+            LocalErrorKind::Transport,
+            "The connection was closed during the handshake",
+        )
+    })?;
+    let peer_hello = SP::WireFormat::deserialize::<Hello<SP::Verifier>>(&peer_hello).map_err(|err| {
+        LocalError::new_with_kind(
+            LocalErrorKind::Transport,
+            format!("Failed to deserialize the handshake: {err:?}"),
+        )
+    })?;
+    let peer_id = peer_hello.verifier;
+    if !is_expected(&peer_id) {
+        return Err(LocalError::new_with_kind(
+            LocalErrorKind::Transport,
+            format!("Unexpected peer: {peer_id:?}"),
+        ));
+    }
+
+    let digest = handshake_digest::<SP>(session_id, &peer_hello.challenge, &my_id, &peer_id)?;
+    let signature = SP::sign_digests(signer, rng, vec![digest])
+        .await
+        .map_err(|err| {
+            LocalError::new_with_kind(LocalErrorKind::Signing, format!("Failed to sign the handshake: {err}"))
+        })?
+        .pop()
+        .ok_or_else(|| LocalError::new("The handshake was not signed"))?;
+    write_frame(stream, &SP::WireFormat::serialize(&signature)?)
+        .await
+        .map_err(io_error)?;
+
+    let peer_signature = read_frame(stream).await.map_err(io_error)?.ok_or_else(|| {
+        LocalError::new_with_kind(
+            LocalErrorKind::Transport,
+            "The connection was closed during the handshake",
+        )
+    })?;
+    let peer_signature = SP::WireFormat::deserialize::<SP::Signature>(&peer_signature).map_err(|err| {
+        LocalError::new_with_kind(
+            LocalErrorKind::Transport,
+            format!("Failed to deserialize the handshake signature: {err:?}"),
+        )
+    })?;
+    let digest = handshake_digest::<SP>(session_id, &challenge, &peer_id, &my_id)?;
+    peer_id.verify_digest(digest, &peer_signature).map_err(|err| {
+        LocalError::new_with_kind(
+            LocalErrorKind::Transport,
+            format!("Failed to authenticate {peer_id:?}: {err}"),
+        )
+    })?;
+
+    Ok(peer_id)
+}
+
+async fn dial<SP: AsyncSessionParameters>(
+    rng: &mut ChaCha20Rng,
+    signer: &SP::Signer,
+    session_id: &SessionId,
+    peer_id: &SP::Verifier,
+    address: SocketAddr,
+) -> Result<TcpStream, LocalError> {
+    let mut attempt = 1;
+    let mut stream = loop {
+        match TcpStream::connect(address).await {
+            Ok(stream) => break stream,
+            Err(_) if attempt < CONNECTION_ATTEMPTS => {
+                attempt += 1;
+                tokio::time::sleep(CONNECTION_DELAY).await;
+            }
+            Err(err) => {
+                return Err(LocalError::new_with_kind(
+                    LocalErrorKind::Transport,
+                    format!("Failed to connect to {peer_id:?} at {address}: {err}"),
+                ))
+            }
+        }
+    };
+
+    let handshake = handshake::<SP>(&mut stream, rng, signer, session_id, |id| id == peer_id);
+    tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await.map_err(|_| {
+        LocalError::new_with_kind(
+            LocalErrorKind::Transport,
+            format!("The handshake with {peer_id:?} timed out"),
+        )
+    })??;
+
+    Ok(stream)
+}
+
+async fn accept<SP: AsyncSessionParameters>(
+    rng: &mut ChaCha20Rng,
+    signer: &SP::Signer,
+    session_id: &SessionId,
+    listener: &TcpListener,
+    expected: &[SP::Verifier],
+) -> Result<BTreeMap<SP::Verifier, TcpStream>, LocalError> {
+    let mut streams = BTreeMap::new();
+    while streams.len() < expected.len() {
+        let (mut stream, address) = listener.accept().await.map_err(|err| {
+            LocalError::new_with_kind(
+                LocalErrorKind::Transport,
+                format!("Failed to accept a connection: {err}"),
+            )
+        })?;
+
+        let is_expected = |id: &SP::Verifier| expected.contains(id) && !streams.contains_key(id);
+        let handshake = handshake::<SP>(&mut stream, rng, signer, session_id, is_expected);
+        // A failed connection attempt from an unauthenticated peer should not stop the others from connecting.
+        match tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await {
+            Ok(Ok(peer_id)) => {
+                streams.insert(peer_id, stream);
+            }
+            Ok(Err(err)) => tracing::warn!("Rejected the connection from {address}: {err}"),
+            Err(_) => tracing::warn!("The handshake with {address} timed out"),
+        }
+    }
+    Ok(streams)
+}
+
+async fn read_messages<SP: SessionParameters>(
+    peer_id: SP::Verifier,
+    mut stream: OwnedReadHalf,
+    tx: mpsc::Sender<MessageIn<SP>>,
+) {
+    loop {
+        let bytes = match read_frame(&mut stream).await {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => break,
+            Err(err) => {
+                tracing::warn!("Failed to read a message from {peer_id:?}: {err}");
+                break;
+            }
+        };
+        let message = match SP::WireFormat::deserialize::<Message<SP::Verifier>>(&bytes) {
+            Ok(message) => message,
+            Err(err) => {
+                tracing::warn!("Failed to deserialize a message from {peer_id:?}: {err:?}");
+                continue;
+            }
+        };
+        let message_in = MessageIn {
+            from: peer_id.clone(),
+            message,
+        };
+        if tx.send(message_in).await.is_err() {
+            // The receiver is dropped, so nobody is interested in the messages anymore.
+            break;
+        }
+    }
+}
+
+async fn write_messages<SP: SessionParameters>(
+    mut streams: BTreeMap<SP::Verifier, OwnedWriteHalf>,
+    mut rx: mpsc::Receiver<MessageOut<SP>>,
+) {
+    while let Some(message_out) = rx.recv().await {
+        let Some(stream) = streams.get_mut(&message_out.to) else {
+            tracing::warn!("No connection to {:?}", message_out.to);
+            continue;
+        };
+        let bytes = match SP::WireFormat::serialize(&message_out.message) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                tracing::warn!("Failed to serialize a message to {:?}: {err}", message_out.to);
+                continue;
+            }
+        };
+        if let Err(err) = write_frame(stream, &bytes).await {
+            tracing::warn!("Failed to send a message to {:?}: {err}", message_out.to);
+            streams.remove(&message_out.to);
+        }
+    }
+
+    // Let the peers know that no more messages will be sent.
+    for (peer_id, mut stream) in streams {
+        if let Err(err) = stream.shutdown().await {
+            tracing::warn!("Failed to close the connection to {peer_id:?}: {err}");
+        }
+    }
+}
+
+/// Establishes authenticated connections with all the `peers` (given as a map of identities to addresses),
+/// returning the channels that can be passed to [`run_session`](`super::tokio::run_session`)
+/// or [`par_run_session`](`super::tokio::par_run_session`).
+///
+/// The node with the identity of `signer` dials the peers with smaller identities,
+/// and waits on `listener` for the connections from the peers with larger identities.
+/// The same `session_id` must be used by all the peers, since it is a part of the authentication.
+///
+/// The connections are closed when the returned sender and all of its clones are dropped.
+pub async fn connect<SP>(
+    rng: &mut impl CryptoRngCore,
+    signer: &SP::Signer,
+    session_id: &SessionId,
+    listener: TcpListener,
+    peers: BTreeMap<SP::Verifier, SocketAddr>,
+) -> Result<(mpsc::Sender<MessageOut<SP>>, mpsc::Receiver<MessageIn<SP>>), LocalError>
+where
+    SP: AsyncSessionParameters,
+{
+    let my_id = signer.verifying_key();
+    let (to_dial, to_accept): (Vec<_>, Vec<_>) = peers.into_iter().partition(|(peer_id, _address)| peer_id < &my_id);
+    let to_accept = to_accept
+        .into_iter()
+        .map(|(peer_id, _address)| peer_id)
+        .collect::<Vec<_>>();
+
+    let mut dial_rng = ChaCha20Rng::from_rng(&mut *rng).map_err(|_| LocalError::new("Can't fork the RNG"))?;
+    let mut accept_rng = ChaCha20Rng::from_rng(&mut *rng).map_err(|_| LocalError::new("Can't fork the RNG"))?;
+
+    // The connections are dialed and accepted concurrently, otherwise the nodes could be waiting for each other.
+    let dial_all = async {
+        let mut streams = BTreeMap::new();
+        for (peer_id, address) in to_dial {
+            let stream = dial::<SP>(&mut dial_rng, signer, session_id, &peer_id, address).await?;
+            streams.insert(peer_id, stream);
+        }
+        Ok::<_, LocalError>(streams)
+    };
+    let accept_all = accept::<SP>(&mut accept_rng, signer, session_id, &listener, &to_accept);
+    let (dialed, accepted) = tokio::try_join!(dial_all, accept_all)?;
+
+    let (incoming_tx, incoming_rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let (outgoing_tx, outgoing_rx) = mpsc::channel(CHANNEL_CAPACITY);
+
+    let mut write_halves = BTreeMap::new();
+    for (peer_id, stream) in dialed.into_iter().chain(accepted) {
+        let (read_half, write_half) = stream.into_split();
+        tokio::spawn(read_messages::<SP>(peer_id.clone(), read_half, incoming_tx.clone()));
+        write_halves.insert(peer_id, write_half);
+    }
+    tokio::spawn(write_messages::<SP>(write_halves, outgoing_rx));
+
+    Ok((outgoing_tx, incoming_rx))
+}
@@ -0,0 +1,405 @@
+//! API for executing sessions on a pool of threads, for applications without an async runtime.
+
+use alloc::{format, vec::Vec};
+use core::{num::NonZeroUsize, time::Duration};
+use std::{
+    sync::{mpsc, Mutex},
+    thread,
+};
+
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRngCore, SeedableRng};
+use tracing::{debug, trace};
+
+use super::{
+    message::VerifiedMessage,
+    retransmission::Outbox,
+    session::{
+        CanFinalize, ProcessedArtifact, ProcessedMessage, RoundAccumulator, RoundOutcome, Session, SessionParameters,
+    },
+    transcript::SessionReport,
+    transport::{answer_resend_request, request_missing_messages},
+    LocalError,
+};
+use crate::protocol::Protocol;
+
+pub use super::transport::{Incoming, MessageIn, MessageOut, Outgoing};
+
+/// A unit of work executed by a worker thread.
+enum Job<SP: SessionParameters> {
+    CreateMessage {
+        destination: SP::Verifier,
+        rng: ChaCha20Rng,
+    },
+    ProcessMessage(VerifiedMessage<SP::Verifier>),
+}
+
+/// The result of a [`Job`], sent back to the host thread.
+enum JobResult<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    MessageCreated(Result<(MessageOut<SP>, ProcessedArtifact<SP>), LocalError>),
+    MessageProcessed(ProcessedMessage<P, SP>),
+}
+
+/// Executes the jobs from `jobs` until the channel is closed.
+fn worker<P, SP>(
+    session: &Session<P, SP>,
+    jobs: &Mutex<mpsc::Receiver<Job<SP>>>,
+    results: mpsc::Sender<JobResult<P, SP>>,
+) where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    loop {
+        // The lock is released as soon as a job is received, so that the other workers could pick up the next one.
+        let job = match jobs.lock() {
+            Ok(jobs) => jobs.recv(),
+            // Another worker panicked; the panic will be propagated when the threads are joined.
+            Err(_) => return,
+        };
+        let Ok(job) = job else {
+            // The round is over.
+            return;
+        };
+
+        let result = match job {
+            Job::CreateMessage { destination, mut rng } => {
+                JobResult::MessageCreated(session.make_message(&mut rng, &destination).map(|(message, artifact)| {
+                    let message_out = MessageOut {
+                        session_id: session.session_id().clone(),
+                        from: session.verifier().clone(),
+                        to: destination,
+                        message,
+                    };
+                    (message_out, artifact)
+                }))
+            }
+            Job::ProcessMessage(message) => JobResult::MessageProcessed(session.process_message(message)),
+        };
+
+        // The receiver is only dropped when the round is already over.
+        if results.send(result).is_err() {
+            return;
+        }
+    }
+}
+
+/// Sends out a message created by the session, keeping a copy in the outbox if retransmission is enabled.
+fn send_message<SP>(
+    send: &mut impl FnMut(Outgoing<SP>) -> Result<(), LocalError>,
+    outbox: Option<&mut Outbox<SP>>,
+    message_out: MessageOut<SP>,
+) -> Result<(), LocalError>
+where
+    SP: SessionParameters,
+{
+    if let Some(outbox) = outbox {
+        outbox.record(&message_out.to, &message_out.message);
+    }
+    send(Outgoing::Message(message_out))
+}
+
+/// Adds the result of a job to the accumulator, sending out the created message if there is one.
+fn apply_result<P, SP>(
+    session: &Session<P, SP>,
+    accum: &mut RoundAccumulator<P, SP>,
+    send: &mut impl FnMut(Outgoing<SP>) -> Result<(), LocalError>,
+    outbox: Option<&mut Outbox<SP>>,
+    result: JobResult<P, SP>,
+) -> Result<(), LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    match result {
+        JobResult::MessageCreated(created) => {
+            let (message_out, artifact) = created?;
+            debug!("{:?}: Sending a message to {:?}", session.verifier(), message_out.to);
+            send_message(send, outbox, message_out)?;
+            session.add_artifact(accum, artifact)
+        }
+        JobResult::MessageProcessed(processed) => session.add_processed_message(accum, processed),
+    }
+}
+
+/// Executes a single round on `num_threads` worker threads,
+/// returning the accumulator and whether the round can be finalized.
+fn run_round<P, SP>(
+    rng: &mut impl CryptoRngCore,
+    num_threads: NonZeroUsize,
+    send: &mut impl FnMut(Outgoing<SP>) -> Result<(), LocalError>,
+    receive: &mut impl FnMut(Option<Duration>) -> Result<Option<Incoming<SP>>, LocalError>,
+    session: &Session<P, SP>,
+    cached_messages: Vec<VerifiedMessage<SP::Verifier>>,
+    mut outbox: Option<&mut Outbox<SP>>,
+) -> Result<(RoundAccumulator<P, SP>, bool), LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+    SP::Signer: Sync,
+    P::ProtocolError: Send + Sync,
+{
+    let my_id = format!("{:?}", session.verifier());
+
+    // This is kept in the host thread since it's mutable,
+    // and we don't want to bother with synchronization.
+    let mut accum = session.make_accumulator();
+
+    let (job_tx, job_rx) = mpsc::channel::<Job<SP>>();
+    let (result_tx, result_rx) = mpsc::channel::<JobResult<P, SP>>();
+    let job_rx = Mutex::new(job_rx);
+
+    thread::scope(|scope| {
+        for _ in 0..num_threads.get() {
+            let job_rx = &job_rx;
+            let result_tx = result_tx.clone();
+            scope.spawn(move || worker(session, job_rx, result_tx));
+        }
+        // Only the workers hold the senders now, so the channel is closed when they all exit.
+        drop(result_tx);
+
+        let send_job = |job: Job<SP>| {
+            job_tx
+                .send(job)
+                .map_err(|_| LocalError::new("All the worker threads have exited"))
+        };
+
+        // The number of jobs whose results have not been received yet.
+        let mut pending_jobs = 0usize;
+
+        for destination in session.message_destinations() {
+            // Worker threads must not share the same RNG state; we use the provided RNG to seed new ChaCha RNGs to
+            // ensure each thread has access to unique randomness.
+            let rng = ChaCha20Rng::from_rng(&mut *rng).map_err(|_| LocalError::new("Can't fork the RNG"))?;
+            send_job(Job::CreateMessage {
+                destination: destination.clone(),
+                rng,
+            })?;
+            pending_jobs += 1;
+        }
+
+        for preprocessed in cached_messages {
+            debug!("{my_id}: Applying a cached message");
+            send_job(Job::ProcessMessage(preprocessed))?;
+            pending_jobs += 1;
+        }
+
+        let can_finalize = loop {
+            while let Ok(result) = result_rx.try_recv() {
+                pending_jobs -= 1;
+                apply_result(session, &mut accum, send, outbox.as_deref_mut(), result)?;
+            }
+
+            match session.can_finalize(&accum) {
+                CanFinalize::Yes => break true,
+                CanFinalize::NotYet => {}
+                // Due to already registered invalid messages from nodes,
+                // even if the remaining nodes send correct messages, it won't be enough.
+                // Terminating.
+                CanFinalize::Never => break false,
+            }
+
+            // If some jobs are still running, only pick up the messages that have already arrived,
+            // and otherwise wait for the workers.
+            let incoming = if pending_jobs > 0 {
+                match receive(Some(Duration::ZERO))? {
+                    Some(incoming) => incoming,
+                    None => {
+                        let result = result_rx
+                            .recv()
+                            .map_err(|_| LocalError::new("All the worker threads have exited"))?;
+                        pending_jobs -= 1;
+                        apply_result(session, &mut accum, send, outbox.as_deref_mut(), result)?;
+                        continue;
+                    }
+                }
+            } else {
+                debug!("{my_id}: Waiting for a message");
+                match receive(None)? {
+                    Some(incoming) => incoming,
+                    // The session was cancelled
+                    None => break false,
+                }
+            };
+
+            let message_in = match incoming {
+                Incoming::Message(message_in) => message_in,
+                Incoming::ResendRequest(request) => {
+                    if let Some(message_out) = answer_resend_request(session, outbox.as_deref(), &request)? {
+                        send(Outgoing::Message(message_out))?;
+                    }
+                    continue;
+                }
+                Incoming::ResendTimeout => {
+                    for item in request_missing_messages(rng, session, &accum, outbox.as_deref())? {
+                        send(item)?;
+                    }
+                    continue;
+                }
+            };
+
+            // Perform quick checks before proceeding with the verification.
+            match session
+                .preprocess_message(&mut accum, &message_in.from, message_in.message)?
+                .ok()
+            {
+                Some(preprocessed) => {
+                    debug!("{my_id}: Applying a message from {:?}", message_in.from);
+                    send_job(Job::ProcessMessage(preprocessed))?;
+                    pending_jobs += 1;
+                }
+                None => {
+                    trace!("{my_id} Pre-processing complete. Current state: {accum:?}")
+                }
+            }
+        };
+
+        // Let the workers exit after they are done with the remaining jobs.
+        drop(job_tx);
+
+        // Send out the messages that are still being created, since the other nodes may be waiting for them.
+        // The results of processing are not needed anymore.
+        for result in result_rx {
+            if let JobResult::MessageCreated(created) = result {
+                let (message_out, artifact) = created?;
+                send_message(send, outbox.as_deref_mut(), message_out)?;
+                session.add_artifact(&mut accum, artifact)?;
+            }
+        }
+
+        Ok((accum, can_finalize))
+    })
+}
+
+/// Executes the session on a pool of `num_threads` threads,
+/// waiting for the messages with the blocking `receive`
+/// and passing outgoing messages to the blocking `send`.
+///
+/// The messages are created and processed in the worker threads,
+/// while the round accumulator is kept in the calling thread,
+/// with the same semantics as [`futures::par_run_session`](`super::futures::par_run_session`).
+///
+/// `receive(None)` must block until a message arrives, and return `None` if the session is cancelled;
+/// `receive(Some(timeout))` must return `None` if no message arrives within `timeout`.
+/// A receiver `rx` from [`std::sync::mpsc`] can be used as
+/// `|timeout| Ok(match timeout { Some(timeout) => rx.recv_timeout(timeout).ok(), None => rx.recv().ok() })`.
+///
+/// Only synchronous rounds are supported, and the messages are signed synchronously.
+pub fn par_run_session<P, SP>(
+    rng: &mut impl CryptoRngCore,
+    num_threads: NonZeroUsize,
+    send: impl FnMut(MessageOut<SP>) -> Result<(), LocalError>,
+    receive: impl FnMut(Option<Duration>) -> Result<Option<MessageIn<SP>>, LocalError>,
+    session: Session<P, SP>,
+) -> Result<SessionReport<P, SP>, LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+    SP::Signer: Sync,
+    P::ProtocolError: Send + Sync,
+{
+    let mut send = send;
+    let mut receive = receive;
+    // Resend requests are never created if retransmission is disabled.
+    let send = |item| match item {
+        Outgoing::Message(message_out) => send(message_out),
+        Outgoing::ResendRequest { .. } => Ok(()),
+    };
+    let receive = |timeout| Ok(receive(timeout)?.map(Incoming::Message));
+    par_run_session_inner(rng, num_threads, send, receive, session, None)
+}
+
+/// Same as [`par_run_session`], but with the retransmission of lost messages enabled.
+///
+/// The sent messages are kept in an [`Outbox`] to answer the [`Incoming::ResendRequest`]s from the other nodes,
+/// and the missing messages of the current round are requested with [`Outgoing::ResendRequest`]
+/// on every [`Incoming::ResendTimeout`] returned by `receive`.
+/// With a receiver `rx` from [`std::sync::mpsc`], `receive(None)` can produce them
+/// by waiting for `rx.recv_timeout(resend_timeout)` and returning `Some(Incoming::ResendTimeout)`
+/// on [`RecvTimeoutError::Timeout`](`std::sync::mpsc::RecvTimeoutError::Timeout`),
+/// and `None` (cancelling the session) on
+/// [`RecvTimeoutError::Disconnected`](`std::sync::mpsc::RecvTimeoutError::Disconnected`).
+///
+/// The outbox is returned along with the report, so that the caller could keep answering the requests
+/// of the nodes that lost the messages of the last round.
+pub fn par_run_session_with_retransmission<P, SP>(
+    rng: &mut impl CryptoRngCore,
+    num_threads: NonZeroUsize,
+    send: impl FnMut(Outgoing<SP>) -> Result<(), LocalError>,
+    receive: impl FnMut(Option<Duration>) -> Result<Option<Incoming<SP>>, LocalError>,
+    session: Session<P, SP>,
+) -> Result<(SessionReport<P, SP>, Outbox<SP>), LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+    SP::Signer: Sync,
+    P::ProtocolError: Send + Sync,
+{
+    let mut outbox = Outbox::new(session.verifier(), session.session_id().clone());
+    let report = par_run_session_inner(rng, num_threads, send, receive, session, Some(&mut outbox))?;
+    Ok((report, outbox))
+}
+
+/// Executes the session on a pool of threads; retransmission is enabled if `outbox` is not `None`.
+fn par_run_session_inner<P, SP>(
+    rng: &mut impl CryptoRngCore,
+    num_threads: NonZeroUsize,
+    send: impl FnMut(Outgoing<SP>) -> Result<(), LocalError>,
+    receive: impl FnMut(Option<Duration>) -> Result<Option<Incoming<SP>>, LocalError>,
+    session: Session<P, SP>,
+    mut outbox: Option<&mut Outbox<SP>>,
+) -> Result<SessionReport<P, SP>, LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+    SP::Signer: Sync,
+    P::ProtocolError: Send + Sync,
+{
+    let mut send = send;
+    let mut receive = receive;
+    let mut session = session;
+    // Some rounds can finalize early and put off sending messages to the next round. Such messages
+    // will be stored here and applied after the messages for this round are sent.
+    let mut cached_messages = Vec::new();
+
+    loop {
+        debug!(
+            "{:?}: *** starting round {:?} ***",
+            session.verifier(),
+            session.round_id()
+        );
+
+        if session.has_async_round() {
+            return Err(LocalError::new(format!(
+                "Round {:?} is asynchronous and cannot be executed on a thread pool",
+                session.round_id()
+            )));
+        }
+
+        let (accum, can_finalize) = run_round(
+            rng,
+            num_threads,
+            &mut send,
+            &mut receive,
+            &session,
+            cached_messages,
+            outbox.as_deref_mut(),
+        )?;
+
+        if !can_finalize {
+            return session.terminate_due_to_errors(accum);
+        }
+
+        debug!("{:?}: Finalizing the round", session.verifier());
+
+        match session.finalize_round(rng, accum)? {
+            RoundOutcome::Finished(report) => break Ok(report),
+            RoundOutcome::AnotherRound {
+                session: new_session,
+                cached_messages: new_cached_messages,
+            } => {
+                session = new_session;
+                cached_messages = new_cached_messages;
+            }
+        }
+    }
+}
@@ -6,7 +6,14 @@ use alloc::{
 };
 use core::fmt::Debug;
 
-use super::{evidence::Evidence, message::SignedMessagePart, session::SessionParameters, LocalError, RemoteError};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    evidence::Evidence,
+    message::{MessageFingerprint, SignedMessagePart},
+    session::SessionParameters,
+    LocalError, RemoteError, RemoteErrorKind,
+};
 use crate::protocol::{DirectMessage, EchoBroadcast, NormalBroadcast, Protocol, RoundId};
 
 #[derive(Debug)]
@@ -17,6 +24,15 @@ pub(crate) struct Transcript<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     provable_errors: BTreeMap<SP::Verifier, Evidence<P, SP>>,
     unprovable_errors: BTreeMap<SP::Verifier, RemoteError>,
     missing_messages: BTreeMap<RoundId, BTreeSet<SP::Verifier>>,
+    offenses: BTreeMap<SP::Verifier, Vec<Offense<P, SP>>>,
+    // The optional senders that had not sent their messages by the time the corresponding round was finalized.
+    silent_optional_senders: BTreeMap<RoundId, BTreeSet<SP::Verifier>>,
+    // The messages accepted for processing in the finished rounds, to recognize their late redeliveries.
+    accepted_messages: BTreeMap<RoundId, BTreeMap<SP::Verifier, MessageFingerprint>>,
+    // The number of errors counted against each sender's budget by the ban policy.
+    error_counts: BTreeMap<SP::Verifier, usize>,
+    // The total size of the messages received from each sender, checked against `MessageLimits`.
+    bytes_received: BTreeMap<SP::Verifier, usize>,
 }
 
 impl<P, SP> Transcript<P, SP>
@@ -32,6 +48,11 @@ where
             provable_errors: BTreeMap::new(),
             unprovable_errors: BTreeMap::new(),
             missing_messages: BTreeMap::new(),
+            offenses: BTreeMap::new(),
+            silent_optional_senders: BTreeMap::new(),
+            accepted_messages: BTreeMap::new(),
+            error_counts: BTreeMap::new(),
+            bytes_received: BTreeMap::new(),
         }
     }
 
@@ -46,6 +67,7 @@ where
         provable_errors: BTreeMap<SP::Verifier, Evidence<P, SP>>,
         unprovable_errors: BTreeMap<SP::Verifier, RemoteError>,
         missing_messages: BTreeSet<SP::Verifier>,
+        offenses: BTreeMap<SP::Verifier, Vec<Offense<P, SP>>>,
     ) -> Result<Self, LocalError> {
         let mut all_echo_broadcasts = self.echo_broadcasts;
         match all_echo_broadcasts.entry(round_id.clone()) {
@@ -82,22 +104,21 @@ where
             }
         };
 
+        // A party may commit offenses in several rounds (e.g. in an echo round following the one it was banned in),
+        // but only the first error, which got it banned, is kept here; the rest are recorded in the offenses.
         let mut all_provable_errors = self.provable_errors;
         for (verifier, error) in provable_errors {
-            if all_provable_errors.insert(verifier.clone(), error).is_some() {
-                return Err(LocalError::new(format!(
-                    "A provable errors entry for {verifier:?} already exists"
-                )));
-            }
+            all_provable_errors.entry(verifier).or_insert(error);
         }
 
         let mut all_unprovable_errors = self.unprovable_errors;
         for (verifier, error) in unprovable_errors {
-            if all_unprovable_errors.insert(verifier.clone(), error).is_some() {
-                return Err(LocalError::new(format!(
-                    "An unprovable errors entry for {verifier:?} already exists"
-                )));
-            }
+            all_unprovable_errors.entry(verifier).or_insert(error);
+        }
+
+        let mut all_offenses = self.offenses;
+        for (verifier, offenses) in offenses {
+            all_offenses.entry(verifier).or_default().extend(offenses);
         }
 
         let mut all_missing_messages = self.missing_messages;
@@ -121,6 +142,102 @@ where
             provable_errors: all_provable_errors,
             unprovable_errors: all_unprovable_errors,
             missing_messages: all_missing_messages,
+            offenses: all_offenses,
+            silent_optional_senders: self.silent_optional_senders,
+            accepted_messages: self.accepted_messages,
+            error_counts: self.error_counts,
+            bytes_received: self.bytes_received,
+        })
+    }
+
+    /// Records the optional senders that had not sent their messages by the time the round was finalized.
+    pub fn add_silent_optional_senders(
+        mut self,
+        round_id: &RoundId,
+        senders: BTreeSet<SP::Verifier>,
+    ) -> Result<Self, LocalError> {
+        if senders.is_empty() {
+            return Ok(self);
+        }
+        match self.silent_optional_senders.entry(round_id.clone()) {
+            Entry::Vacant(entry) => entry.insert(senders),
+            Entry::Occupied(_) => {
+                return Err(LocalError::new(format!(
+                    "A silent optional senders entry for {round_id:?} already exists"
+                )))
+            }
+        };
+        Ok(self)
+    }
+
+    /// Records the fingerprints of the messages accepted for processing in the given round.
+    pub fn add_accepted_messages(
+        mut self,
+        round_id: &RoundId,
+        messages: BTreeMap<SP::Verifier, MessageFingerprint>,
+    ) -> Result<Self, LocalError> {
+        if messages.is_empty() {
+            return Ok(self);
+        }
+        match self.accepted_messages.entry(round_id.clone()) {
+            Entry::Vacant(entry) => entry.insert(messages),
+            Entry::Occupied(_) => {
+                return Err(LocalError::new(format!(
+                    "An accepted messages entry for {round_id:?} already exists"
+                )))
+            }
+        };
+        Ok(self)
+    }
+
+    /// Replaces the numbers of errors counted against each sender with the updated ones.
+    pub fn with_error_counts(self, error_counts: BTreeMap<SP::Verifier, usize>) -> Self {
+        Self { error_counts, ..self }
+    }
+
+    pub fn error_counts(&self) -> &BTreeMap<SP::Verifier, usize> {
+        &self.error_counts
+    }
+
+    /// Replaces the total sizes of the messages received from each sender with the updated ones.
+    pub fn with_bytes_received(self, bytes_received: BTreeMap<SP::Verifier, usize>) -> Self {
+        Self { bytes_received, ..self }
+    }
+
+    pub fn bytes_received(&self) -> &BTreeMap<SP::Verifier, usize> {
+        &self.bytes_received
+    }
+
+    pub fn get_accepted_message(&self, round_id: &RoundId, from: &SP::Verifier) -> Option<&MessageFingerprint> {
+        self.accepted_messages
+            .get(round_id)
+            .and_then(|messages| messages.get(from))
+    }
+
+    pub fn is_silent_optional_sender(&self, round_id: &RoundId, from: &SP::Verifier) -> bool {
+        self.silent_optional_senders
+            .get(round_id)
+            .is_some_and(|senders| senders.contains(from))
+    }
+
+    /// Adds the normal broadcasts of a round that was carried out along with another one.
+    pub fn add_normal_broadcasts(
+        self,
+        round_id: &RoundId,
+        normal_broadcasts: BTreeMap<SP::Verifier, SignedMessagePart<NormalBroadcast>>,
+    ) -> Result<Self, LocalError> {
+        let mut all_normal_broadcasts = self.normal_broadcasts;
+        match all_normal_broadcasts.entry(round_id.clone()) {
+            Entry::Vacant(entry) => entry.insert(normal_broadcasts),
+            Entry::Occupied(_) => {
+                return Err(LocalError::new(format!(
+                    "A normal-broadcasts entry for {round_id:?} already exists"
+                )))
+            }
+        };
+        Ok(Self {
+            normal_broadcasts: all_normal_broadcasts,
+            ..self
         })
     }
 
@@ -150,6 +267,12 @@ where
             .ok_or_else(|| LocalError::new(format!("No normal broadcasts registered for {from:?} in {round_id:?}")))
     }
 
+    pub fn has_normal_broadcast(&self, round_id: &RoundId, from: &SP::Verifier) -> bool {
+        self.normal_broadcasts
+            .get(round_id)
+            .is_some_and(|messages| messages.contains_key(from))
+    }
+
     pub fn get_direct_message(
         &self,
         round_id: &RoundId,
@@ -216,6 +339,52 @@ where
     }
 }
 
+/// The kind of an [`Offense`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OffenseKind {
+    /// A provable error, with the evidence attached to the offense.
+    Provable,
+    /// An unprovable error of the given kind.
+    Unprovable(RemoteErrorKind),
+}
+
+/// A single offense committed by a node during the session.
+#[derive_where::derive_where(Debug, Clone, Serialize, Deserialize)]
+pub struct Offense<P: Protocol<SP::Verifier>, SP: SessionParameters> {
+    /// The ID of the round the offending message was for.
+    pub round_id: RoundId,
+    /// The kind of the offense.
+    pub kind: OffenseKind,
+    /// The human-readable description of the offense.
+    pub description: String,
+    /// The evidence proving the offense, if it is provable.
+    pub evidence: Option<Evidence<P, SP>>,
+}
+
+impl<P, SP> Offense<P, SP>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    pub(crate) fn provable(round_id: &RoundId, evidence: &Evidence<P, SP>) -> Self {
+        Self {
+            round_id: round_id.clone(),
+            kind: OffenseKind::Provable,
+            description: evidence.description().into(),
+            evidence: Some(evidence.clone()),
+        }
+    }
+
+    pub(crate) fn unprovable(round_id: &RoundId, error: &RemoteError) -> Self {
+        Self {
+            round_id: round_id.clone(),
+            kind: OffenseKind::Unprovable(error.kind()),
+            description: error.message().into(),
+            evidence: None,
+        }
+    }
+}
+
 /// The report of a session execution.
 #[derive(Debug)]
 pub struct SessionReport<P: Protocol<SP::Verifier>, SP: SessionParameters> {
@@ -227,6 +396,13 @@ pub struct SessionReport<P: Protocol<SP::Verifier>, SP: SessionParameters> {
     pub unprovable_errors: BTreeMap<SP::Verifier, RemoteError>,
     /// The nodes that did not send their messages in time for the corresponding round.
     pub missing_messages: BTreeMap<RoundId, BTreeSet<SP::Verifier>>,
+    /// All the offenses committed by each node, in the order they were detected.
+    ///
+    /// Unlike [`provable_errors`](`Self::provable_errors`) and [`unprovable_errors`](`Self::unprovable_errors`),
+    /// which only hold the first error that got the node banned, these include the errors committed after that
+    /// (e.g. while the node's messages for the same round were still being processed),
+    /// and the errors that did not lead to a ban according to the [`BanPolicy`](`super::BanPolicy`).
+    pub offenses: BTreeMap<SP::Verifier, Vec<Offense<P, SP>>>,
 }
 
 impl<P, SP> SessionReport<P, SP>
@@ -240,6 +416,7 @@ where
             provable_errors: transcript.provable_errors,
             unprovable_errors: transcript.unprovable_errors,
             missing_messages: transcript.missing_messages,
+            offenses: transcript.offenses,
         }
     }
 
@@ -0,0 +1,122 @@
+//! Messages passed between the sessions and the transport by the session runners.
+
+use alloc::vec::Vec;
+
+use rand_core::CryptoRngCore;
+use tracing::debug;
+
+use super::{
+    message::Message,
+    retransmission::{Outbox, ResendRequest},
+    session::{RoundAccumulator, Session, SessionId, SessionParameters},
+    LocalError,
+};
+use crate::protocol::Protocol;
+
+/// The outgoing message from a local session.
+#[derive(Debug)]
+pub struct MessageOut<SP: SessionParameters> {
+    /// The session ID that created the message.
+    ///
+    /// Useful when there are several sessions running on a node, pushing messages into the same channel.
+    pub session_id: SessionId,
+    /// The verifying key of the party that created the message.
+    ///
+    /// Useful when there are several sessions running on a node, pushing messages into the same channel.
+    pub from: SP::Verifier,
+    /// The verifying key of the party the message is intended for.
+    pub to: SP::Verifier,
+    /// The message to be sent.
+    ///
+    /// Note that the caller is responsible for encrypting the message and attaching authentication info.
+    pub message: Message<SP::Verifier>,
+}
+
+/// The incoming message from a remote session.
+#[derive(Debug)]
+pub struct MessageIn<SP: SessionParameters> {
+    /// The verifying key of the party the message originated from.
+    ///
+    /// It is assumed that the message's authentication info has been checked at this point.
+    pub from: SP::Verifier,
+    /// The incoming message.
+    pub message: Message<SP::Verifier>,
+}
+
+/// An item to be sent to a remote session by a runner with the retransmission of lost messages enabled.
+// Messages are the most common case, so they are not boxed.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum Outgoing<SP: SessionParameters> {
+    /// A message created by the local session, or resent on request.
+    Message(MessageOut<SP>),
+    /// A request to resend a lost message, to be delivered to the remote runner as [`Incoming::ResendRequest`].
+    ResendRequest {
+        /// The verifying key of the party the message is requested from.
+        to: SP::Verifier,
+        /// The request to be sent.
+        request: ResendRequest<SP>,
+    },
+}
+
+/// An item received by a runner with the retransmission of lost messages enabled.
+// Messages are the most common case, so they are not boxed.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug)]
+pub enum Incoming<SP: SessionParameters> {
+    /// A message from a remote session.
+    Message(MessageIn<SP>),
+    /// A request to resend a message, answered with [`Outgoing::Message`] if it is valid.
+    ResendRequest(ResendRequest<SP>),
+    /// The messages for the current round are taking too long to arrive.
+    ///
+    /// The runner requests the missing messages with [`Outgoing::ResendRequest`].
+    /// The runners do not keep any timers, so these have to be produced by the caller.
+    ResendTimeout,
+}
+
+/// Answers a resend request from the outbox, if retransmission is enabled.
+pub(crate) fn answer_resend_request<P, SP>(
+    session: &Session<P, SP>,
+    outbox: Option<&Outbox<SP>>,
+    request: &ResendRequest<SP>,
+) -> Result<Option<MessageOut<SP>>, LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    let Some(outbox) = outbox else {
+        return Ok(None);
+    };
+    Ok(outbox.answer(request)?.map(|message| MessageOut {
+        session_id: session.session_id().clone(),
+        from: session.verifier(),
+        to: request.requester().clone(),
+        message,
+    }))
+}
+
+/// Creates the requests for the messages missing in the current round, if retransmission is enabled.
+pub(crate) fn request_missing_messages<P, SP>(
+    rng: &mut impl CryptoRngCore,
+    session: &Session<P, SP>,
+    accum: &RoundAccumulator<P, SP>,
+    outbox: Option<&Outbox<SP>>,
+) -> Result<Vec<Outgoing<SP>>, LocalError>
+where
+    P: Protocol<SP::Verifier>,
+    SP: SessionParameters,
+{
+    if outbox.is_none() {
+        return Ok(Vec::new());
+    }
+    session
+        .missing_messages(accum)
+        .into_iter()
+        .map(|id| {
+            debug!("{:?}: Requesting {:?} to resend its message", session.verifier(), id);
+            let request = session.make_resend_request(rng, &id)?;
+            Ok(Outgoing::ResendRequest { to: id, request })
+        })
+        .collect()
+}
@@ -1 +1,2 @@
+mod optional_senders;
 mod partial_echo;
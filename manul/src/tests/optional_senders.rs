@@ -0,0 +1,335 @@
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+use core::{fmt::Debug, marker::PhantomData};
+
+use rand_core::{CryptoRngCore, OsRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dev::{run_sync_with_config, BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
+    protocol::{
+        Artifact, BoxedFormat, BoxedRound, CommunicationInfo, DirectMessage, EchoBroadcast, EchoRoundParticipation,
+        EntryPoint, FinalizeOutcome, LocalError, MessageValidationError, NoProtocolErrors, NormalBroadcast, PartyId,
+        Payload, Protocol, ProtocolMessage, ProtocolMessagePart, ReceiveError, Round, RoundId, TransitionInfo,
+    },
+    session::{BroadcastConsistency, CanFinalize, RoundOutcome, Session, SessionConfig, SessionId, SessionOutcome},
+    signature::Keypair,
+};
+
+#[derive(Debug)]
+struct OptionalSendersProtocol<Id>(PhantomData<Id>);
+
+impl<Id: PartyId> Protocol<Id> for OptionalSendersProtocol<Id> {
+    // The nodes whose payloads reached `finalize()`
+    type Result = BTreeSet<Id>;
+    type ProtocolError = NoProtocolErrors;
+
+    fn verify_direct_message_is_invalid(
+        _format: &BoxedFormat,
+        _round_id: &RoundId,
+        _message: &DirectMessage,
+    ) -> Result<(), MessageValidationError> {
+        unimplemented!()
+    }
+
+    fn verify_echo_broadcast_is_invalid(
+        _format: &BoxedFormat,
+        _round_id: &RoundId,
+        _message: &EchoBroadcast,
+    ) -> Result<(), MessageValidationError> {
+        unimplemented!()
+    }
+
+    fn verify_normal_broadcast_is_invalid(
+        _format: &BoxedFormat,
+        _round_id: &RoundId,
+        _message: &NormalBroadcast,
+    ) -> Result<(), MessageValidationError> {
+        unimplemented!()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Inputs<Id> {
+    id: Id,
+    message_destinations: BTreeSet<Id>,
+    expecting_messages_from: BTreeSet<Id>,
+    optional_messages_from: BTreeSet<Id>,
+    echo_round_participation: EchoRoundParticipation<Id>,
+}
+
+#[derive(Debug)]
+struct Round1<Id> {
+    inputs: Inputs<Id>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Round1Echo<Id> {
+    sender: Id,
+}
+
+impl<Id: PartyId + Serialize + for<'de> Deserialize<'de>> EntryPoint<Id> for Inputs<Id> {
+    type Protocol = OptionalSendersProtocol<Id>;
+
+    fn entry_round_id() -> RoundId {
+        1.into()
+    }
+
+    fn make_round(
+        self,
+        _rng: &mut dyn CryptoRngCore,
+        _shared_randomness: &[u8],
+        _id: &Id,
+    ) -> Result<BoxedRound<Id, Self::Protocol>, LocalError> {
+        Ok(BoxedRound::new_dynamic(Round1 { inputs: self }))
+    }
+}
+
+impl<Id: PartyId + Serialize + for<'de> Deserialize<'de>> Round<Id> for Round1<Id> {
+    type Protocol = OptionalSendersProtocol<Id>;
+
+    fn transition_info(&self) -> TransitionInfo {
+        TransitionInfo::new_linear_terminating(1)
+    }
+
+    fn communication_info(&self) -> CommunicationInfo<Id> {
+        CommunicationInfo {
+            message_destinations: self.inputs.message_destinations.clone(),
+            expecting_messages_from: self.inputs.expecting_messages_from.clone(),
+            optional_messages_from: self.inputs.optional_messages_from.clone(),
+            echo_round_participation: self.inputs.echo_round_participation.clone(),
+        }
+    }
+
+    fn make_echo_broadcast(
+        &self,
+        _rng: &mut dyn CryptoRngCore,
+        format: &BoxedFormat,
+    ) -> Result<EchoBroadcast, LocalError> {
+        if self.inputs.message_destinations.is_empty() {
+            Ok(EchoBroadcast::none())
+        } else {
+            EchoBroadcast::new(
+                format,
+                Round1Echo {
+                    sender: self.inputs.id.clone(),
+                },
+            )
+        }
+    }
+
+    fn receive_message(
+        &self,
+        format: &BoxedFormat,
+        from: &Id,
+        message: ProtocolMessage,
+    ) -> Result<Payload, ReceiveError<Id, Self::Protocol>> {
+        message.normal_broadcast.assert_is_none()?;
+        message.direct_message.assert_is_none()?;
+
+        let echo = message.echo_broadcast.deserialize::<Round1Echo<Id>>(format)?;
+        assert_eq!(&echo.sender, from);
+
+        Ok(Payload::new(()))
+    }
+
+    fn finalize(
+        self: Box<Self>,
+        _rng: &mut dyn CryptoRngCore,
+        payloads: BTreeMap<Id, Payload>,
+        _artifacts: BTreeMap<Id, Artifact>,
+    ) -> Result<FinalizeOutcome<Id, Self::Protocol>, LocalError> {
+        Ok(FinalizeOutcome::Result(payloads.into_keys().collect()))
+    }
+}
+
+fn make_entry_points(node0_sends: bool) -> Vec<(TestSigner, Inputs<TestVerifier>)> {
+    let signers = (0..4).map(TestSigner::new).collect::<Vec<_>>();
+    let ids = signers.iter().map(|signer| signer.verifying_key()).collect::<Vec<_>>();
+    let required_senders = BTreeSet::from([ids[1], ids[2], ids[3]]);
+
+    // Nodes 1, 2, 3 exchange messages with each other, and node 0 may send them a message too.
+    // Since node 0 may not be heard from, its echo broadcast is not echoed back to it.
+
+    let node0 = (
+        signers[0],
+        Inputs {
+            id: ids[0],
+            message_destinations: if node0_sends {
+                required_senders.clone()
+            } else {
+                BTreeSet::new()
+            },
+            expecting_messages_from: BTreeSet::new(),
+            optional_messages_from: BTreeSet::new(),
+            echo_round_participation: EchoRoundParticipation::Send,
+        },
+    );
+    let other_nodes = signers.iter().skip(1).map(|signer| {
+        let id = signer.verifying_key();
+        let mut others = required_senders.clone();
+        others.remove(&id);
+        (
+            *signer,
+            Inputs {
+                id,
+                message_destinations: others.clone(),
+                expecting_messages_from: others,
+                optional_messages_from: BTreeSet::from([ids[0]]),
+                echo_round_participation: EchoRoundParticipation::Default,
+            },
+        )
+    });
+
+    [node0].into_iter().chain(other_nodes).collect()
+}
+
+fn run_optional_senders(config: SessionConfig<TestVerifier>, node0_sends: bool) {
+    let ids = (0..4).map(|id| TestSigner::new(id).verifying_key()).collect::<Vec<_>>();
+    let required_senders = BTreeSet::from([ids[1], ids[2], ids[3]]);
+    let entry_points = make_entry_points(node0_sends);
+
+    let reports = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
+        .unwrap()
+        .reports;
+
+    for (id, report) in reports {
+        assert!(report.provable_errors.is_empty());
+        assert!(report.unprovable_errors.is_empty());
+        assert!(report.missing_messages.is_empty());
+
+        let mut senders = match report.outcome {
+            SessionOutcome::Result(senders) => senders,
+            _ => panic!("{id:?} did not reach a result"),
+        };
+
+        // The round could have been finalized before the optional message was delivered,
+        // but if it was, its payload must be passed to `finalize()`.
+        let optional_sender_included = senders.remove(&ids[0]);
+        assert!(node0_sends || !optional_sender_included);
+
+        let expected_senders = if id == ids[0] {
+            BTreeSet::new()
+        } else {
+            let mut others = required_senders.clone();
+            others.remove(&id);
+            others
+        };
+        assert_eq!(senders, expected_senders);
+    }
+}
+
+/// Executes the sessions round by round, delivering the messages from node 0 before all the others.
+fn run_optional_sender_first(
+    config: SessionConfig<TestVerifier>,
+) -> BTreeMap<TestVerifier, SessionOutcome<TestVerifier, OptionalSendersProtocol<TestVerifier>>> {
+    type SP = TestSessionParams<BinaryFormat>;
+
+    let node0 = TestSigner::new(0).verifying_key();
+    let session_id = SessionId::random::<SP>(&mut OsRng);
+    let mut sessions = make_entry_points(true)
+        .into_iter()
+        .map(|(signer, entry_point)| {
+            let session =
+                Session::<_, SP>::new_with_config(&mut OsRng, session_id.clone(), signer, entry_point, config.clone())
+                    .unwrap();
+            (session, Vec::new())
+        })
+        .collect::<Vec<_>>();
+
+    let mut outcomes = BTreeMap::new();
+    while !sessions.is_empty() {
+        let mut accums = sessions
+            .iter()
+            .map(|(session, _cached)| session.make_accumulator())
+            .collect::<Vec<_>>();
+
+        let mut messages = Vec::new();
+        for ((session, cached), accum) in sessions.iter_mut().zip(accums.iter_mut()) {
+            for message in cached.drain(..) {
+                let processed = session.process_message(message);
+                session.add_processed_message(accum, processed).unwrap();
+            }
+            for destination in session.message_destinations() {
+                let (message, artifact) = session.make_message(&mut OsRng, destination).unwrap();
+                session.add_artifact(accum, artifact).unwrap();
+                messages.push((session.verifier(), *destination, message));
+            }
+        }
+
+        // `sort_by_key` is stable, so the order of the other messages is preserved.
+        messages.sort_by_key(|(from, _to, _message)| *from != node0);
+        for (from, to, message) in messages {
+            let Some(idx) = sessions.iter().position(|(session, _cached)| session.verifier() == to) else {
+                continue;
+            };
+            let session = &sessions[idx].0;
+            if let Some(verified) = session
+                .preprocess_message(&mut accums[idx], &from, message)
+                .unwrap()
+                .ok()
+            {
+                let processed = session.process_message(verified);
+                session.add_processed_message(&mut accums[idx], processed).unwrap();
+            }
+        }
+
+        let mut next_sessions = Vec::new();
+        for ((session, _cached), accum) in sessions.into_iter().zip(accums) {
+            assert!(matches!(session.can_finalize(&accum), CanFinalize::Yes));
+            let id = session.verifier();
+            match session.finalize_round(&mut OsRng, accum).unwrap() {
+                RoundOutcome::Finished(report) => {
+                    outcomes.insert(id, report.outcome);
+                }
+                RoundOutcome::AnotherRound {
+                    session,
+                    cached_messages,
+                } => next_sessions.push((session, cached_messages)),
+            }
+        }
+        sessions = next_sessions;
+    }
+
+    outcomes
+}
+
+fn check_optional_sender_first(config: SessionConfig<TestVerifier>) {
+    let node0 = TestSigner::new(0).verifying_key();
+    for (id, outcome) in run_optional_sender_first(config) {
+        let senders = match outcome {
+            SessionOutcome::Result(senders) => senders,
+            _ => panic!("{id:?} did not reach a result"),
+        };
+        // The optional message was delivered before the round could be finalized,
+        // so its payload must reach `finalize()`.
+        assert_eq!(senders.contains(&node0), id != node0);
+    }
+}
+
+#[test]
+fn silent_optional_sender() {
+    run_optional_senders(SessionConfig::default(), false);
+    run_optional_senders(
+        SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::MerkleEcho),
+        false,
+    );
+}
+
+#[test]
+fn optional_sender() {
+    run_optional_senders(SessionConfig::default(), true);
+    run_optional_senders(
+        SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::MerkleEcho),
+        true,
+    );
+}
+
+#[test]
+fn optional_sender_delivered_first() {
+    check_optional_sender_first(SessionConfig::default());
+    check_optional_sender_first(SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::MerkleEcho));
+}
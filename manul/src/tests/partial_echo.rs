@@ -10,12 +10,13 @@ use rand_core::{CryptoRngCore, OsRng};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    dev::{run_sync, BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
+    dev::{run_sync_with_config, BinaryFormat, TestSessionParams, TestSigner, TestVerifier},
     protocol::{
         Artifact, BoxedFormat, BoxedRound, CommunicationInfo, DirectMessage, EchoBroadcast, EchoRoundParticipation,
         EntryPoint, FinalizeOutcome, LocalError, MessageValidationError, NoProtocolErrors, NormalBroadcast, PartyId,
         Payload, Protocol, ProtocolMessage, ProtocolMessagePart, ReceiveError, Round, RoundId, TransitionInfo,
     },
+    session::{BroadcastConsistency, SessionConfig},
     signature::Keypair,
 };
 
@@ -97,6 +98,7 @@ impl<Id: PartyId + Serialize + for<'de> Deserialize<'de>> Round<Id> for Round1<I
         CommunicationInfo {
             message_destinations: self.inputs.message_destinations.clone(),
             expecting_messages_from: self.inputs.expecting_messages_from.clone(),
+            optional_messages_from: BTreeSet::new(),
             echo_round_participation: self.inputs.echo_round_participation.clone(),
         }
     }
@@ -148,8 +150,7 @@ impl<Id: PartyId + Serialize + for<'de> Deserialize<'de>> Round<Id> for Round1<I
     }
 }
 
-#[test]
-fn partial_echo() {
+fn run_partial_echo(config: SessionConfig<TestVerifier>) {
     let signers = (0..5).map(TestSigner::new).collect::<Vec<_>>();
     let ids = signers.iter().map(|signer| signer.verifying_key()).collect::<Vec<_>>();
 
@@ -210,8 +211,28 @@ fn partial_echo() {
 
     let entry_points = vec![node0, node1, node2, node3, node4];
 
-    let _results = run_sync::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points)
+    let _results = run_sync_with_config::<_, TestSessionParams<BinaryFormat>>(&mut OsRng, entry_points, config)
         .unwrap()
         .results()
         .unwrap();
 }
+
+#[test]
+fn partial_echo() {
+    run_partial_echo(SessionConfig::default());
+}
+
+#[test]
+fn partial_echo_with_merkle_echo() {
+    run_partial_echo(SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::MerkleEcho));
+}
+
+#[test]
+fn partial_echo_with_bracha() {
+    run_partial_echo(SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::Bracha));
+}
+
+#[test]
+fn partial_echo_with_piggybacked_echo() {
+    run_partial_echo(SessionConfig::default().with_broadcast_consistency(BroadcastConsistency::PiggybackedEcho));
+}
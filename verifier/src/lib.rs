@@ -0,0 +1,309 @@
+//! Verification of the evidence of malicious behavior produced by `manul` sessions.
+//!
+//! The evidence is self-contained, but its verification requires the knowledge of the concrete protocol type
+//! and [`SessionParameters`] used in the session.
+//! A [`Registry`] maps the names supplied at runtime to these types.
+//! Protocols make themselves available to it by implementing [`VerifiableProtocol`].
+//!
+//! [`run`] implements a command-line interface on top of a registry.
+//!
+//! This crate does not know about any concrete protocols, so applications ship their own binary
+//! with the protocols they use added to the registry.
+//! The `manul-verifier` binary built with the `example` feature is an example of that,
+//! registering the protocols from `manul-example`.
+
+#![warn(
+    clippy::mod_module_files,
+    clippy::unwrap_used,
+    missing_docs,
+    missing_copy_implementations,
+    rust_2018_idioms,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_qualifications,
+    missing_debug_implementations
+)]
+
+use std::{collections::BTreeMap, fmt, fs, marker::PhantomData, process::ExitCode};
+
+use manul::{
+    protocol::{ProtocolError, VerifiableProtocol},
+    session::{Evidence, EvidenceError, SessionParameters, WireFormat},
+};
+use serde::de::value::{Error as ValueError, UnitDeserializer};
+
+/// Errors preventing the evidence from being verified.
+#[derive(displaydoc::Display, Debug, Clone)]
+pub enum Error {
+    /// Unknown format: {0}
+    UnknownFormat(String),
+    /// Unknown combination of the protocol `{0}` and session parameters `{1}`
+    UnknownProtocol(String, String),
+    /// Failed to deserialize {0}: {1}
+    Deserialization(&'static str, String),
+    /// Local error: {0}
+    Local(String),
+}
+
+/// The result of evidence verification.
+#[derive(Debug, Clone)]
+pub struct Verdict {
+    /// The party the evidence accuses of malicious behavior.
+    pub guilty_party: String,
+    /// The description of the offense.
+    pub description: String,
+    /// The round in which the offense was committed, if the evidence is well-formed enough to determine it.
+    pub round_id: Option<String>,
+    /// `Ok(())` if the evidence proves the offense, or the reason why it does not.
+    pub result: Result<(), String>,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.result {
+            Ok(()) => writeln!(f, "Evidence is VALID")?,
+            Err(reason) => writeln!(f, "Evidence is INVALID: {reason}")?,
+        }
+        writeln!(f, "Guilty party: {}", self.guilty_party)?;
+        writeln!(f, "Offense: {}", self.description)?;
+        write!(
+            f,
+            "Committed in: {}",
+            self.round_id.as_deref().unwrap_or("unknown round")
+        )
+    }
+}
+
+trait FileFormat {
+    fn deserializer<'de>(&self, bytes: &'de [u8]) -> Box<dyn erased_serde::Deserializer<'de> + 'de>;
+}
+
+struct FileFormatWrapper<F>(PhantomData<fn(F)>);
+
+impl<F: WireFormat> FileFormat for FileFormatWrapper<F> {
+    fn deserializer<'de>(&self, bytes: &'de [u8]) -> Box<dyn erased_serde::Deserializer<'de> + 'de> {
+        Box::new(<dyn erased_serde::Deserializer<'_>>::erase(F::deserializer(bytes)))
+    }
+}
+
+trait ErasedVerifier {
+    fn verify(
+        &self,
+        format: &dyn FileFormat,
+        evidence: &[u8],
+        associated_data: Option<&[u8]>,
+    ) -> Result<Verdict, Error>;
+}
+
+struct VerifierWrapper<P, SP>(PhantomData<fn(P, SP)>);
+
+impl<P, SP> ErasedVerifier for VerifierWrapper<P, SP>
+where
+    SP: SessionParameters,
+    P: VerifiableProtocol<SP::Verifier>,
+{
+    fn verify(
+        &self,
+        format: &dyn FileFormat,
+        evidence: &[u8],
+        associated_data: Option<&[u8]>,
+    ) -> Result<Verdict, Error> {
+        let mut deserializer = format.deserializer(evidence);
+        let evidence = erased_serde::deserialize::<Evidence<P, SP>>(&mut deserializer)
+            .map_err(|err| Error::Deserialization("evidence", err.to_string()))?;
+
+        let associated_data: <P::ProtocolError as ProtocolError<SP::Verifier>>::AssociatedData = match associated_data {
+            Some(bytes) => {
+                let mut deserializer = format.deserializer(bytes);
+                P::deserialize_associated_data(&mut *deserializer)
+                    .map_err(|err| Error::Deserialization("associated data", err.to_string()))?
+            }
+            None => P::deserialize_associated_data(UnitDeserializer::<ValueError>::new())
+                .map_err(|err| Error::Deserialization("associated data", err.to_string()))?,
+        };
+
+        let result = match evidence.verify(&associated_data) {
+            Ok(()) => Ok(()),
+            Err(EvidenceError::InvalidEvidence(reason)) => Err(reason),
+            Err(EvidenceError::Local(error)) => return Err(Error::Local(error.to_string())),
+        };
+
+        Ok(Verdict {
+            guilty_party: format!("{:?}", evidence.guilty_party()),
+            description: evidence.description().into(),
+            round_id: evidence.round_id().map(|round_id| round_id.to_string()),
+            result,
+        })
+    }
+}
+
+/// A mapping of names to protocols, session parameters, and formats of the evidence files.
+pub struct Registry {
+    formats: BTreeMap<String, Box<dyn FileFormat>>,
+    verifiers: BTreeMap<(String, String), Box<dyn ErasedVerifier>>,
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field("formats", &self.formats.keys().collect::<Vec<_>>())
+            .field("verifiers", &self.verifiers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Registry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            formats: BTreeMap::new(),
+            verifiers: BTreeMap::new(),
+        }
+    }
+
+    /// Makes the format `F` available to deserialize the evidence files under the given name.
+    pub fn add_format<F: WireFormat>(&mut self, name: &str) -> &mut Self {
+        self.formats
+            .insert(name.into(), Box::new(FileFormatWrapper::<F>(PhantomData)));
+        self
+    }
+
+    /// Makes the evidence of the protocol `P` executed with the session parameters `SP` verifiable,
+    /// with the latter available under the given name.
+    pub fn add_protocol<P, SP>(&mut self, session_parameters: &str) -> &mut Self
+    where
+        SP: SessionParameters,
+        P: VerifiableProtocol<SP::Verifier>,
+    {
+        self.verifiers.insert(
+            (P::NAME.into(), session_parameters.into()),
+            Box::new(VerifierWrapper::<P, SP>(PhantomData)),
+        );
+        self
+    }
+
+    /// Verifies the serialized evidence (and the associated data, if the protocol requires it).
+    pub fn verify(
+        &self,
+        protocol: &str,
+        session_parameters: &str,
+        format: &str,
+        evidence: &[u8],
+        associated_data: Option<&[u8]>,
+    ) -> Result<Verdict, Error> {
+        let file_format = self
+            .formats
+            .get(format)
+            .ok_or_else(|| Error::UnknownFormat(format.into()))?;
+        let verifier = self
+            .verifiers
+            .get(&(protocol.into(), session_parameters.into()))
+            .ok_or_else(|| Error::UnknownProtocol(protocol.into(), session_parameters.into()))?;
+        verifier.verify(file_format.as_ref(), evidence, associated_data)
+    }
+
+    fn usage(&self) -> String {
+        let mut usage = String::from(
+            "Usage: manul-verifier --protocol <NAME> --session-parameters <NAME> --format <NAME> \
+            --evidence <FILE> [--associated-data <FILE>]\n\nAvailable protocols and session parameters:\n",
+        );
+        for (protocol, session_parameters) in self.verifiers.keys() {
+            usage += &format!("  {protocol} {session_parameters}\n");
+        }
+        usage += "\nAvailable formats:\n";
+        for format in self.formats.keys() {
+            usage += &format!("  {format}\n");
+        }
+        usage
+    }
+}
+
+#[derive(Default)]
+struct Args {
+    protocol: Option<String>,
+    session_parameters: Option<String>,
+    format: Option<String>,
+    evidence: Option<String>,
+    associated_data: Option<String>,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let field = match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--protocol" => &mut parsed.protocol,
+            "--session-parameters" => &mut parsed.session_parameters,
+            "--format" => &mut parsed.format,
+            "--evidence" => &mut parsed.evidence,
+            "--associated-data" => &mut parsed.associated_data,
+            _ => return Err(format!("Unexpected argument: {arg}")),
+        };
+        let value = args.next().ok_or_else(|| format!("Missing the value for {arg}"))?;
+        *field = Some(value);
+    }
+    Ok(Some(parsed))
+}
+
+fn run_inner(registry: &Registry, args: Args) -> Result<Verdict, String> {
+    let protocol = args.protocol.ok_or("--protocol is required")?;
+    let session_parameters = args.session_parameters.ok_or("--session-parameters is required")?;
+    let format = args.format.ok_or("--format is required")?;
+    let evidence_path = args.evidence.ok_or("--evidence is required")?;
+
+    let evidence = fs::read(&evidence_path).map_err(|err| format!("Failed to read {evidence_path}: {err}"))?;
+    let associated_data = args
+        .associated_data
+        .map(|path| fs::read(&path).map_err(|err| format!("Failed to read {path}: {err}")))
+        .transpose()?;
+
+    registry
+        .verify(
+            &protocol,
+            &session_parameters,
+            &format,
+            &evidence,
+            associated_data.as_deref(),
+        )
+        .map_err(|err| err.to_string())
+}
+
+/// Runs the command-line interface with the given registry and arguments (not including the program name).
+///
+/// Prints the verdict and returns the exit code `0` if the evidence is valid, `1` if it is invalid,
+/// and `2` if it could not be verified (e.g. because of invalid arguments or unreadable files).
+pub fn run(registry: &Registry, args: impl IntoIterator<Item = String>) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", registry.usage());
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("{err}\n\n{}", registry.usage());
+            return ExitCode::from(2);
+        }
+    };
+
+    match run_inner(registry, args) {
+        Ok(verdict) => {
+            println!("{verdict}");
+            if verdict.result.is_ok() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(1)
+            }
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! An example evidence verifier for the protocols from `manul-example`,
+//! executed with the testing session parameters.
+
+use std::process::ExitCode;
+
+use manul::dev::{BinaryFormat, HumanReadableFormat, TestSessionParams};
+use manul_example::simple::SimpleProtocol;
+use manul_verifier::Registry;
+
+fn main() -> ExitCode {
+    let mut registry = Registry::new();
+    registry
+        .add_format::<BinaryFormat>("binary")
+        .add_format::<HumanReadableFormat>("human-readable")
+        .add_protocol::<SimpleProtocol, TestSessionParams<BinaryFormat>>("test-binary")
+        .add_protocol::<SimpleProtocol, TestSessionParams<HumanReadableFormat>>("test-human-readable");
+    manul_verifier::run(&registry, std::env::args().skip(1))
+}
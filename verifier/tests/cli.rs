@@ -0,0 +1,127 @@
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+use manul::{
+    combinators::misbehave::{Misbehaving, MisbehavingEntryPoint},
+    dev::{run_sync, HumanReadableFormat, TestSessionParams, TestSigner, TestVerifier},
+    protocol::{Artifact, BoxedFormat, BoxedRound, DirectMessage, EntryPoint, LocalError, ProtocolMessagePart},
+    session::{Evidence, WireFormat},
+    signature::Keypair,
+};
+use manul_example::simple::{SimpleProtocol, SimpleProtocolEntryPoint};
+use rand_core::{CryptoRngCore, OsRng};
+
+type SP = TestSessionParams<HumanReadableFormat>;
+
+/// Sends a direct message that cannot be deserialized, which is a provable error.
+struct SerializedGarbage;
+
+impl Misbehaving<TestVerifier, ()> for SerializedGarbage {
+    type EntryPoint = SimpleProtocolEntryPoint<TestVerifier>;
+
+    fn modify_direct_message(
+        _rng: &mut dyn CryptoRngCore,
+        _round: &BoxedRound<TestVerifier, <Self::EntryPoint as EntryPoint<TestVerifier>>::Protocol>,
+        _behavior: &(),
+        format: &BoxedFormat,
+        _destination: &TestVerifier,
+        _direct_message: DirectMessage,
+        artifact: Option<Artifact>,
+    ) -> Result<(DirectMessage, Option<Artifact>), LocalError> {
+        Ok((DirectMessage::new(format, [99u8])?, artifact))
+    }
+}
+
+fn make_evidence() -> Evidence<SimpleProtocol, SP> {
+    let signers = (0..3).map(TestSigner::new).collect::<Vec<_>>();
+    let all_ids = signers
+        .iter()
+        .map(|signer| signer.verifying_key())
+        .collect::<BTreeSet<_>>();
+
+    let entry_points = signers
+        .iter()
+        .map(|signer| {
+            let behavior = if signer.verifying_key().id() == 0 {
+                Some(())
+            } else {
+                None
+            };
+            let entry_point = MisbehavingEntryPoint::<TestVerifier, (), SerializedGarbage>::new(
+                SimpleProtocolEntryPoint::new(all_ids.clone()),
+                behavior,
+            );
+            (*signer, entry_point)
+        })
+        .collect::<Vec<_>>();
+
+    let mut reports = run_sync::<_, SP>(&mut OsRng, entry_points).unwrap().reports;
+    let mut report = reports.remove(&TestVerifier::new(1)).unwrap();
+    report.provable_errors.remove(&TestVerifier::new(0)).unwrap()
+}
+
+fn write_temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("manul-verifier-{}-{name}", std::process::id()));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn run_verifier(evidence_path: &PathBuf) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_manul-verifier"))
+        .args(["--protocol", "simple"])
+        .args(["--session-parameters", "test-human-readable"])
+        .args(["--format", "human-readable"])
+        .arg("--evidence")
+        .arg(evidence_path)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn valid_evidence() {
+    let evidence = HumanReadableFormat::serialize(make_evidence()).unwrap();
+    let path = write_temp_file("valid.json", &evidence);
+
+    let output = run_verifier(&path);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(0), "{stdout}");
+    assert!(stdout.contains("Evidence is VALID"));
+    assert!(stdout.contains("Guilty party: TestVerifier(0)"));
+    assert!(stdout.contains("Committed in: Round 1"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn evidence_against_another_party() {
+    let evidence = HumanReadableFormat::serialize(make_evidence()).unwrap();
+    // Blame a party that did not sign the attached message.
+    let evidence = String::from_utf8(evidence.into()).unwrap();
+    let tampered = evidence.replacen(r#""guilty_party":0"#, r#""guilty_party":2"#, 1);
+    assert_ne!(evidence, tampered);
+    let path = write_temp_file("invalid.json", tampered.as_bytes());
+
+    let output = run_verifier(&path);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(output.status.code(), Some(1), "{stdout}");
+    assert!(stdout.contains("Evidence is INVALID"));
+    assert!(stdout.contains("Guilty party: TestVerifier(2)"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn unknown_protocol() {
+    let output = Command::new(env!("CARGO_BIN_EXE_manul-verifier"))
+        .args(["--protocol", "unknown"])
+        .args(["--session-parameters", "test-binary"])
+        .args(["--format", "binary"])
+        .args(["--evidence", "nonexistent"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}